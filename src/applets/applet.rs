@@ -1,15 +1,32 @@
+use super::context::EventContext;
 use crate::AppState;
 use crate::db::inventory::Inventory;
-use ratatui::DefaultTerminal;
+use crate::events::AppEvent;
+use ratatui::Frame;
 
 pub trait Applet {
-    fn run(
+    /// Draws the applet over the whole frame. The `App` draws its overlays on top afterwards.
+    fn render(&mut self, frame: &mut Frame);
+    /// Reacts to an event the `App` and its overlays did not use.
+    fn handle_event(
         &mut self,
-        _terminal: &mut DefaultTerminal,
-        _db: &Inventory,
+        _event: &AppEvent,
+        _ctx: &mut EventContext,
     ) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
+    /// The name shown for this applet in the breadcrumb bar.
+    fn title(&self) -> String;
     fn get_next_state(&self) -> AppState;
+    /// Asked before the `App` closes this applet to go further back, e.g. to a breadcrumb.
+    /// Returns false to stay open, in which case the applet asks for `AppState::Exit` itself
+    /// once it is ready to go.
+    fn request_leave(&mut self) -> bool {
+        true
+    }
+    /// True while the applet waits for an answer after refusing `request_leave`.
+    fn is_asking_to_leave(&self) -> bool {
+        false
+    }
     fn refresh(&mut self, _db: &Inventory) {}
 }
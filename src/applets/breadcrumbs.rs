@@ -0,0 +1,218 @@
+use super::mouse;
+use crate::keymap::{self, Action, Context};
+use crate::theme;
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListState, Padding, Paragraph};
+
+const SEPARATOR: &str = " › ";
+
+/// The path from the dashboard to the current applet, drawn over the top border of the screen.
+///
+/// Owned by the `App`, which sets the titles of the applet stack before each run and asks
+/// `take_jump` afterwards whether the user picked an ancestor to go back to, with the home key,
+/// a click on a crumb or the picker popup.
+#[derive(Default)]
+pub struct Breadcrumbs {
+    titles: Vec<String>,
+    /// Where each title was last drawn, for mouse hit testing.
+    areas: Vec<Rect>,
+    picker: Option<ListState>,
+    jump: Option<usize>,
+}
+
+impl Breadcrumbs {
+    pub fn set_titles(&mut self, titles: Vec<String>) {
+        self.titles = titles;
+    }
+
+    /// The depth of the applet the user asked to go back to, if any.
+    pub fn take_jump(&mut self) -> Option<usize> {
+        self.jump.take()
+    }
+
+    fn jump_to(&mut self, depth: usize) {
+        self.picker = None;
+        if depth + 1 < self.titles.len() {
+            self.jump = Some(depth);
+        }
+    }
+
+    fn pressed(action: Action, key: KeyEvent) -> bool {
+        keymap::current().matches(action, Context::List, key)
+            || keymap::current().matches(action, Context::Form, key)
+    }
+
+    /// Handles the home and breadcrumb keys, clicks on a crumb, and every event while the picker
+    /// is open. Returns true if the event was used.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Mouse(mouse) if mouse::is_left_click(mouse) => {
+                if let Some(depth) = self.areas.iter().position(|a| mouse::contains(*a, mouse)) {
+                    self.jump_to(depth);
+                    return true;
+                }
+                self.picker.take().is_some()
+            }
+            Event::Key(key) if key.is_press() => self.handle_key(*key),
+            _ => self.picker.is_some(),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let Some(picker) = self.picker.as_mut() else {
+            if Self::pressed(Action::Home, key) {
+                self.jump_to(0);
+                return true;
+            }
+            if Self::pressed(Action::Breadcrumbs, key) {
+                if self.titles.len() > 1 {
+                    self.picker =
+                        Some(ListState::default().with_selected(Some(self.titles.len() - 2)));
+                }
+                return true;
+            }
+            return false;
+        };
+        match key.code {
+            _ if Self::pressed(Action::Breadcrumbs, key) => self.picker = None,
+            KeyCode::Esc | KeyCode::Char('q') => self.picker = None,
+            KeyCode::Up => picker.select_previous(),
+            KeyCode::Down => picker.select_next(),
+            KeyCode::Home => picker.select_first(),
+            KeyCode::Enter => {
+                let depth = picker.selected().unwrap_or(0);
+                self.jump_to(depth);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let bar = Rect::new(area.x + 1, area.y, area.width.saturating_sub(2), 1);
+        let last = self.titles.len().saturating_sub(1);
+        let mut spans = vec![Span::raw(" ")];
+        let mut x = bar.x + 1;
+        self.areas.clear();
+        for (i, title) in self.titles.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(SEPARATOR));
+                x += SEPARATOR.chars().count() as u16;
+            }
+            let width = title.chars().count() as u16;
+            self.areas
+                .push(Rect::new(x, bar.y, width, 1).intersection(bar));
+            let style = if i == last {
+                theme::current().focus.bold()
+            } else {
+                theme::current().text
+            };
+            spans.push(Span::styled(title.as_str(), style));
+            x += width;
+        }
+        spans.push(Span::raw(" "));
+        let line = Line::from(spans);
+        let width = (line.width() as u16).min(bar.width);
+        frame.render_widget(
+            Paragraph::new(line).style(theme::current().text),
+            Rect { width, ..bar },
+        );
+
+        let Some(picker) = self.picker.as_mut() else {
+            return;
+        };
+        let widest = self.titles.iter().map(|t| t.chars().count()).max();
+        let popup = Rect::new(
+            bar.x,
+            bar.y + 1,
+            widest.unwrap_or(0) as u16 + 4,
+            self.titles.len() as u16 + 2,
+        )
+        .intersection(area);
+        let list = List::new(self.titles.iter().map(String::as_str))
+            .block(
+                Block::bordered()
+                    .title("Go back to")
+                    .border_type(ratatui::widgets::BorderType::Thick)
+                    .padding(Padding::horizontal(1)),
+            )
+            .style(theme::current().text)
+            .highlight_style(theme::current().selection);
+        frame.render_widget(Clear, popup);
+        frame.render_stateful_widget(list, popup, picker);
+    }
+}
+
+#[cfg(test)]
+mod breadcrumbs_tests {
+    use super::*;
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn my_crumbs() -> Breadcrumbs {
+        let mut my_crumbs = Breadcrumbs::default();
+        my_crumbs.set_titles(vec![
+            "Dashboard".to_string(),
+            "List Items".to_string(),
+            "Edit Item 104".to_string(),
+        ]);
+        my_crumbs
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut my_crumbs = my_crumbs();
+        assert!(!my_crumbs.handle_event(&press(KeyCode::Char('h'), KeyModifiers::NONE)));
+        assert!(my_crumbs.handle_event(&press(KeyCode::Char('h'), KeyModifiers::ALT)));
+        assert_eq!(my_crumbs.take_jump(), Some(0));
+        assert_eq!(my_crumbs.take_jump(), None);
+
+        //the picker starts on the parent and takes every key until closed
+        assert!(my_crumbs.handle_event(&press(KeyCode::Char('b'), KeyModifiers::ALT)));
+        assert_eq!(my_crumbs.picker.as_ref().unwrap().selected(), Some(1));
+        assert!(my_crumbs.handle_event(&press(KeyCode::Char('x'), KeyModifiers::NONE)));
+        assert!(my_crumbs.handle_event(&press(KeyCode::Esc, KeyModifiers::NONE)));
+        assert!(my_crumbs.picker.is_none());
+        assert_eq!(my_crumbs.take_jump(), None);
+
+        my_crumbs.handle_event(&press(KeyCode::Char('b'), KeyModifiers::ALT));
+        my_crumbs.handle_event(&press(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(my_crumbs.take_jump(), Some(1));
+
+        //going back to the current applet does nothing
+        my_crumbs.set_titles(vec!["Dashboard".to_string()]);
+        assert!(my_crumbs.handle_event(&press(KeyCode::Char('h'), KeyModifiers::ALT)));
+        assert_eq!(my_crumbs.take_jump(), None);
+    }
+
+    #[test]
+    fn test_render_and_click() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        let mut my_crumbs = my_crumbs();
+        terminal.draw(|frame| my_crumbs.render(frame)).unwrap();
+        let top: String = terminal.backend().buffer().content()[..80]
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(top.contains("Dashboard › List Items › Edit Item 104"));
+
+        let list_items = my_crumbs.areas[1];
+        let click = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: list_items.x + 2,
+            row: list_items.y,
+            modifiers: KeyModifiers::NONE,
+        });
+        assert!(my_crumbs.handle_event(&click));
+        assert_eq!(my_crumbs.take_jump(), Some(1));
+    }
+}
@@ -0,0 +1,414 @@
+use super::modal::{Modal, ModalAction};
+use super::mouse;
+use crate::db::inventory::{Inventory, ItemChange};
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crate::validation;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Clear, List, ListState, Padding, Paragraph};
+
+const MOVE: usize = 0;
+const SET_COMMENT: usize = 1;
+const CLEAR_COMMENT: usize = 2;
+const ADD_TAG: usize = 3;
+const REMOVE_TAG: usize = 4;
+const DELETE: usize = 5;
+const CHOICES: &[&str] = &[
+    "Move to location",
+    "Set comment",
+    "Clear comment",
+    "Add tag",
+    "Remove tag",
+    "Delete",
+];
+const CONFIRM_BUTTONS: &[&str] = &["Apply", "Cancel"];
+/// How many item names the confirmation summary lists before "and N more".
+const PREVIEW_NAMES: usize = 3;
+
+#[derive(Debug, PartialEq)]
+pub enum BulkAction {
+    None,
+    /// The user confirmed the change.
+    Apply(ItemChange),
+    /// The editor was closed without changing anything.
+    Close,
+}
+
+enum Step {
+    Choose(ListState),
+    Input {
+        choice: usize,
+        value: String,
+        error: Option<String>,
+    },
+    Confirm {
+        change: ItemChange,
+        modal: Modal,
+    },
+}
+
+/// The popups that pick a bulk action for the marked items, ask for its value and confirm it
+/// with a summary of what will change.
+pub struct BulkEditor {
+    /// The names of the items the change applies to, for the summary.
+    names: Vec<String>,
+    step: Step,
+    keys: KeyReader,
+    list_area: Rect,
+}
+
+/// "1 item" or "N items".
+pub fn count_items(count: usize) -> String {
+    if count == 1 {
+        "1 item".to_string()
+    } else {
+        format!("{count} items")
+    }
+}
+
+/// The toast shown once `change` has been applied to `count` items.
+pub fn done_message(change: &ItemChange, count: usize) -> String {
+    let items = count_items(count);
+    match change {
+        ItemChange::Move(Some(id)) => format!("Moved {items} to location {id}"),
+        ItemChange::Move(None) => format!("Removed the location of {items}"),
+        ItemChange::SetComment(Some(_)) => format!("Set the comment of {items}"),
+        ItemChange::SetComment(None) => format!("Cleared the comment of {items}"),
+        ItemChange::AddTag(tag) => format!("Tagged {items} with \"{tag}\""),
+        ItemChange::RemoveTag(tag) => format!("Removed the tag \"{tag}\" from {items}"),
+        ItemChange::Delete => format!("Deleted {items}"),
+    }
+}
+
+impl BulkEditor {
+    pub fn new(names: Vec<String>) -> Self {
+        Self {
+            names,
+            step: Step::Choose(ListState::default().with_selected(Some(0))),
+            keys: KeyReader::default(),
+            list_area: Rect::default(),
+        }
+    }
+
+    /// Skips straight to confirming that the items should be deleted.
+    pub fn delete(names: Vec<String>) -> Self {
+        let mut editor = Self::new(names);
+        editor.confirm(ItemChange::Delete, String::new());
+        editor
+    }
+
+    fn confirm(&mut self, change: ItemChange, target: String) {
+        let modal = Modal::new(
+            "Confirm Bulk Change",
+            &self.summary(&change, &target),
+            CONFIRM_BUTTONS,
+        );
+        self.step = Step::Confirm { change, modal };
+    }
+
+    /// Describes what `change` will do, followed by the first few affected items. `target` names
+    /// the location items are moved to.
+    fn summary(&self, change: &ItemChange, target: &str) -> String {
+        let items = count_items(self.names.len());
+        let what = match change {
+            ItemChange::Move(Some(_)) => format!("Move {items} to {target}?"),
+            ItemChange::Move(None) => format!("Remove the location of {items}?"),
+            ItemChange::SetComment(Some(comment)) => {
+                format!("Set the comment of {items} to \"{comment}\"?")
+            }
+            ItemChange::SetComment(None) => format!("Clear the comment of {items}?"),
+            ItemChange::AddTag(tag) => format!("Add the tag \"{tag}\" to {items}?"),
+            ItemChange::RemoveTag(tag) => format!("Remove the tag \"{tag}\" from {items}?"),
+            ItemChange::Delete => format!("Delete {items}? This can not be undone."),
+        };
+        let mut preview = self.names[..self.names.len().min(PREVIEW_NAMES)].join(", ");
+        if self.names.len() > PREVIEW_NAMES {
+            preview += &format!(" and {} more", self.names.len() - PREVIEW_NAMES);
+        }
+        format!("{what}\n{preview}")
+    }
+
+    fn choose(&mut self, choice: usize) {
+        match choice {
+            CLEAR_COMMENT => self.confirm(ItemChange::SetComment(None), String::new()),
+            DELETE => self.confirm(ItemChange::Delete, String::new()),
+            _ => {
+                self.step = Step::Input {
+                    choice,
+                    value: String::new(),
+                    error: None,
+                }
+            }
+        }
+    }
+
+    /// Turns the typed value into a change, or an error to show under the input.
+    fn parse(choice: usize, value: &str, db: &Inventory) -> Result<(ItemChange, String), String> {
+        let value = value.trim();
+        match choice {
+            MOVE if value.is_empty() => Ok((ItemChange::Move(None), String::new())),
+            MOVE => {
+                let id = value
+                    .parse::<i64>()
+                    .map_err(|_| "Must be a whole number".to_string())?;
+                validation::location_id_exists(value, db)?;
+                let name = db
+                    .search_location_id(id)
+                    .map(|l| l.name)
+                    .unwrap_or_default();
+                Ok((ItemChange::Move(Some(id)), format!("{name} (#{id})")))
+            }
+            SET_COMMENT if value.is_empty() => Ok((ItemChange::SetComment(None), String::new())),
+            SET_COMMENT => Ok((
+                ItemChange::SetComment(Some(value.to_string())),
+                String::new(),
+            )),
+            _ if value.is_empty() => Err("Tag can not be empty".to_string()),
+            ADD_TAG => Ok((ItemChange::AddTag(value.to_string()), String::new())),
+            REMOVE_TAG => Ok((ItemChange::RemoveTag(value.to_string()), String::new())),
+            _ => Err(format!("{} needs no value", CHOICES[choice])),
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, db: &Inventory) -> BulkAction {
+        let action = self.keys.action(Context::Form, key);
+        match &mut self.step {
+            Step::Choose(list_state) => match (action, key.code) {
+                (Some(Action::Cancel), _) => return BulkAction::Close,
+                (Some(Action::NextField), _) | (_, KeyCode::Down) => list_state.select_next(),
+                (Some(Action::PreviousField), _) | (_, KeyCode::Up) => list_state.select_previous(),
+                (_, KeyCode::Enter) => {
+                    let choice = list_state.selected().unwrap_or(0);
+                    self.choose(choice);
+                }
+                _ => {}
+            },
+            Step::Input {
+                choice,
+                value,
+                error,
+            } => match (action, key.code) {
+                (Some(Action::Cancel), _) => {
+                    let choice = *choice;
+                    self.step = Step::Choose(ListState::default().with_selected(Some(choice)));
+                }
+                (_, KeyCode::Enter) => match Self::parse(*choice, value, db) {
+                    Ok((change, target)) => self.confirm(change, target),
+                    Err(e) => *error = Some(e),
+                },
+                (_, KeyCode::Backspace) => {
+                    value.pop();
+                    *error = None;
+                }
+                (_, KeyCode::Char(c))
+                    if !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    value.push(c);
+                    *error = None;
+                }
+                _ => {}
+            },
+            Step::Confirm { change, modal } => {
+                return Self::confirmed(change, modal.handle_key(key));
+            }
+        }
+        BulkAction::None
+    }
+
+    fn confirmed(change: &ItemChange, action: ModalAction) -> BulkAction {
+        match action {
+            ModalAction::Choose(0) => BulkAction::Apply(change.clone()),
+            ModalAction::Choose(_) | ModalAction::Dismiss => BulkAction::Close,
+            ModalAction::None => BulkAction::None,
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> BulkAction {
+        match &mut self.step {
+            Step::Choose(list_state) => {
+                if let Some(row) = mouse::row_at(self.list_area, 0, 0, &mouse)
+                    && mouse::is_left_click(&mouse)
+                    && row < CHOICES.len()
+                {
+                    list_state.select(Some(row));
+                    self.choose(row);
+                }
+                BulkAction::None
+            }
+            Step::Input { .. } => BulkAction::None,
+            Step::Confirm { change, modal } => Self::confirmed(change, modal.handle_mouse(mouse)),
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let title = format!("Change {}", count_items(self.names.len()));
+        match &mut self.step {
+            Step::Choose(list_state) => {
+                let area = frame.area().centered(
+                    Constraint::Max(40),
+                    Constraint::Length(CHOICES.len() as u16 + 2),
+                );
+                self.list_area = area;
+                let list = List::new(CHOICES.iter().copied())
+                    .block(
+                        Block::bordered()
+                            .title(title)
+                            .border_type(ratatui::widgets::BorderType::Thick)
+                            .padding(Padding::horizontal(1)),
+                    )
+                    .style(theme::current().text)
+                    .highlight_style(theme::current().selection)
+                    .highlight_symbol(">>");
+                frame.render_widget(Clear, area);
+                frame.render_stateful_widget(list, area, list_state);
+            }
+            Step::Input {
+                choice,
+                value,
+                error,
+            } => {
+                let area = frame
+                    .area()
+                    .centered(Constraint::Max(48), Constraint::Length(5));
+                let hint = match *choice {
+                    MOVE => "Location ID, empty for none",
+                    SET_COMMENT => "Comment, empty to clear",
+                    _ => "Tag",
+                };
+                let block = Block::bordered()
+                    .title(format!("{title} - {}", CHOICES[*choice]))
+                    .title_bottom("Enter to continue, Esc to go back")
+                    .border_type(ratatui::widgets::BorderType::Thick)
+                    .padding(Padding::horizontal(1));
+                let [hint_area, input_area, error_area] =
+                    Layout::vertical([Constraint::Length(1); 3]).areas(block.inner(area));
+                frame.render_widget(Clear, area);
+                frame.render_widget(block.style(theme::current().text), area);
+                frame.render_widget(Line::styled(hint, theme::current().disabled), hint_area);
+                frame.render_widget(
+                    Paragraph::new(value.as_str()).style(theme::current().focus),
+                    input_area,
+                );
+                if let Some(error) = error {
+                    frame.render_widget(
+                        Line::styled(error.as_str(), theme::current().error),
+                        error_area,
+                    );
+                }
+                frame.set_cursor_position(Position::new(
+                    input_area.x + value.chars().count() as u16,
+                    input_area.y,
+                ));
+            }
+            Step::Confirm { modal, .. } => modal.render(frame),
+        }
+    }
+}
+
+#[cfg(test)]
+mod bulk_tests {
+    use super::*;
+    use crate::db::inventory::Location;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn names(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("item{i}")).collect()
+    }
+
+    #[test]
+    fn test_move() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let garage = Location {
+            id: 7,
+            name: "Garage".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        assert!(my_inv.add_location(&garage).is_ok());
+        let mut my_editor = BulkEditor::new(names(5));
+        assert_eq!(
+            my_editor.handle_key(key(KeyCode::Enter), &my_inv),
+            BulkAction::None
+        );
+
+        //a missing location is reported under the input
+        for c in "8".chars() {
+            my_editor.handle_key(key(KeyCode::Char(c)), &my_inv);
+        }
+        my_editor.handle_key(key(KeyCode::Enter), &my_inv);
+        let Step::Input { error, .. } = &my_editor.step else {
+            panic!("expected the input step");
+        };
+        assert_eq!(error.as_deref(), Some("Location ID does not exist"));
+
+        my_editor.handle_key(key(KeyCode::Backspace), &my_inv);
+        my_editor.handle_key(key(KeyCode::Char('7')), &my_inv);
+        my_editor.handle_key(key(KeyCode::Enter), &my_inv);
+        assert!(matches!(my_editor.step, Step::Confirm { .. }));
+        assert_eq!(
+            my_editor.summary(&ItemChange::Move(Some(7)), "Garage (#7)"),
+            "Move 5 items to Garage (#7)?\nitem0, item1, item2 and 2 more"
+        );
+        assert_eq!(
+            my_editor.handle_key(key(KeyCode::Enter), &my_inv),
+            BulkAction::Apply(ItemChange::Move(Some(7)))
+        );
+    }
+
+    #[test]
+    fn test_choices() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let mut my_editor = BulkEditor::new(names(1));
+        assert_eq!(
+            my_editor.handle_key(key(KeyCode::Esc), &my_inv),
+            BulkAction::Close
+        );
+
+        //an empty tag is refused, and Esc goes back to the list of choices
+        for _ in 0..3 {
+            my_editor.handle_key(key(KeyCode::Down), &my_inv);
+        }
+        my_editor.handle_key(key(KeyCode::Enter), &my_inv);
+        assert!(matches!(
+            my_editor.step,
+            Step::Input {
+                choice: ADD_TAG,
+                ..
+            }
+        ));
+        my_editor.handle_key(key(KeyCode::Enter), &my_inv);
+        assert!(matches!(my_editor.step, Step::Input { error: Some(_), .. }));
+        my_editor.handle_key(key(KeyCode::Esc), &my_inv);
+        assert!(matches!(my_editor.step, Step::Choose(_)));
+
+        my_editor.handle_key(key(KeyCode::Up), &my_inv);
+        my_editor.handle_key(key(KeyCode::Enter), &my_inv);
+        let Step::Confirm { change, .. } = &my_editor.step else {
+            panic!("expected the confirm step");
+        };
+        assert_eq!(*change, ItemChange::SetComment(None));
+        assert_eq!(
+            my_editor.summary(change, ""),
+            "Clear the comment of 1 item?\nitem0"
+        );
+        my_editor.handle_key(key(KeyCode::Right), &my_inv);
+        assert_eq!(
+            my_editor.handle_key(key(KeyCode::Enter), &my_inv),
+            BulkAction::Close
+        );
+
+        let mut my_editor = BulkEditor::delete(names(2));
+        assert_eq!(
+            my_editor.handle_key(key(KeyCode::Enter), &my_inv),
+            BulkAction::Apply(ItemChange::Delete)
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use super::notifications::{Level, Notifications};
+use crate::AppState;
+use crate::db::inventory::Inventory;
+use crate::events::AppEvent;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// What an applet gets to work with while handling an event.
+///
+/// Lets applets reach the database, raise notifications owned by the `App`, and start jobs in
+/// the background without blocking the event loop.
+pub struct EventContext<'a> {
+    pub db: &'a Inventory,
+    notifications: &'a mut Notifications,
+    worker: Sender<AppEvent>,
+    /// The state of the applet handling the event, so jobs it spawns can find it again.
+    owner: AppState,
+}
+
+impl<'a> EventContext<'a> {
+    pub fn new(
+        db: &'a Inventory,
+        notifications: &'a mut Notifications,
+        worker: Sender<AppEvent>,
+        owner: AppState,
+    ) -> Self {
+        Self {
+            db,
+            notifications,
+            worker,
+            owner,
+        }
+    }
+
+    pub fn notify(&mut self, level: Level, text: impl Into<String>) {
+        self.notifications.push(level, text);
+    }
+
+    /// Runs `job` on another thread. Its result comes back as an `AppEvent::Worker` event, which
+    /// the `App` hands to the applet that spawned it even if another one is on top by then.
+    pub fn spawn<T: Send + 'static>(&self, job: impl FnOnce() -> T + Send + 'static) {
+        let worker = self.worker.clone();
+        let owner = self.owner.clone();
+        thread::spawn(move || {
+            let _ = worker.send(AppEvent::Worker(owner, Box::new(job())));
+        });
+    }
+}
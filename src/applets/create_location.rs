@@ -1,330 +1,118 @@
 use super::applet::Applet;
+use super::context::EventContext;
+use super::form::{Field, Form, FormAction};
+use super::notifications::Level;
 use crate::AppState;
 use crate::db::inventory;
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
-use ratatui::layout::{Constraint, Layout, Position};
-use ratatui::style::Style;
-use ratatui::widgets::{Block, Padding, Paragraph};
-use std::error;
-use std::fmt;
+use crate::events::AppEvent;
+use crate::validation;
+use crossterm::event::Event;
+use ratatui::Frame;
+
+const ID: usize = 0;
+const NAME: usize = 1;
+const COMMENT: usize = 2;
+const PARENT: usize = 3;
 
 pub struct CreateLocationApplet {
     next_state: AppState,
-    id: String,
-    name: String,
-    comment: String,
-    selection: CreateLocationSelection,
-    cursor_position: usize,
-}
-
-#[derive(Debug)]
-struct CreateLocationError {
-    error_text: String,
-}
-
-impl fmt::Display for CreateLocationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Create Item Error: {}", self.error_text)
-    }
-}
-
-impl error::Error for CreateLocationError {}
-
-impl CreateLocationError {
-    fn new(msg: &str) -> Box<CreateLocationError> {
-        Box::new(CreateLocationError {
-            error_text: msg.to_string(),
-        })
-    }
-}
-#[derive(Debug, PartialEq)]
-enum CreateLocationSelection {
-    Id,
-    Name,
-    Comment,
-    Cancel,
-    Save,
-}
-
-impl CreateLocationSelection {
-    fn next(&self) -> Self {
-        match self {
-            CreateLocationSelection::Id => CreateLocationSelection::Name,
-            CreateLocationSelection::Name => CreateLocationSelection::Comment,
-            CreateLocationSelection::Comment => CreateLocationSelection::Cancel,
-            CreateLocationSelection::Cancel => CreateLocationSelection::Save,
-            CreateLocationSelection::Save => CreateLocationSelection::Id,
-        }
-    }
-    fn previous(&self) -> Self {
-        match self {
-            CreateLocationSelection::Id => CreateLocationSelection::Save,
-            CreateLocationSelection::Name => CreateLocationSelection::Id,
-            CreateLocationSelection::Comment => CreateLocationSelection::Name,
-            CreateLocationSelection::Cancel => CreateLocationSelection::Comment,
-            CreateLocationSelection::Save => CreateLocationSelection::Cancel,
-        }
-    }
+    form: Form,
 }
 
 impl CreateLocationApplet {
     pub fn new() -> Self {
         Self {
             next_state: AppState::NoChange,
-            id: String::new(),
-            name: String::new(),
-            comment: String::new(),
-            selection: CreateLocationSelection::Id,
-            cursor_position: 0,
+            form: Form::new("Create Location", save_location)
+                .field(
+                    Field::integer("Location ID")
+                        .required()
+                        .validator(validation::location_id_unused),
+                )
+                .field(Field::text("Name").required())
+                .field(Field::text("Comment").grow())
+                .field(Field::integer("Parent ID").validator(validation::location_id_exists)),
         }
     }
-    fn save_location(&self, db: &inventory::Inventory) -> Result<(), Box<dyn std::error::Error>> {
-        //Check ID
-        let id = self
-            .id
-            .parse::<i64>()
-            .map_err(|_| CreateLocationError::new("Failed to parse Location ID"))?;
-        if db.item_exists(id) {
-            return Err(CreateLocationError::new("Location ID already exists"));
-        }
-
-        //check Name
-        if self.name.is_empty() {
-            return Err(CreateLocationError::new("Name cannot be empty"));
-        }
-
-        //check comment
-        let comment_opt = if self.comment.is_empty() {
-            None
-        } else {
-            Some(self.comment.clone())
-        };
+}
 
-        let new_location = inventory::Location {
-            id,
-            name: self.name.clone(),
-            comment: comment_opt,
-        };
-        db.add_location(&new_location)?;
-        Ok(())
-    }
+fn save_location(form: &Form, db: &inventory::Inventory) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(id) = form.integer(ID) else {
+        return Err(form.error("Failed to parse Location ID"));
+    };
+    let new_location = inventory::Location {
+        id,
+        name: form.value(NAME).to_string(),
+        comment: form.optional_text(COMMENT),
+        parent_id: form.integer(PARENT),
+    };
+    db.add_location(&new_location)?;
+    Ok(())
 }
 
 impl Applet for CreateLocationApplet {
-    fn run(
+    fn render(&mut self, frame: &mut Frame) {
+        self.form.render(frame);
+    }
+
+    fn handle_event(
         &mut self,
-        terminal: &mut DefaultTerminal,
-        db: &inventory::Inventory,
+        event: &AppEvent,
+        ctx: &mut EventContext,
     ) -> Result<(), Box<dyn std::error::Error>> {
         self.next_state = AppState::NoChange;
-
-        //Prepare Draw
-        let vertical = Layout::vertical([
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Min(1),
-            Constraint::Length(3),
-            Constraint::Length(3),
-        ]);
-        let border = Block::bordered()
-            .title_top("Inventory Manager - Create Location")
-            .title_bottom("Press 'q' or Esc to exit")
-            .border_type(ratatui::widgets::BorderType::Thick)
-            .padding(Padding::horizontal(1));
-        let id_widget = Paragraph::new(self.id.as_str())
-            .style(if self.selection == CreateLocationSelection::Id {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Location ID"));
-        let name_widget = Paragraph::new(self.name.as_str())
-            .style(if self.selection == CreateLocationSelection::Name {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Name"));
-        let comment_widget = Paragraph::new(self.comment.as_str())
-            .style(if self.selection == CreateLocationSelection::Comment {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Comment"));
-        let cancel_button = Paragraph::new("Cancel".to_string())
-            .style(if self.selection == CreateLocationSelection::Cancel {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered());
-        let save_button = Paragraph::new("Save Changes".to_string())
-            .style(if self.selection == CreateLocationSelection::Save {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered());
-
-        terminal.draw(|frame| {
-            let inner_area = border.inner(frame.area());
-            let [id_area, name_area, comment_area, cancel_area, save_area] =
-                vertical.areas(inner_area);
-            frame.render_widget(border, frame.area());
-            frame.render_widget(id_widget, id_area);
-            frame.render_widget(name_widget, name_area);
-            frame.render_widget(comment_widget, comment_area);
-            frame.render_widget(cancel_button, cancel_area);
-            frame.render_widget(save_button, save_area);
-            match self.selection {
-                CreateLocationSelection::Id => {
-                    frame.set_cursor_position(Position::new(
-                        id_area.x + self.cursor_position as u16 + 1,
-                        id_area.y + 1,
-                    ));
-                }
-                CreateLocationSelection::Name => {
-                    frame.set_cursor_position(Position::new(
-                        name_area.x + self.cursor_position as u16 + 1,
-                        name_area.y + 1,
-                    ));
-                }
-                CreateLocationSelection::Comment => {
-                    frame.set_cursor_position(Position::new(
-                        comment_area.x + self.cursor_position as u16 + 1,
-                        comment_area.y + 1,
-                    ));
-                }
-                _ => (),
-            }
-        })?;
-
-        //Handle Input
-        if let Some(key) = event::read()?.as_key_press_event() {
-            match self.selection {
-                CreateLocationSelection::Id => match key.code {
-                    KeyCode::Char(c) => {
-                        self.id.insert(self.cursor_position, c);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            self.id.remove(self.cursor_position);
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.id.len() {
-                            self.id.remove(self.cursor_position);
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position =
-                            self.cursor_position.saturating_add(1).min(self.id.len())
-                    }
-                    _ => {}
-                },
-                CreateLocationSelection::Name => match key.code {
-                    KeyCode::Char(c) => {
-                        self.name.insert(self.cursor_position, c);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            self.name.remove(self.cursor_position);
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.name.len() {
-                            self.name.remove(self.cursor_position);
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position =
-                            self.cursor_position.saturating_add(1).min(self.name.len())
-                    }
-                    _ => {}
-                },
-
-                CreateLocationSelection::Comment => match key.code {
-                    KeyCode::Char(c) => {
-                        self.comment.insert(self.cursor_position, c);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            self.comment.remove(self.cursor_position);
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.comment.len() {
-                            self.comment.remove(self.cursor_position);
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position = self
-                            .cursor_position
-                            .saturating_add(1)
-                            .min(self.comment.len())
-                    }
-                    _ => {}
-                },
-                CreateLocationSelection::Cancel => match key.code {
-                    KeyCode::Enter => self.next_state = AppState::Exit,
-                    _ => (),
-                },
-                CreateLocationSelection::Save => match key.code {
-                    KeyCode::Enter => {
-                        self.next_state = if self.save_location(db).is_ok() {
-                            AppState::Exit
-                        } else {
-                            AppState::NoChange
-                        }
-                    }
-                    _ => (),
-                },
-            }
-
-            match key.code {
-                KeyCode::Esc => self.next_state = AppState::Exit,
-                KeyCode::Down | KeyCode::Tab => {
-                    self.selection = self.selection.next();
-                    self.cursor_position = 0
-                }
-                KeyCode::Up => {
-                    self.selection = self.selection.previous();
-                    self.cursor_position = 0
-                }
-                _ => {}
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        let db = ctx.db;
+        let action = match *event {
+            Event::Key(key) if key.is_press() => self.form.handle_key(key, db),
+            Event::Mouse(mouse) => self.form.handle_mouse(mouse, db),
+            _ => FormAction::None,
+        };
+        match action {
+            FormAction::Save => {
+                self.form.submit(db)?;
+                ctx.notify(
+                    Level::Success,
+                    format!("Created location {}", self.form.value(ID)),
+                );
+                self.next_state = AppState::Exit
             }
+            FormAction::Cancel => self.next_state = AppState::Exit,
+            FormAction::None => {}
         }
         Ok(())
     }
 
+    fn title(&self) -> String {
+        "Create Location".to_string()
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
+    fn request_leave(&mut self) -> bool {
+        self.form.request_leave()
+    }
+    fn is_asking_to_leave(&self) -> bool {
+        self.form.is_asking_to_leave()
+    }
 }
 
 #[cfg(test)]
 mod create_location_tests {
+    use super::super::driver::Driver;
+    use super::super::form::Focus;
     use super::*;
     #[test]
     fn test_new() {
         let my_applet = CreateLocationApplet::new();
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert!(my_applet.id.is_empty());
-        assert!(my_applet.name.is_empty());
-        assert!(my_applet.comment.is_empty());
-        assert_eq!(my_applet.cursor_position, 0);
-        assert_eq!(my_applet.selection, CreateLocationSelection::Id);
+        assert!(my_applet.form.value(ID).is_empty());
+        assert!(my_applet.form.value(NAME).is_empty());
+        assert!(my_applet.form.value(COMMENT).is_empty());
+        assert_eq!(my_applet.form.cursor_position, 0);
+        assert_eq!(my_applet.form.focus, Focus::Field(ID));
     }
 
     #[test]
@@ -332,55 +120,80 @@ mod create_location_tests {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
 
         let mut my_applet = CreateLocationApplet::new();
-
-        my_applet.name = "Some_name".into();
-        my_applet.id = "1".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
-        my_applet.id = "nan".into();
-        assert!(my_applet.save_location(&my_inv).is_err());
-        my_applet.id = "0xff".into();
-        assert!(my_applet.save_location(&my_inv).is_err());
-        my_applet.id = "1".into();
-        assert!(my_applet.save_location(&my_inv).is_err());
-        my_applet.id = "2".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
-
-        my_applet.id = "3".into();
-        my_applet.name = "".into();
-        assert!(my_applet.save_location(&my_inv).is_err());
-        my_applet.name = "43".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        let form = &mut my_applet.form;
+
+        form.set_value(NAME, "Some_name");
+        form.set_value(ID, "1");
+        assert!(form.submit(&my_inv).is_ok());
+        form.set_value(ID, "nan");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(ID, "0xff");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(ID, "1");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(ID, "2");
+        assert!(form.submit(&my_inv).is_ok());
+
+        form.set_value(ID, "3");
+        form.set_value(NAME, "");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(NAME, "43");
+        assert!(form.submit(&my_inv).is_ok());
     }
 
     #[test]
     fn test_save() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
         let mut my_applet = CreateLocationApplet::new();
+        let form = &mut my_applet.form;
 
-        my_applet.id = "201".into();
-        my_applet.name = "n".into();
-        my_applet.comment = "".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(ID, "201");
+        form.set_value(NAME, "n");
+        form.set_value(COMMENT, "");
+        assert!(form.submit(&my_inv).is_ok());
         assert_eq!(
             my_inv.search_location_id(201),
             Some(inventory::Location {
                 id: 201,
                 name: "n".into(),
                 comment: None,
+                parent_id: None,
             })
         );
 
-        my_applet.id = "202".into();
-        my_applet.name = "n2".into();
-        my_applet.comment = "comment".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(ID, "202");
+        form.set_value(NAME, "n2");
+        form.set_value(COMMENT, "comment");
+        assert!(form.submit(&my_inv).is_ok());
         assert_eq!(
             my_inv.search_location_id(202),
             Some(inventory::Location {
                 id: 202,
                 name: "n2".into(),
                 comment: Some("comment".into()),
+                parent_id: None,
             })
         );
     }
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        let mut my_driver = Driver::new(CreateLocationApplet::new(), my_inv);
+        my_driver.assert_screen_contains("Location ID");
+        my_driver
+            .type_text("7")
+            .keys("Tab")
+            .type_text("Garage")
+            .keys("Ctrl+s");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
+        assert_eq!(my_driver.db.search_location_id(7).unwrap().name, "Garage");
+
+        //the same ID can not be used twice
+        let mut my_driver = Driver::new(CreateLocationApplet::new(), my_driver.db);
+        my_driver.type_text("7").keys("Tab");
+        my_driver.assert_screen_contains("Location ID already exists");
+        my_driver.keys("Esc").click_text("Discard");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
+    }
 }
@@ -0,0 +1,414 @@
+use super::applet::Applet;
+use super::context::EventContext;
+use super::help::{self, HelpOverlay};
+use super::mouse::{self, ClickTracker};
+use crate::AppState;
+use crate::db::inventory::{Inventory, Location, RecentChange, RecordKind, Stats};
+use crate::events::AppEvent;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::{Event, MouseEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListState, Padding, Paragraph};
+
+/// How many rows the largest locations and recent changes panels load.
+const PANEL_ROWS: usize = 10;
+
+const MENU: &[(&str, AppState)] = &[
+    ("List Locations", AppState::ListLocations),
+    ("Location Tree", AppState::LocationTree),
+    ("List Items", AppState::ListItems),
+    ("Create Location", AppState::CreateLocation),
+    ("Create Item", AppState::CreateItem),
+    ("Item Lookup", AppState::ItemLookup),
+    ("Export to CSV", AppState::Export),
+    ("Exit", AppState::Exit),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Panel {
+    Menu,
+    Largest,
+    Recent,
+}
+const PANELS: [Panel; 3] = [Panel::Menu, Panel::Largest, Panel::Recent];
+
+/// The home screen: the menu next to totals, the fullest locations and the latest changes.
+pub struct DashboardApplet {
+    stats: Stats,
+    largest: Vec<(Location, usize)>,
+    recent: Vec<RecentChange>,
+    focus: Panel,
+    /// Indexed like `PANELS`.
+    list_states: [ListState; 3],
+    list_areas: [Rect; 3],
+    next_state: AppState,
+    /// Clicked rows, along with the panel they are in.
+    clicks: ClickTracker<(Panel, usize)>,
+    keys: KeyReader,
+    help: HelpOverlay,
+}
+
+const ACTIONS: &[Action] = &[
+    Action::Up,
+    Action::Down,
+    Action::Top,
+    Action::Bottom,
+    Action::Left,
+    Action::Right,
+    Action::Select,
+    Action::Back,
+    Action::Quit,
+    Action::Help,
+];
+const HINTS: &[Action] = &[Action::Select, Action::Right, Action::Quit, Action::Help];
+
+impl Default for DashboardApplet {
+    fn default() -> Self {
+        Self {
+            stats: Stats::default(),
+            largest: Vec::new(),
+            recent: Vec::new(),
+            focus: Panel::Menu,
+            list_states: [
+                ListState::default().with_selected(Some(0)),
+                ListState::default(),
+                ListState::default(),
+            ],
+            list_areas: [Rect::default(); 3],
+            next_state: AppState::NoChange,
+            clicks: ClickTracker::default(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
+        }
+    }
+}
+
+impl DashboardApplet {
+    fn panel_index(panel: Panel) -> usize {
+        PANELS.iter().position(|p| *p == panel).unwrap_or(0)
+    }
+
+    fn panel_len(&self, panel: Panel) -> usize {
+        match panel {
+            Panel::Menu => MENU.len(),
+            Panel::Largest => self.largest.len(),
+            Panel::Recent => self.recent.len(),
+        }
+    }
+
+    fn list_state(&mut self) -> &mut ListState {
+        &mut self.list_states[Self::panel_index(self.focus)]
+    }
+
+    /// Moves the focus to the next panel with rows in it, `step` being 1 or -1.
+    fn move_focus(&mut self, step: isize) {
+        let mut index = Self::panel_index(self.focus);
+        for _ in 0..PANELS.len() {
+            index = (index as isize + step).rem_euclid(PANELS.len() as isize) as usize;
+            if self.panel_len(PANELS[index]) > 0 {
+                break;
+            }
+        }
+        self.focus = PANELS[index];
+        if self.list_state().selected().is_none() {
+            self.list_state().select_first();
+        }
+    }
+
+    fn activate(&mut self, row: usize) {
+        let state = match self.focus {
+            Panel::Menu => MENU.get(row).map(|(_, state)| state.clone()),
+            Panel::Largest => self
+                .largest
+                .get(row)
+                .map(|(location, _)| AppState::EditLocation(location.id)),
+            Panel::Recent => self.recent.get(row).map(|change| match change.kind {
+                RecordKind::Item => AppState::EditItem(change.id),
+                RecordKind::Location => AppState::EditLocation(change.id),
+            }),
+        };
+        if let Some(state) = state {
+            self.next_state = state;
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Quit | Action::Back => self.next_state = AppState::Exit,
+            Action::Down => self.list_state().select_next(),
+            Action::Up => self.list_state().select_previous(),
+            Action::Top => self.list_state().select_first(),
+            Action::Bottom => {
+                let last = self.panel_len(self.focus).checked_sub(1);
+                self.list_state().select(last);
+            }
+            Action::Right => self.move_focus(1),
+            Action::Left => self.move_focus(-1),
+            Action::Select => {
+                let row = self.list_state().selected().unwrap_or(0);
+                self.activate(row);
+            }
+            Action::Help => self.help.open(),
+            _ => {}
+        }
+    }
+
+    fn stat_tiles(&self) -> [Paragraph<'static>; 4] {
+        let tile = |value: usize, label: &'static str| {
+            Paragraph::new(vec![
+                Line::styled(value.to_string(), theme::current().focus),
+                Line::styled(label, theme::current().read_only),
+            ])
+            .alignment(Alignment::Center)
+            .block(Block::bordered())
+        };
+        [
+            tile(self.stats.items, "Items"),
+            tile(self.stats.locations, "Locations"),
+            tile(self.stats.unassigned_items, "Without a location"),
+            tile(self.stats.empty_locations, "Empty locations"),
+        ]
+    }
+
+    fn panel_lines(&self, panel: Panel) -> Vec<Line<'static>> {
+        let detail = |text: String| Span::styled(text, theme::current().disabled);
+        match panel {
+            Panel::Menu => MENU.iter().map(|(label, _)| Line::from(*label)).collect(),
+            Panel::Largest => self
+                .largest
+                .iter()
+                .map(|(location, count)| {
+                    Line::from(vec![
+                        Span::raw(location.name.clone()),
+                        detail(format!(" ({count})")),
+                    ])
+                })
+                .collect(),
+            Panel::Recent => self
+                .recent
+                .iter()
+                .map(|change| {
+                    let kind = match change.kind {
+                        RecordKind::Item => "item",
+                        RecordKind::Location => "location",
+                    };
+                    let verb = if change.added { "added" } else { "edited" };
+                    Line::from(vec![
+                        Span::raw(format!("{kind}: {}", change.name)),
+                        detail(format!(" {verb} {}", change.when)),
+                    ])
+                })
+                .collect(),
+        }
+    }
+
+    fn panel_list(&self, panel: Panel) -> List<'static> {
+        let title = match panel {
+            Panel::Menu => "Menu",
+            Panel::Largest => "Largest Locations",
+            Panel::Recent => "Recently Changed",
+        };
+        let highlight = if panel == self.focus {
+            theme::current().selection
+        } else {
+            Style::default()
+        };
+        List::new(self.panel_lines(panel))
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_style(if panel == self.focus {
+                        theme::current().focus
+                    } else {
+                        Style::default()
+                    })
+                    .padding(Padding::horizontal(1)),
+            )
+            .highlight_style(highlight)
+            .highlight_symbol(if panel == self.focus { ">>" } else { "  " })
+    }
+}
+
+impl Applet for DashboardApplet {
+    fn render(&mut self, frame: &mut Frame) {
+        let block = Block::bordered()
+            .title_bottom(help::hint(Context::List, HINTS))
+            .border_type(ratatui::widgets::BorderType::Thick)
+            .style(theme::current().text);
+        let tiles = self.stat_tiles();
+        let lists = PANELS.map(|panel| self.panel_list(panel));
+
+        let inner = block.inner(frame.area());
+        frame.render_widget(block, frame.area());
+        let [tiles_area, panels_area] =
+            Layout::vertical([Constraint::Length(4), Constraint::Fill(1)]).areas(inner);
+        let tile_areas: [Rect; 4] =
+            Layout::horizontal([Constraint::Ratio(1, 4); 4]).areas(tiles_area);
+        for (tile, area) in tiles.into_iter().zip(tile_areas) {
+            frame.render_widget(tile, area);
+        }
+        self.list_areas = Layout::horizontal([
+            Constraint::Length(24),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ])
+        .areas(panels_area);
+        for ((list, area), state) in lists
+            .into_iter()
+            .zip(self.list_areas)
+            .zip(self.list_states.iter_mut())
+        {
+            frame.render_stateful_widget(list, area, state);
+        }
+        self.help.render(frame, "Dashboard", Context::List, ACTIONS);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        _ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+
+        if self.help.dismiss(event) {
+            return Ok(());
+        }
+        match *event {
+            Event::Key(key) if key.is_press() => {
+                if let Some(action) = self.keys.action(Context::List, key) {
+                    self.handle_action(action);
+                }
+            }
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollDown => self.list_state().select_next(),
+                MouseEventKind::ScrollUp => self.list_state().select_previous(),
+                _ if mouse::is_left_click(&mouse) => {
+                    for (i, panel) in PANELS.into_iter().enumerate() {
+                        let offset = self.list_states[i].offset();
+                        if let Some(row) = mouse::row_at(self.list_areas[i], 0, offset, &mouse)
+                            && row < self.panel_len(panel)
+                        {
+                            self.focus = panel;
+                            self.list_states[i].select(Some(row));
+                            if self.clicks.click((panel, row)) {
+                                self.activate(row);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn title(&self) -> String {
+        "Dashboard".to_string()
+    }
+    fn get_next_state(&self) -> AppState {
+        self.next_state.clone()
+    }
+    fn refresh(&mut self, db: &Inventory) {
+        self.stats = db.stats().unwrap_or_default();
+        self.largest = db.largest_locations(PANEL_ROWS).unwrap_or_default();
+        self.recent = db.recent_changes(PANEL_ROWS).unwrap_or_default();
+        for (i, panel) in PANELS.into_iter().enumerate() {
+            let len = self.panel_len(panel);
+            let state = &mut self.list_states[i];
+            if len == 0 {
+                state.select(None);
+            } else if state.selected().is_some_and(|row| row >= len) {
+                state.select(Some(len - 1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod dashboard_tests {
+    use super::super::driver::Driver;
+    use super::*;
+    use crate::seed::fixtures;
+
+    #[test]
+    fn test_refresh() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::STATS);
+        let mut my_applet = DashboardApplet::default();
+        my_applet.refresh(&my_inv);
+
+        assert_eq!(my_applet.stats.items, 4);
+        assert_eq!(my_applet.stats.unassigned_items, 1);
+        assert_eq!(my_applet.stats.empty_locations, 1);
+        assert_eq!(my_applet.largest[0].0.id, 1);
+        assert_eq!(my_applet.recent.len(), 7);
+        assert_eq!(
+            my_applet.panel_lines(Panel::Largest)[0].to_string(),
+            "location1 (2)"
+        );
+        assert!(
+            my_applet.panel_lines(Panel::Recent)[0]
+                .to_string()
+                .contains(" added ")
+        );
+    }
+
+    #[test]
+    fn test_navigation() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let mut my_applet = DashboardApplet::default();
+        my_applet.refresh(&my_inv);
+
+        //empty panels are skipped
+        my_applet.handle_action(Action::Right);
+        assert_eq!(my_applet.focus, Panel::Menu);
+        my_applet.handle_action(Action::Bottom);
+        my_applet.handle_action(Action::Select);
+        assert_eq!(my_applet.next_state, AppState::Exit);
+        my_applet.handle_action(Action::Top);
+        my_applet.handle_action(Action::Down);
+        my_applet.handle_action(Action::Select);
+        assert_eq!(my_applet.next_state, AppState::LocationTree);
+
+        fixtures::load(&my_inv, fixtures::STATS);
+        my_applet.refresh(&my_inv);
+        my_applet.handle_action(Action::Right);
+        assert_eq!(my_applet.focus, Panel::Largest);
+        my_applet.handle_action(Action::Select);
+        assert_eq!(my_applet.next_state, AppState::EditLocation(1));
+        my_applet.handle_action(Action::Right);
+        my_applet.handle_action(Action::Select);
+        assert!(matches!(
+            my_applet.next_state,
+            AppState::EditItem(_) | AppState::EditLocation(_)
+        ));
+        my_applet.handle_action(Action::Right);
+        assert_eq!(my_applet.focus, Panel::Menu);
+        my_applet.handle_action(Action::Left);
+        assert_eq!(my_applet.focus, Panel::Recent);
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::STATS);
+        let mut my_driver = Driver::new(DashboardApplet::default(), my_inv);
+        my_driver.assert_screen_contains("Without a location");
+        my_driver.assert_screen_contains("location1 (2)");
+
+        my_driver.click_text("List Items").click_text("List Items");
+        assert_eq!(my_driver.last_state(), AppState::ListItems);
+        my_driver.keys("Right Enter");
+        assert_eq!(my_driver.last_state(), AppState::EditLocation(1));
+        my_driver.keys("q");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
+    }
+}
@@ -0,0 +1,173 @@
+use super::applet::Applet;
+use super::context::EventContext;
+use super::notifications::{Level, Notifications};
+use crate::AppState;
+use crate::db::inventory::Inventory;
+use crate::events::{AppEvent, EventLoop};
+use crate::keymap::Key;
+use crossterm::event::{Event, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+
+/// Runs an applet without a real terminal, for end to end tests.
+///
+/// Draws into ratatui's `TestBackend` after every scripted event, the same way the `App` would,
+/// so tests can check what ended up on screen and which state the applet asked for.
+pub struct Driver<A: Applet> {
+    pub applet: A,
+    pub db: Inventory,
+    notifications: Notifications,
+    terminal: Terminal<TestBackend>,
+    events: EventLoop,
+    /// Every state the applet asked for, in order, leaving out `AppState::NoChange`.
+    pub states: Vec<AppState>,
+}
+
+impl<A: Applet> Driver<A> {
+    /// Refreshes `applet` from `db` and draws it on an 80x24 screen.
+    pub fn new(mut applet: A, db: Inventory) -> Self {
+        applet.refresh(&db);
+        let mut driver = Self {
+            applet,
+            db,
+            notifications: Notifications::default(),
+            terminal: Terminal::new(TestBackend::new(80, 24)).unwrap(),
+            events: EventLoop::default(),
+            states: Vec::new(),
+        };
+        driver.draw();
+        driver
+    }
+
+    fn draw(&mut self) {
+        let applet = &mut self.applet;
+        let notifications = &mut self.notifications;
+        self.terminal
+            .draw(|frame| {
+                applet.render(frame);
+                notifications.render(frame);
+            })
+            .unwrap();
+    }
+
+    /// Hands `event` to the applet and redraws. Errors become error toasts, like in the `App`.
+    pub fn send(&mut self, event: AppEvent) -> &mut Self {
+        //the driver runs a single applet, so every job result comes back to it
+        let mut ctx = EventContext::new(
+            &self.db,
+            &mut self.notifications,
+            self.events.sender(),
+            AppState::NoChange,
+        );
+        match self.applet.handle_event(&event, &mut ctx) {
+            Ok(()) => match self.applet.get_next_state() {
+                AppState::NoChange => {}
+                state => self.states.push(state),
+            },
+            Err(e) => self.notifications.push(Level::Error, e.to_string()),
+        }
+        self.draw();
+        self
+    }
+
+    /// Resizes the screen to `columns` by `rows` and tells the applet, like a terminal resize.
+    pub fn resize(&mut self, columns: u16, rows: u16) -> &mut Self {
+        self.terminal.backend_mut().resize(columns, rows);
+        self.send(AppEvent::Resize)
+    }
+
+    /// Blocks until a job started with `EventContext::spawn` finishes, then hands its result to
+    /// the applet.
+    pub fn wait_for_worker(&mut self) -> &mut Self {
+        let event = self.events.next().unwrap();
+        self.send(event)
+    }
+
+    /// Presses space separated keys written as in the config file, e.g. `"Down Down Ctrl+a"`.
+    pub fn keys(&mut self, keys: &str) -> &mut Self {
+        for key in keys.split_whitespace() {
+            let key = Key::parse(key).unwrap_or_else(|e| panic!("{e}"));
+            self.send(AppEvent::Input(Event::Key(KeyEvent::from(key))));
+        }
+        self
+    }
+
+    /// Types `text` one character at a time.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            let key = KeyEvent::new(crossterm::event::KeyCode::Char(c), KeyModifiers::NONE);
+            self.send(AppEvent::Input(Event::Key(key)));
+        }
+        self
+    }
+
+    pub fn click(&mut self, column: u16, row: u16) -> &mut Self {
+        self.send(AppEvent::Input(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })))
+    }
+
+    /// Clicks the first place on screen showing `text`.
+    pub fn click_text(&mut self, text: &str) -> &mut Self {
+        let Some((column, row)) = self.find(text) else {
+            panic!("'{text}' is not on screen:\n{}", self.screen());
+        };
+        self.click(column, row)
+    }
+
+    /// The screen as text, one line per row.
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let mut lines = Vec::new();
+        for row in 0..buffer.area.height {
+            let line: String = (0..buffer.area.width)
+                .map(|column| buffer[(column, row)].symbol())
+                .collect();
+            lines.push(line.trim_end().to_string());
+        }
+        lines.join("\n")
+    }
+
+    /// The column and row where `text` first appears on screen.
+    pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+        self.screen().lines().enumerate().find_map(|(row, line)| {
+            let start = line.find(text)?;
+            Some((line[..start].chars().count() as u16, row as u16))
+        })
+    }
+
+    pub fn assert_screen_contains(&self, text: &str) {
+        assert!(
+            self.find(text).is_some(),
+            "'{text}' is not on screen:\n{}",
+            self.screen()
+        );
+    }
+
+    /// The state the applet most recently asked for.
+    pub fn last_state(&self) -> AppState {
+        self.states.last().cloned().unwrap_or(AppState::NoChange)
+    }
+}
+
+#[cfg(test)]
+mod driver_tests {
+    use super::super::ErrorApplet;
+    use super::*;
+
+    #[test]
+    fn test_screen_and_resize() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_driver = Driver::new(ErrorApplet::new("disk full".into()), my_inv);
+        my_driver.assert_screen_contains("disk full");
+        assert_eq!(my_driver.screen().lines().count(), 24);
+
+        my_driver.resize(40, 10);
+        assert_eq!(my_driver.screen().lines().count(), 10);
+        my_driver.assert_screen_contains("disk full");
+        assert_eq!(my_driver.last_state(), AppState::NoChange);
+    }
+}
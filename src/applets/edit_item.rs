@@ -1,436 +1,194 @@
 use super::applet::Applet;
+use super::context::EventContext;
+use super::form::{Field, Form, FormAction};
+use super::notifications::Level;
 use crate::AppState;
 use crate::db::inventory;
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
-use ratatui::layout::{Constraint, Layout, Position};
-use ratatui::style::Style;
-use ratatui::widgets::{Block, Padding, Paragraph};
-use std::error;
-use std::fmt;
+use crate::events::AppEvent;
+use crate::validation;
+use crossterm::event::Event;
+use ratatui::Frame;
+
+const ID: usize = 0;
+const NAME: usize = 1;
+const COMMENT: usize = 2;
+const LOCATION_ID: usize = 3;
 
 pub struct EditItemApplet {
     next_state: AppState,
-    item: inventory::Item,
     id: i64,
-    cursor_position: u16,
-    selection: EditItemSelection,
-    loc_id_str: String,
-}
-#[derive(Debug)]
-struct EditItemError {
-    error_text: String,
-}
-
-impl fmt::Display for EditItemError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Edit Item Error: {}", self.error_text)
-    }
-}
-
-impl error::Error for EditItemError {}
-
-impl EditItemError {
-    fn new(msg: &str) -> Box<EditItemError> {
-        Box::new(EditItemError {
-            error_text: msg.to_string(),
-        })
-    }
-}
-
-#[derive(Debug, PartialEq)]
-enum EditItemSelection {
-    Name,
-    Comment,
-    LocationID,
-    Cancel,
-    Save,
-}
-
-impl EditItemSelection {
-    fn next(&self) -> Self {
-        match self {
-            EditItemSelection::Name => EditItemSelection::Comment,
-            EditItemSelection::Comment => EditItemSelection::LocationID,
-            EditItemSelection::LocationID => EditItemSelection::Cancel,
-            EditItemSelection::Cancel => EditItemSelection::Save,
-            EditItemSelection::Save => EditItemSelection::Name,
-        }
-    }
-    fn previous(&self) -> Self {
-        match self {
-            EditItemSelection::Name => EditItemSelection::Save,
-            EditItemSelection::Comment => EditItemSelection::Name,
-            EditItemSelection::LocationID => EditItemSelection::Comment,
-            EditItemSelection::Cancel => EditItemSelection::LocationID,
-            EditItemSelection::Save => EditItemSelection::Cancel,
-        }
-    }
+    form: Form,
 }
 
 impl EditItemApplet {
     pub fn new(id: i64) -> Self {
         Self {
             next_state: AppState::NoChange,
-            item: inventory::Item {
-                id: -1,
-                name: "".to_string(),
-                comment: None,
-                location_id: None,
-            },
             id,
-            cursor_position: 0,
-            selection: EditItemSelection::Name,
-            loc_id_str: String::default(),
+            form: Form::new("Edit Item", save_item)
+                .field(Field::integer("Item ID").read_only())
+                .field(Field::text("Name").required())
+                .field(Field::text("Comment").grow())
+                .field(Field::integer("Location ID").validator(validation::location_id_exists)),
         }
     }
-    fn save_item(&mut self, db: &inventory::Inventory) -> Result<(), Box<dyn std::error::Error>> {
-        if self.loc_id_str.is_empty() {
-            self.item.location_id = None;
-        } else {
-            match self.loc_id_str.parse::<i64>() {
-                Ok(id) => self.item.location_id = Some(id),
-                Err(_) => return Err(EditItemError::new("Could not parse Location ID")),
-            }
-            if !db.location_exists(self.item.location_id.unwrap()) {
-                return Err(EditItemError::new("Location ID not found in Database"));
-            }
-        }
-        if self.item.comment.clone().unwrap_or_default().is_empty() {
-            self.item.comment = None;
-        }
-
-        if self.item.name.is_empty() {
-            return Err(EditItemError::new("Name cannot be empty"));
-        }
-
-        if db.edit_item(&self.item).is_err() {
-            return Err(EditItemError::new("Failed to write update to Database"));
-        }
+}
 
-        Ok(())
+fn save_item(form: &Form, db: &inventory::Inventory) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(id) = form.integer(ID) else {
+        return Err(form.error("Item has not been loaded"));
+    };
+    let new_item = inventory::Item {
+        id,
+        name: form.value(NAME).to_string(),
+        comment: form.optional_text(COMMENT),
+        location_id: form.integer(LOCATION_ID),
+    };
+    if db.edit_item(&new_item).is_err() {
+        return Err(form.error("Failed to write update to Database"));
     }
+    Ok(())
 }
 
 impl Applet for EditItemApplet {
-    fn run(
+    fn render(&mut self, frame: &mut Frame) {
+        self.form.render(frame);
+    }
+
+    fn handle_event(
         &mut self,
-        terminal: &mut DefaultTerminal,
-        db: &inventory::Inventory,
+        event: &AppEvent,
+        ctx: &mut EventContext,
     ) -> Result<(), Box<dyn std::error::Error>> {
         self.next_state = AppState::NoChange;
-
-        //Prepare Draw
-        let vertical = Layout::vertical([
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Min(1),
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Length(3),
-        ]);
-        let border = Block::bordered()
-            .title_top("Inventory Manager - Edit Item")
-            .title_bottom("Press 'q' or Esc to exit")
-            .border_type(ratatui::widgets::BorderType::Thick)
-            .padding(Padding::horizontal(1));
-        let id_widget = Paragraph::new(self.item.id.to_string())
-            .style(Style::default().bold())
-            .block(Block::bordered().title("Item ID"));
-        let name_widget = Paragraph::new(self.item.name.clone())
-            .style(if self.selection == EditItemSelection::Name {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Name"));
-        let comment_widget = Paragraph::new(self.item.comment.clone().unwrap_or("".to_string()))
-            .style(if self.selection == EditItemSelection::Comment {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Comment"));
-        let location_widget = Paragraph::new(self.loc_id_str.clone())
-            .style(if self.selection == EditItemSelection::LocationID {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Location ID"));
-        let cancel_button = Paragraph::new("Cancel".to_string())
-            .style(if self.selection == EditItemSelection::Cancel {
-                Style::default().yellow().bold()
-            } else {
-                Style::default().bold()
-            })
-            .block(Block::bordered());
-        let save_button = Paragraph::new("Save Changes".to_string())
-            .style(if self.selection == EditItemSelection::Save {
-                Style::default().yellow().bold()
-            } else {
-                Style::default().bold()
-            })
-            .block(Block::bordered());
-
-        terminal.draw(|frame| {
-            let inner_area = border.inner(frame.area());
-            let [
-                id_area,
-                name_area,
-                comment_area,
-                location_area,
-                cancel_area,
-                save_area,
-            ] = vertical.areas(inner_area);
-            frame.render_widget(border, frame.area());
-            frame.render_widget(id_widget, id_area);
-            frame.render_widget(name_widget, name_area);
-            frame.render_widget(comment_widget, comment_area);
-            frame.render_widget(location_widget, location_area);
-            frame.render_widget(cancel_button, cancel_area);
-            frame.render_widget(save_button, save_area);
-            match self.selection {
-                EditItemSelection::Name => {
-                    frame.set_cursor_position(Position::new(
-                        name_area.x + self.cursor_position + 1,
-                        name_area.y + 1,
-                    ));
-                }
-                EditItemSelection::Comment => {
-                    frame.set_cursor_position(Position::new(
-                        comment_area.x + self.cursor_position + 1,
-                        comment_area.y + 1,
-                    ));
-                }
-                EditItemSelection::LocationID => {
-                    frame.set_cursor_position(Position::new(
-                        location_area.x + self.cursor_position + 1,
-                        location_area.y + 1,
-                    ));
-                }
-                _ => (),
-            }
-        })?;
-
-        //Handle Input
-        if let Some(key) = event::read()?.as_key_press_event() {
-            match self.selection {
-                EditItemSelection::Name => match key.code {
-                    KeyCode::Char(c) => {
-                        self.item.name.insert(self.cursor_position.into(), c);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            self.item.name.remove(self.cursor_position.into());
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.item.name.len() as u16 {
-                            self.item.name.remove(self.cursor_position.into());
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position = self
-                            .cursor_position
-                            .saturating_add(1)
-                            .min(self.item.name.len() as u16)
-                    }
-                    _ => {}
-                },
-
-                EditItemSelection::Comment => match key.code {
-                    KeyCode::Char(c) => {
-                        let mut comment = self.item.comment.clone().unwrap_or_default();
-                        comment.insert(self.cursor_position.into(), c);
-                        self.item.comment = Some(comment);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            let mut comment = self.item.comment.clone().unwrap_or_default();
-                            comment.remove(self.cursor_position.into());
-                            self.item.comment = Some(comment);
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.item.comment.as_ref().unwrap().len() as u16
-                        {
-                            let mut comment = self.item.comment.clone().unwrap_or_default();
-                            comment.remove(self.cursor_position.into());
-                            self.item.comment = Some(comment);
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position = self
-                            .cursor_position
-                            .saturating_add(1)
-                            .min(self.item.comment.as_ref().unwrap().len() as u16)
-                    }
-                    _ => {}
-                },
-                EditItemSelection::LocationID => match key.code {
-                    KeyCode::Char(c) => {
-                        self.loc_id_str.insert(self.cursor_position.into(), c);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            self.loc_id_str.remove(self.cursor_position.into());
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.loc_id_str.len() as u16 {
-                            self.loc_id_str.remove(self.cursor_position.into());
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position = self
-                            .cursor_position
-                            .saturating_add(1)
-                            .min(self.loc_id_str.len() as u16)
-                    }
-                    _ => {}
-                },
-                EditItemSelection::Cancel => match key.code {
-                    KeyCode::Enter => self.next_state = AppState::Exit,
-                    _ => (),
-                },
-                EditItemSelection::Save => match key.code {
-                    KeyCode::Enter => {
-                        self.save_item(db)?;
-                        self.next_state = AppState::Exit
-                    }
-                    _ => (),
-                },
-            }
-
-            match key.code {
-                KeyCode::Esc => self.next_state = AppState::Exit,
-                KeyCode::Down | KeyCode::Tab => {
-                    self.selection = self.selection.next();
-                    self.cursor_position = 0
-                }
-                KeyCode::Up => {
-                    self.selection = self.selection.previous();
-                    self.cursor_position = 0
-                }
-                _ => {}
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        let db = ctx.db;
+        let action = match *event {
+            Event::Key(key) if key.is_press() => self.form.handle_key(key, db),
+            Event::Mouse(mouse) => self.form.handle_mouse(mouse, db),
+            _ => FormAction::None,
+        };
+        match action {
+            FormAction::Save => {
+                self.form.submit(db)?;
+                ctx.notify(
+                    Level::Success,
+                    format!("Saved item {}", self.form.value(ID)),
+                );
+                self.next_state = AppState::Exit
             }
+            FormAction::Cancel => self.next_state = AppState::Exit,
+            FormAction::None => {}
         }
         Ok(())
     }
 
+    fn title(&self) -> String {
+        format!("Edit Item {}", self.id)
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
+    fn request_leave(&mut self) -> bool {
+        self.form.request_leave()
+    }
+    fn is_asking_to_leave(&self) -> bool {
+        self.form.is_asking_to_leave()
+    }
 
     fn refresh(&mut self, db: &inventory::Inventory) {
         //check if we need to load
-        if self.item.id != self.id {
-            self.item = db.search_item_id(self.id).unwrap(); //shouldn't be none, as we shouldn't get here if it doesn't exist
-            if self.item.comment.is_none() {
-                self.item.comment = Some("".to_string());
-            }
-            self.loc_id_str = self
-                .item
-                .location_id
-                .map(|lid| lid.to_string())
-                .unwrap_or("".to_string())
+        if self.form.integer(ID) != Some(self.id)
+            && let Some(item) = db.search_item_id(self.id)
+        {
+            self.form.set_value(ID, item.id.to_string());
+            self.form.set_value(NAME, item.name);
+            self.form
+                .set_value(COMMENT, item.comment.unwrap_or_default());
+            self.form.set_value(
+                LOCATION_ID,
+                item.location_id
+                    .map(|lid| lid.to_string())
+                    .unwrap_or_default(),
+            );
+            self.form.mark_clean();
         }
     }
 }
 
 #[cfg(test)]
 mod edit_item_tests {
+    use super::super::driver::Driver;
+    use super::super::form::Focus;
     use super::*;
+    use crate::seed::fixtures;
     #[test]
     fn test_creation() {
         let my_applet = EditItemApplet::new(1);
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert_eq!(my_applet.item.id, -1);
-        assert_eq!(my_applet.item.name, "".to_string());
-        assert_eq!(my_applet.item.comment, None);
-        assert_eq!(my_applet.item.location_id, None);
+        assert_eq!(my_applet.form.value(ID), "");
+        assert_eq!(my_applet.form.value(NAME), "");
+        assert_eq!(my_applet.form.value(COMMENT), "");
+        assert_eq!(my_applet.form.value(LOCATION_ID), "");
         assert_eq!(my_applet.id, 1);
-        assert_eq!(my_applet.cursor_position, 0);
-        assert_eq!(my_applet.selection, EditItemSelection::Name);
-        assert_eq!(my_applet.loc_id_str, "".to_string());
+        assert_eq!(my_applet.form.cursor_position, 0);
+        assert_eq!(my_applet.form.focus, Focus::Field(NAME));
     }
 
     #[test]
     fn test_refresh() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
 
         let mut my_applet = EditItemApplet::new(101);
         my_applet.refresh(&my_inv);
-        assert_eq!(
-            my_applet.item,
-            inventory::Item {
-                id: 101,
-                name: "item1".to_string(),
-                comment: Some("comment1".to_string()),
-                location_id: Some(1)
-            }
-        );
-        my_applet.item.location_id = None;
-        assert_eq!(
-            my_applet.item,
-            inventory::Item {
-                id: 101,
-                name: "item1".to_string(),
-                comment: Some("comment1".to_string()),
-                location_id: None,
-            }
-        );
+        assert_eq!(my_applet.form.value(ID), "101");
+        assert_eq!(my_applet.form.value(NAME), "item1");
+        assert_eq!(my_applet.form.value(COMMENT), "comment1");
+        assert_eq!(my_applet.form.value(LOCATION_ID), "1");
+        assert!(!my_applet.form.is_dirty());
+
+        my_applet.form.set_value(LOCATION_ID, "");
         my_applet.refresh(&my_inv);
-        assert_eq!(
-            my_applet.item,
-            inventory::Item {
-                id: 101,
-                name: "item1".to_string(),
-                comment: Some("comment1".to_string()),
-                location_id: None,
-            }
-        );
+        assert!(my_applet.form.is_dirty());
+        assert_eq!(my_applet.form.value(ID), "101");
+        assert_eq!(my_applet.form.value(NAME), "item1");
+        assert_eq!(my_applet.form.value(COMMENT), "comment1");
+        assert_eq!(my_applet.form.value(LOCATION_ID), "");
     }
 
     #[test]
     fn test_save_parsing() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
         let mut my_applet = EditItemApplet::new(101);
         my_applet.refresh(&my_inv);
+        let form = &mut my_applet.form;
 
-        my_applet.loc_id_str = "nan".to_string();
-        assert!(my_applet.save_item(&my_inv).is_err());
-        my_applet.loc_id_str = "0xff".to_string();
-        assert!(my_applet.save_item(&my_inv).is_err());
-        my_applet.loc_id_str = "99".to_string();
-        assert!(my_applet.save_item(&my_inv).is_err());
-        my_applet.loc_id_str = "4".to_string();
-        assert!(my_applet.save_item(&my_inv).is_ok());
+        form.set_value(LOCATION_ID, "nan");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(LOCATION_ID, "0xff");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(LOCATION_ID, "99");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(LOCATION_ID, "4");
+        assert!(form.submit(&my_inv).is_ok());
 
-        my_applet.item.name = "".to_string();
-        assert!(my_applet.save_item(&my_inv).is_err());
+        form.set_value(NAME, "");
+        assert!(form.submit(&my_inv).is_err());
     }
 
     #[test]
     fn test_save() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
         let mut my_applet = EditItemApplet::new(101);
         my_applet.refresh(&my_inv);
+        let form = &mut my_applet.form;
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "item1".to_string(),
@@ -439,10 +197,10 @@ mod edit_item_tests {
             })
         );
 
-        my_applet.loc_id_str = "nan".to_string();
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(LOCATION_ID, "nan");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "item1".to_string(),
@@ -451,10 +209,10 @@ mod edit_item_tests {
             })
         );
 
-        my_applet.loc_id_str = "".to_string();
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(LOCATION_ID, "");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "item1".to_string(),
@@ -463,10 +221,10 @@ mod edit_item_tests {
             })
         );
 
-        my_applet.loc_id_str = "4".to_string();
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(LOCATION_ID, "4");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "item1".to_string(),
@@ -474,10 +232,10 @@ mod edit_item_tests {
                 location_id: Some(4)
             })
         );
-        my_applet.item.name = "".to_string();
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(NAME, "");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "item1".to_string(),
@@ -486,10 +244,10 @@ mod edit_item_tests {
             })
         );
 
-        my_applet.item.name = "newname".to_string();
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(NAME, "newname");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "newname".to_string(),
@@ -498,10 +256,10 @@ mod edit_item_tests {
             })
         );
 
-        my_applet.item.comment = Some("newcomment".to_string());
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(COMMENT, "newcomment");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "newname".to_string(),
@@ -509,10 +267,10 @@ mod edit_item_tests {
                 location_id: Some(4)
             })
         );
-        my_applet.item.comment = Some("".to_string());
-        let _ = my_applet.save_item(&my_inv);
+        form.set_value(COMMENT, "");
+        let _ = form.submit(&my_inv);
         assert_eq!(
-            my_inv.search_item_id(my_applet.item.id),
+            my_inv.search_item_id(101),
             Some(inventory::Item {
                 id: 101,
                 name: "newname".to_string(),
@@ -522,21 +280,21 @@ mod edit_item_tests {
         );
     }
 
-    fn fill_db(my_inv: &inventory::Inventory) {
-        for i in 0..5 {
-            let loc = inventory::Location {
-                id: i,
-                name: format!("location{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-            };
-            let item = inventory::Item {
-                id: i + 100,
-                name: format!("item{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-                location_id: Some(i),
-            };
-            assert!(my_inv.add_location(&loc).is_ok());
-            assert!(my_inv.add_item(&item).is_ok());
-        }
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        let item = inventory::Item {
+            id: 104,
+            name: "screws".to_string(),
+            comment: None,
+            location_id: None,
+        };
+        assert!(my_inv.add_item(&item).is_ok());
+        let mut my_driver = Driver::new(EditItemApplet::new(104), my_inv);
+        my_driver.assert_screen_contains("screws");
+
+        my_driver.type_text(" M3").keys("Ctrl+s");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
+        assert_eq!(my_driver.db.search_item_id(104).unwrap().name, "screws M3");
     }
 }
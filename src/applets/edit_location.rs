@@ -1,456 +1,296 @@
 use super::applet::Applet;
+use super::context::EventContext;
+use super::form::{Field, Form, FormAction};
+use super::notifications::Level;
 use crate::AppState;
 use crate::db::inventory;
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
-use ratatui::layout::{Constraint, Layout, Position};
-use ratatui::style::Style;
-use ratatui::widgets::{Block, Padding, Paragraph};
-use std::error;
-use std::fmt;
+use crate::events::AppEvent;
+use crate::validation;
+use crossterm::event::Event;
+use ratatui::Frame;
+
+const ID: usize = 0;
+const NAME: usize = 1;
+const COMMENT: usize = 2;
+const PARENT: usize = 3;
 
 pub struct EditLocationApplet {
     next_state: AppState,
-    loc: inventory::Location,
     id: i64,
-    cursor_position: u16,
-    selection: EditLocationSelection,
-}
-
-#[derive(Debug)]
-struct EditLocationError {
-    error_text: String,
-}
-
-impl fmt::Display for EditLocationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Edit Item Error: {}", self.error_text)
-    }
-}
-
-impl error::Error for EditLocationError {}
-
-impl EditLocationError {
-    fn new(msg: &str) -> Box<EditLocationError> {
-        Box::new(EditLocationError {
-            error_text: msg.to_string(),
-        })
-    }
-}
-
-#[derive(PartialEq, Debug)]
-enum EditLocationSelection {
-    Name,
-    Comment,
-    Cancel,
-    Save,
-}
-
-impl EditLocationSelection {
-    fn next(&self) -> Self {
-        match self {
-            EditLocationSelection::Name => EditLocationSelection::Comment,
-            EditLocationSelection::Comment => EditLocationSelection::Cancel,
-            EditLocationSelection::Cancel => EditLocationSelection::Save,
-            EditLocationSelection::Save => EditLocationSelection::Name,
-        }
-    }
-    fn previous(&self) -> Self {
-        match self {
-            EditLocationSelection::Name => EditLocationSelection::Save,
-            EditLocationSelection::Comment => EditLocationSelection::Name,
-            EditLocationSelection::Cancel => EditLocationSelection::Comment,
-            EditLocationSelection::Save => EditLocationSelection::Cancel,
-        }
-    }
-}
-
-impl Default for EditLocationApplet {
-    fn default() -> Self {
-        Self {
-            next_state: AppState::NoChange,
-            loc: inventory::Location {
-                id: 260126,
-                name: "Tweezers".to_string(),
-                comment: Some("ESD Safe".to_string()),
-            },
-            id: -1,
-            cursor_position: 0,
-            selection: EditLocationSelection::Name,
-        }
-    }
+    form: Form,
 }
 
 impl EditLocationApplet {
     pub fn new(id: i64) -> Self {
         Self {
             next_state: AppState::NoChange,
-            loc: inventory::Location {
-                id: -1,
-                name: "".to_string(),
-                comment: None,
-            },
             id,
-            cursor_position: 0,
-            selection: EditLocationSelection::Name,
+            form: Form::new("Edit Location", save_location)
+                .field(Field::integer("Location ID").read_only())
+                .field(Field::text("Name").required())
+                .field(Field::text("Comment").grow())
+                .field(Field::integer("Parent ID").validator(validation::location_id_exists)),
         }
     }
-    fn save_location(
-        &mut self,
-        db: &inventory::Inventory,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if self.loc.name.is_empty() {
-            return Err(EditLocationError::new("Name cannot be empty"));
-        }
-        if self.loc.comment.clone().unwrap_or_default().is_empty() {
-            self.loc.comment = None;
-        }
-        db.edit_location(&self.loc)?;
-        Ok(())
-    }
 }
+
+fn save_location(form: &Form, db: &inventory::Inventory) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(id) = form.integer(ID) else {
+        return Err(form.error("Location has not been loaded"));
+    };
+    let new_location = inventory::Location {
+        id,
+        name: form.value(NAME).to_string(),
+        comment: form.optional_text(COMMENT),
+        parent_id: form.integer(PARENT),
+    };
+    db.edit_location(&new_location)?;
+    Ok(())
+}
+
 impl Applet for EditLocationApplet {
-    fn run(
+    fn render(&mut self, frame: &mut Frame) {
+        self.form.render(frame);
+    }
+
+    fn handle_event(
         &mut self,
-        terminal: &mut DefaultTerminal,
-        db: &inventory::Inventory,
+        event: &AppEvent,
+        ctx: &mut EventContext,
     ) -> Result<(), Box<dyn std::error::Error>> {
         self.next_state = AppState::NoChange;
-
-        //Prepare Draw
-        let vertical = Layout::vertical([
-            Constraint::Length(3),
-            Constraint::Length(3),
-            Constraint::Min(1),
-            Constraint::Length(3),
-            Constraint::Length(3),
-        ]);
-        let border = Block::bordered()
-            .title_top("Inventory Manager - Edit Location")
-            .title_bottom("Press 'q' or Esc to exit")
-            .border_type(ratatui::widgets::BorderType::Thick)
-            .padding(Padding::horizontal(1));
-        let id_widget = Paragraph::new(self.loc.id.to_string())
-            .style(Style::default())
-            .block(Block::bordered().title("Location ID"));
-        let name_widget = Paragraph::new(self.loc.name.to_string())
-            .style(if self.selection == EditLocationSelection::Name {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Name"));
-        let comment_widget = Paragraph::new(self.loc.comment.clone().unwrap_or("".to_string()))
-            .style(if self.selection == EditLocationSelection::Comment {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered().title("Comment"));
-        let cancel_button = Paragraph::new("Cancel".to_string())
-            .style(if self.selection == EditLocationSelection::Cancel {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered());
-        let save_button = Paragraph::new("Save Changes".to_string())
-            .style(if self.selection == EditLocationSelection::Save {
-                Style::default().yellow()
-            } else {
-                Style::default()
-            })
-            .block(Block::bordered());
-
-        terminal.draw(|frame| {
-            let inner_area = border.inner(frame.area());
-            let [id_area, name_area, comment_area, cancel_area, save_area] =
-                vertical.areas(inner_area);
-            frame.render_widget(border, frame.area());
-            frame.render_widget(id_widget, id_area);
-            frame.render_widget(name_widget, name_area);
-            frame.render_widget(comment_widget, comment_area);
-            frame.render_widget(cancel_button, cancel_area);
-            frame.render_widget(save_button, save_area);
-            match self.selection {
-                EditLocationSelection::Name => {
-                    frame.set_cursor_position(Position::new(
-                        name_area.x + self.cursor_position + 1,
-                        name_area.y + 1,
-                    ));
-                }
-                EditLocationSelection::Comment => {
-                    frame.set_cursor_position(Position::new(
-                        comment_area.x + self.cursor_position + 1,
-                        comment_area.y + 1,
-                    ));
-                }
-                _ => (),
-            }
-        })?;
-
-        //Handle Input
-        if let Some(key) = event::read()?.as_key_press_event() {
-            match self.selection {
-                EditLocationSelection::Name => match key.code {
-                    KeyCode::Char(c) => {
-                        self.loc.name.insert(self.cursor_position.into(), c);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            self.loc.name.remove(self.cursor_position.into());
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.loc.name.len() as u16 {
-                            self.loc.name.remove(self.cursor_position.into());
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position = self
-                            .cursor_position
-                            .saturating_add(1)
-                            .min(self.loc.name.len() as u16)
-                    }
-                    _ => {}
-                },
-
-                EditLocationSelection::Comment => match key.code {
-                    KeyCode::Char(c) => {
-                        let mut comment = self.loc.comment.clone().unwrap_or_default();
-                        comment.insert(self.cursor_position.into(), c);
-                        self.loc.comment = Some(comment);
-                        self.cursor_position += 1
-                    }
-                    KeyCode::Backspace => {
-                        if self.cursor_position != 0 {
-                            self.cursor_position -= 1;
-                            let mut comment = self.loc.comment.clone().unwrap_or_default();
-                            comment.remove(self.cursor_position.into());
-                            self.loc.comment = Some(comment);
-                        }
-                    }
-                    KeyCode::Delete => {
-                        if self.cursor_position != self.loc.comment.as_ref().unwrap().len() as u16 {
-                            let mut comment = self.loc.comment.clone().unwrap_or_default();
-                            comment.remove(self.cursor_position.into());
-                            self.loc.comment = Some(comment);
-                        }
-                    }
-                    KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
-                    KeyCode::Right => {
-                        self.cursor_position = self
-                            .cursor_position
-                            .saturating_add(1)
-                            .min(self.loc.comment.as_ref().unwrap().len() as u16)
-                    }
-                    _ => {}
-                },
-                EditLocationSelection::Cancel => match key.code {
-                    KeyCode::Enter => self.next_state = AppState::Exit,
-                    _ => (),
-                },
-                EditLocationSelection::Save => match key.code {
-                    KeyCode::Enter => {
-                        self.save_location(db)?;
-                        self.next_state = AppState::Exit
-                    }
-                    _ => (),
-                },
-            }
-
-            match key.code {
-                KeyCode::Esc => self.next_state = AppState::Exit,
-                KeyCode::Down | KeyCode::Tab => {
-                    self.selection = self.selection.next();
-                    self.cursor_position = 0
-                }
-                KeyCode::Up => {
-                    self.selection = self.selection.previous();
-                    self.cursor_position = 0
-                }
-                _ => {}
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        let db = ctx.db;
+        let action = match *event {
+            Event::Key(key) if key.is_press() => self.form.handle_key(key, db),
+            Event::Mouse(mouse) => self.form.handle_mouse(mouse, db),
+            _ => FormAction::None,
+        };
+        match action {
+            FormAction::Save => {
+                self.form.submit(db)?;
+                ctx.notify(
+                    Level::Success,
+                    format!("Saved location {}", self.form.value(ID)),
+                );
+                self.next_state = AppState::Exit
             }
+            FormAction::Cancel => self.next_state = AppState::Exit,
+            FormAction::None => {}
         }
         Ok(())
     }
 
+    fn title(&self) -> String {
+        format!("Edit Location {}", self.id)
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
+    fn request_leave(&mut self) -> bool {
+        self.form.request_leave()
+    }
+    fn is_asking_to_leave(&self) -> bool {
+        self.form.is_asking_to_leave()
+    }
 
     fn refresh(&mut self, db: &inventory::Inventory) {
         //check if we need to load
-        if self.loc.id != self.id {
-            self.loc = db.search_location_id(self.id).unwrap(); //shouldn't be none, as we shouldn't get here if it doesn't exist
-            if self.loc.comment.is_none() {
-                self.loc.comment = Some(String::new());
-            }
+        if self.form.integer(ID) != Some(self.id)
+            && let Some(loc) = db.search_location_id(self.id)
+        {
+            self.form.set_value(ID, loc.id.to_string());
+            self.form.set_value(NAME, loc.name);
+            self.form
+                .set_value(COMMENT, loc.comment.unwrap_or_default());
+            self.form.set_value(
+                PARENT,
+                loc.parent_id.map(|id| id.to_string()).unwrap_or_default(),
+            );
+            self.form.mark_clean();
         }
     }
 }
 
 #[cfg(test)]
 mod edit_location_tests {
+    use super::super::driver::Driver;
+    use super::super::form::Focus;
     use super::*;
+    use crate::seed::fixtures;
     #[test]
     fn test_creation() {
         let my_applet = EditLocationApplet::new(1);
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert_eq!(my_applet.loc.id, -1);
-        assert_eq!(my_applet.loc.name, "".to_string());
-        assert_eq!(my_applet.loc.comment, None);
+        assert_eq!(my_applet.form.value(ID), "");
+        assert_eq!(my_applet.form.value(NAME), "");
+        assert_eq!(my_applet.form.value(COMMENT), "");
         assert_eq!(my_applet.id, 1);
-        assert_eq!(my_applet.cursor_position, 0);
-        assert_eq!(my_applet.selection, EditLocationSelection::Name);
+        assert_eq!(my_applet.form.cursor_position, 0);
+        assert_eq!(my_applet.form.focus, Focus::Field(NAME));
     }
 
     #[test]
     fn test_refresh() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
 
         let mut my_applet = EditLocationApplet::new(1);
         my_applet.refresh(&my_inv);
+        assert_eq!(my_applet.form.value(ID), "1");
+        assert_eq!(my_applet.form.value(NAME), "location1");
+        assert_eq!(my_applet.form.value(COMMENT), "comment1");
 
-        assert_eq!(
-            my_applet.loc,
-            inventory::Location {
-                id: 1,
-                name: "location1".into(),
-                comment: Some("comment1".into())
-            }
-        );
-
-        my_applet.loc.comment = None;
-        assert_eq!(
-            my_applet.loc,
-            inventory::Location {
-                id: 1,
-                name: "location1".into(),
-                comment: None
-            }
-        );
+        my_applet.form.set_value(COMMENT, "");
         my_applet.refresh(&my_inv);
-        assert_eq!(
-            my_applet.loc,
-            inventory::Location {
-                id: 1,
-                name: "location1".into(),
-                comment: None
-            }
-        );
+        assert_eq!(my_applet.form.value(ID), "1");
+        assert_eq!(my_applet.form.value(NAME), "location1");
+        assert_eq!(my_applet.form.value(COMMENT), "");
     }
 
     #[test]
     fn test_save_parsing() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
         let mut my_applet = EditLocationApplet::new(1);
         my_applet.refresh(&my_inv);
+        let form = &mut my_applet.form;
 
-        my_applet.loc.name = "".to_string();
-        assert!(my_applet.save_location(&my_inv).is_err());
-        my_applet.loc.name = "Something else".to_string();
-        assert!(my_applet.save_location(&my_inv).is_ok());
-        my_applet.loc.comment = None;
-        assert!(my_applet.save_location(&my_inv).is_ok());
-        my_applet.loc.comment = Some("Other Comment".into());
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(NAME, "");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(NAME, "Something else");
+        assert!(form.submit(&my_inv).is_ok());
+        form.set_value(COMMENT, "");
+        assert!(form.submit(&my_inv).is_ok());
+        form.set_value(COMMENT, "Other Comment");
+        assert!(form.submit(&my_inv).is_ok());
     }
 
     #[test]
     fn test_save() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
         let mut my_applet = EditLocationApplet::new(1);
         my_applet.refresh(&my_inv);
+        let form = &mut my_applet.form;
         assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
+            my_inv.search_location_id(1),
             Some(inventory::Location {
                 id: 1,
                 name: "location1".to_string(),
                 comment: Some("comment1".to_string()),
+                parent_id: None,
             })
         );
 
-        my_applet.loc.name = "newname".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(NAME, "newname");
+        assert!(form.submit(&my_inv).is_ok());
         assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
+            my_inv.search_location_id(1),
             Some(inventory::Location {
                 id: 1,
                 name: "newname".to_string(),
                 comment: Some("comment1".to_string()),
+                parent_id: None,
             })
         );
 
-        my_applet.loc.name = "".into();
-        assert!(my_applet.save_location(&my_inv).is_err());
+        form.set_value(NAME, "");
+        assert!(form.submit(&my_inv).is_err());
         assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
+            my_inv.search_location_id(1),
             Some(inventory::Location {
                 id: 1,
                 name: "newname".to_string(),
                 comment: Some("comment1".to_string()),
+                parent_id: None,
             })
         );
-        my_applet.loc.name = "newername".into();
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(NAME, "newername");
+        assert!(form.submit(&my_inv).is_ok());
         assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
+            my_inv.search_location_id(1),
             Some(inventory::Location {
                 id: 1,
                 name: "newername".to_string(),
                 comment: Some("comment1".to_string()),
+                parent_id: None,
             })
         );
 
-        my_applet.loc.comment = Some("".into());
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(COMMENT, "");
+        assert!(form.submit(&my_inv).is_ok());
         assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
+            my_inv.search_location_id(1),
             Some(inventory::Location {
                 id: 1,
                 name: "newername".to_string(),
                 comment: None,
+                parent_id: None,
             })
         );
 
-        my_applet.loc.comment = Some("Other Comment".into());
-        assert!(my_applet.save_location(&my_inv).is_ok());
+        form.set_value(COMMENT, "Other Comment");
+        assert!(form.submit(&my_inv).is_ok());
         assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
+            my_inv.search_location_id(1),
             Some(inventory::Location {
                 id: 1,
                 name: "newername".to_string(),
                 comment: Some("Other Comment".into()),
+                parent_id: None,
             })
         );
+    }
 
-        my_applet.loc.comment = None;
-        assert!(my_applet.save_location(&my_inv).is_ok());
-        assert_eq!(
-            my_inv.search_location_id(my_applet.loc.id),
-            Some(inventory::Location {
-                id: 1,
-                name: "newername".to_string(),
-                comment: None,
-            })
-        );
+    #[test]
+    fn test_save_parent() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_applet = EditLocationApplet::new(1);
+        my_applet.refresh(&my_inv);
+        assert_eq!(my_applet.form.value(PARENT), "");
+        let form = &mut my_applet.form;
+
+        form.set_value(PARENT, "2");
+        assert!(form.submit(&my_inv).is_ok());
+        assert_eq!(my_inv.search_location_id(1).unwrap().parent_id, Some(2));
+
+        //missing parents and loops are refused
+        form.set_value(PARENT, "9");
+        assert!(form.submit(&my_inv).is_err());
+        form.set_value(PARENT, "1");
+        assert!(form.submit(&my_inv).is_err());
+
+        form.set_value(PARENT, "");
+        assert!(form.submit(&my_inv).is_ok());
+        assert_eq!(my_inv.search_location_id(1).unwrap().parent_id, None);
     }
 
-    fn fill_db(my_inv: &inventory::Inventory) {
-        for i in 0..5 {
-            let loc = inventory::Location {
-                id: i,
-                name: format!("location{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-            };
-            assert!(my_inv.add_location(&loc).is_ok());
-        }
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        let loc = inventory::Location {
+            id: 7,
+            name: "Garage".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        assert!(my_inv.add_location(&loc).is_ok());
+        let mut my_driver = Driver::new(EditLocationApplet::new(7), my_inv);
+        my_driver.assert_screen_contains("Garage");
+
+        //leaving with changes asks first
+        my_driver.type_text(" 2").keys("Esc");
+        assert_eq!(my_driver.last_state(), AppState::NoChange);
+        my_driver.assert_screen_contains("Unsaved Changes");
+        //a reflexive Enter keeps editing
+        my_driver.keys("Enter");
+        assert_eq!(my_driver.last_state(), AppState::NoChange);
+        my_driver.keys("Esc Right Enter");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
+        assert_eq!(my_driver.db.search_location_id(7).unwrap().name, "Garage 2");
     }
 }
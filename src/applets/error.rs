@@ -1,9 +1,14 @@
 use super::applet::Applet;
+use super::context::EventContext;
+use super::help::{self, HelpOverlay};
+use super::mouse;
 use crate::AppState;
-use crate::db::inventory;
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
-use ratatui::layout::{Constraint, Layout};
+use crate::events::AppEvent;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::Event;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Style;
 use ratatui::widgets::{Block, Paragraph};
 
@@ -11,8 +16,14 @@ pub struct ErrorApplet {
     next_state: AppState,
     error_text: String,
     selection: ErrorSelection,
+    accept_area: Rect,
+    keys: KeyReader,
+    help: HelpOverlay,
 }
 
+const ACTIONS: &[Action] = &[Action::Select, Action::Back, Action::Quit, Action::Help];
+const HINTS: &[Action] = &[Action::Select, Action::Help];
+
 #[derive(PartialEq, Debug)]
 enum ErrorSelection {
     Accept,
@@ -24,20 +35,16 @@ impl ErrorApplet {
             next_state: AppState::NoChange,
             error_text: text,
             selection: ErrorSelection::Accept,
+            accept_area: Rect::default(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
         }
     }
 }
 
 impl Applet for ErrorApplet {
-    fn run(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-        _db: &inventory::Inventory,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.next_state = AppState::NoChange;
-        let border = Block::bordered()
-            .title_top("Inventory Manager")
-            .title_bottom("Press 'q' or Esc to exit");
+    fn render(&mut self, frame: &mut Frame) {
+        let border = Block::bordered().title_bottom(help::hint(Context::List, HINTS));
         let vertical = Layout::vertical([
             Constraint::Fill(1),
             Constraint::Length(1),
@@ -49,38 +56,59 @@ impl Applet for ErrorApplet {
             .style(Style::default())
             .centered();
         let line2 = Paragraph::new(self.error_text.clone())
-            .style(Style::default().red())
+            .style(theme::current().error)
             .centered();
         let accept_button = Paragraph::new("Ok")
             .style(if self.selection == ErrorSelection::Accept {
-                Style::default().yellow()
+                theme::current().focus
             } else {
                 Style::default()
             })
             .centered()
             .block(Block::bordered());
-        terminal.draw(|frame| {
-            let inner_area = border.inner(frame.area());
-            let [_, l1_area, l2_area, accept_area, _] = vertical.areas(inner_area);
-            frame.render_widget(border, frame.area());
-            frame.render_widget(line1, l1_area);
-            frame.render_widget(line2, l2_area);
-            frame.render_widget(
-                accept_button,
-                accept_area.centered_horizontally(Constraint::Length(20)),
-            );
-        })?;
-        if let Some(key) = event::read()?.as_key_press_event() {
-            match key.code {
-                KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {
+        let inner_area = border.inner(frame.area());
+        let [_, l1_area, l2_area, accept_area, _] = vertical.areas(inner_area);
+        frame.render_widget(border, frame.area());
+        frame.render_widget(line1, l1_area);
+        frame.render_widget(line2, l2_area);
+        self.accept_area = accept_area.centered_horizontally(Constraint::Length(20));
+        frame.render_widget(accept_button, self.accept_area);
+        self.help.render(frame, "Error", Context::List, ACTIONS);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        _ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        if self.help.dismiss(event) {
+            return Ok(());
+        }
+        match *event {
+            Event::Key(key) if key.is_press() => match self.keys.action(Context::List, key) {
+                Some(Action::Select | Action::Back | Action::Quit) => {
                     self.next_state = AppState::Exit
                 }
+                Some(Action::Help) => self.help.open(),
                 _ => {}
+            },
+            Event::Mouse(mouse)
+                if mouse::is_left_click(&mouse) && mouse::contains(self.accept_area, &mouse) =>
+            {
+                self.next_state = AppState::Exit
             }
+            _ => {}
         }
 
         Ok(())
     }
+    fn title(&self) -> String {
+        "Error".to_string()
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
@@ -88,7 +116,9 @@ impl Applet for ErrorApplet {
 
 #[cfg(test)]
 mod error_tests {
+    use super::super::driver::Driver;
     use super::*;
+    use crate::db::inventory;
     #[test]
     fn test_creation() {
         let my_applet = ErrorApplet::new("msg".into());
@@ -103,7 +133,19 @@ mod error_tests {
         my_applet.refresh(&my_inv); //ensure it doesn't panic
 
         assert_eq!(my_applet.next_state, AppState::NoChange);
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        let mut my_driver = Driver::new(ErrorApplet::new("msg".into()), my_inv);
+        my_driver.assert_screen_contains("Inventory Manager Encountered an Error");
+        my_driver.assert_screen_contains("msg");
 
-        //would like to test run, but not sure how
+        //the help popup swallows the next key
+        my_driver.keys("? Enter");
+        assert_eq!(my_driver.last_state(), AppState::NoChange);
+        my_driver.click_text("Ok");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
     }
 }
@@ -0,0 +1,211 @@
+use super::applet::Applet;
+use super::context::EventContext;
+use super::form::{Field, Form, FormAction};
+use super::notifications::Level;
+use crate::AppState;
+use crate::csv::{self, Records};
+use crate::db::inventory;
+use crate::events::AppEvent;
+use crossterm::event::Event;
+use ratatui::Frame;
+use std::fs;
+use std::path::Path;
+
+const RECORDS: usize = 0;
+const SEARCH: usize = 1;
+const FILE: usize = 2;
+
+/// Sent back by the worker once the file has been written.
+struct Written {
+    file: String,
+    records: Records,
+    count: usize,
+    result: Result<(), String>,
+}
+
+/// Writes items or locations to a CSV file.
+///
+/// The records are read on the UI thread, where the database lives, and the file is written by
+/// a background worker so a slow disk does not freeze the screen.
+pub struct ExportApplet {
+    next_state: AppState,
+    form: Form,
+    /// True while a worker is writing the file.
+    writing: bool,
+}
+
+impl ExportApplet {
+    pub fn new() -> Self {
+        let mut form = Form::new("Export to CSV", check_file)
+            .field(
+                Field::text("Records (items or locations)")
+                    .required()
+                    .validator(records_valid),
+            )
+            .field(Field::text("Search (only matching names or comments)"))
+            .field(Field::text("File").required());
+        form.set_value(RECORDS, "items");
+        form.set_value(FILE, "inventory.csv");
+        form.mark_clean();
+        Self {
+            next_state: AppState::NoChange,
+            form,
+            writing: false,
+        }
+    }
+
+    fn start_export(&mut self, ctx: &mut EventContext) -> Result<(), Box<dyn std::error::Error>> {
+        self.form.submit(ctx.db)?;
+        let records = Records::parse(self.form.value(RECORDS))?;
+        let search = self.form.optional_text(SEARCH);
+        let (text, count) = csv::export(ctx.db, records, search.as_deref())?;
+        let file = self.form.value(FILE).to_string();
+        self.writing = true;
+        ctx.spawn(move || Written {
+            result: fs::write(&file, text).map_err(|e| e.to_string()),
+            file,
+            records,
+            count,
+        });
+        Ok(())
+    }
+
+    fn finish_export(&mut self, written: &Written, ctx: &mut EventContext) {
+        self.writing = false;
+        match &written.result {
+            Ok(()) => {
+                ctx.notify(
+                    Level::Success,
+                    format!(
+                        "Exported {} to {}",
+                        written.records.count(written.count),
+                        written.file
+                    ),
+                );
+                self.next_state = AppState::Exit;
+            }
+            Err(e) => ctx.notify(
+                Level::Error,
+                format!("Could not write {}: {e}", written.file),
+            ),
+        }
+    }
+}
+
+fn records_valid(value: &str, _db: &inventory::Inventory) -> Result<(), String> {
+    Records::parse(value)
+        .map(|_| ())
+        .map_err(|_| "Enter items or locations".to_string())
+}
+
+/// Runs as the form's save action, catching a missing folder before any work is done.
+fn check_file(form: &Form, _db: &inventory::Inventory) -> Result<(), Box<dyn std::error::Error>> {
+    let file = Path::new(form.value(FILE));
+    match file.parent() {
+        Some(folder) if !folder.as_os_str().is_empty() && !folder.is_dir() => {
+            Err(form.error(&format!("The folder {} does not exist", folder.display())))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl Applet for ExportApplet {
+    fn render(&mut self, frame: &mut Frame) {
+        self.form.render(frame);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let event = match event {
+            AppEvent::Input(event) => event,
+            AppEvent::Worker(_, result) => {
+                if let Some(written) = result.downcast_ref::<Written>() {
+                    self.finish_export(written, ctx);
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        let action = match *event {
+            Event::Key(key) if key.is_press() => self.form.handle_key(key, ctx.db),
+            Event::Mouse(mouse) => self.form.handle_mouse(mouse, ctx.db),
+            _ => FormAction::None,
+        };
+        match action {
+            FormAction::Save if !self.writing => self.start_export(ctx)?,
+            FormAction::Save | FormAction::None => {}
+            FormAction::Cancel => self.next_state = AppState::Exit,
+        }
+        Ok(())
+    }
+
+    fn title(&self) -> String {
+        "Export to CSV".to_string()
+    }
+    fn get_next_state(&self) -> AppState {
+        self.next_state.clone()
+    }
+    fn request_leave(&mut self) -> bool {
+        self.form.request_leave()
+    }
+    fn is_asking_to_leave(&self) -> bool {
+        self.form.is_asking_to_leave()
+    }
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::super::driver::Driver;
+    use super::*;
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        let item = inventory::Item {
+            id: 104,
+            name: "screws, M3".to_string(),
+            comment: None,
+            location_id: None,
+        };
+        assert!(my_inv.add_item(&item).is_ok());
+        let path =
+            std::env::temp_dir().join(format!("inventory_export_{}.csv", std::process::id()));
+        let mut my_driver = Driver::new(ExportApplet::new(), my_inv);
+        my_driver.assert_screen_contains("inventory.csv");
+
+        //records must be items or locations
+        my_driver.type_text("x").keys("Tab");
+        my_driver.assert_screen_contains("Enter items or locations");
+        my_driver.keys("BackTab Backspace");
+
+        //point the file at a folder that does not exist, then fix it
+        let missing = path.with_file_name("missing_folder").join("out.csv");
+        let missing = missing.to_str().unwrap();
+        my_driver
+            .keys("Tab Tab")
+            .keys(&"Backspace ".repeat("inventory.csv".len()))
+            .type_text(missing)
+            .keys("Ctrl+s");
+        my_driver.assert_screen_contains("does not exist");
+        assert_eq!(my_driver.last_state(), AppState::NoChange);
+
+        my_driver
+            .keys("BackTab BackTab")
+            .keys(&"Backspace ".repeat(missing.chars().count()))
+            .type_text(path.to_str().unwrap())
+            .keys("Ctrl+s");
+        my_driver.wait_for_worker();
+        assert_eq!(my_driver.last_state(), AppState::Exit);
+        my_driver.assert_screen_contains("Exported 1 item");
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            text,
+            "id,name,comment,location_id,location\r\n104,\"screws, M3\",,,\r\n"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,367 @@
+use super::mouse::{self, ClickTracker};
+use super::sort::{SortState, Sortable};
+use crate::keymap::Action;
+use crate::theme;
+use crossterm::event::{KeyCode, MouseEvent, MouseEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Position, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, TableState};
+
+/// A less/vim style filter line shown under the list tables.
+///
+/// `/` opens the bar, typing narrows the rows, Enter keeps the filter and returns to the table,
+/// and Esc clears it.
+#[derive(Default)]
+pub struct FilterBar {
+    query: String,
+    cursor_position: usize,
+    editing: bool,
+}
+
+impl FilterBar {
+    pub fn open(&mut self) {
+        self.editing = true;
+        self.cursor_position = self.query.chars().count();
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.cursor_position = 0;
+        self.editing = false;
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// A filter is active while the bar is open or a query has been kept.
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+
+    /// Handles a key while the bar is open, returning true if the query changed.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        let offset = |query: &str, pos: usize| {
+            query
+                .char_indices()
+                .nth(pos)
+                .map(|(i, _)| i)
+                .unwrap_or(query.len())
+        };
+        match code {
+            KeyCode::Char(c) => {
+                self.query
+                    .insert(offset(&self.query, self.cursor_position), c);
+                self.cursor_position += 1;
+                return true;
+            }
+            KeyCode::Backspace if self.cursor_position != 0 => {
+                self.cursor_position -= 1;
+                self.query.remove(offset(&self.query, self.cursor_position));
+                return true;
+            }
+            KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
+            KeyCode::Right => {
+                self.cursor_position = (self.cursor_position + 1).min(self.query.chars().count())
+            }
+            KeyCode::Enter => self.editing = false,
+            KeyCode::Esc => {
+                let changed = !self.query.is_empty();
+                self.clear();
+                return changed;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Case-insensitive match of the query against any of the cells.
+    pub fn matches(&self, cells: &[&str]) -> bool {
+        let query = self.query.to_lowercase();
+        cells.iter().any(|c| c.to_lowercase().contains(&query))
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, matches: usize, total: usize) {
+        let line = Line::from(vec![
+            Span::raw("/"),
+            Span::styled(self.query.as_str(), theme::current().focus),
+            Span::raw(format!("  ({matches} of {total} match)")),
+        ]);
+        frame.render_widget(Paragraph::new(line), area);
+        if self.editing {
+            frame.set_cursor_position(Position::new(
+                area.x + self.cursor_position as u16 + 1,
+                area.y,
+            ));
+        }
+    }
+}
+
+/// A record shown as a row of a list table.
+pub trait ListRow: Sortable {
+    /// The ID the row is found by again after the table is sorted, filtered or reloaded.
+    fn id(&self) -> i64;
+    /// The text of each column, which the filter matches against.
+    fn cells(&self) -> Vec<String>;
+}
+
+/// The rows of a list table with their sort, filter and selection, shared by the list applets.
+pub struct ListTable<T> {
+    /// Every row, in sort order.
+    pub rows: Vec<T>,
+    /// Indices into `rows` of the rows that pass the filter, in display order.
+    pub visible: Vec<usize>,
+    pub sort: Option<SortState>,
+    pub filter: FilterBar,
+    pub state: TableState,
+    /// Where the table was last drawn, to find the row under a click.
+    pub area: Rect,
+    clicks: ClickTracker,
+}
+
+impl<T> Default for ListTable<T> {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            visible: Vec::new(),
+            sort: None,
+            filter: FilterBar::default(),
+            state: TableState::default().with_selected_cell(Some((0, 0))),
+            area: Rect::default(),
+            clicks: ClickTracker::default(),
+        }
+    }
+}
+
+impl<T: ListRow> ListTable<T> {
+    pub fn selected(&self) -> Option<&T> {
+        self.state
+            .selected()
+            .and_then(|row| self.visible.get(row))
+            .map(|&i| &self.rows[i])
+    }
+
+    /// The rows passing the filter, in display order.
+    pub fn visible_rows(&self) -> impl Iterator<Item = &T> {
+        self.visible.iter().map(|&i| &self.rows[i])
+    }
+
+    /// Replaces the rows, keeping the sort, the filter and the selected row.
+    pub fn set_rows(&mut self, rows: Vec<T>) {
+        let selected_id = self.selected().map(T::id);
+        self.rows = rows;
+        if let Some(sort) = self.sort {
+            sort.sort(&mut self.rows);
+        }
+        self.update_visible(selected_id);
+    }
+
+    /// Sorts by `column`, or flips the order if the table is already sorted by it.
+    pub fn toggle_sort(&mut self, column: usize) {
+        let sort = SortState::toggle(self.sort, column);
+        self.sort = Some(sort);
+        let selected_id = self.selected().map(T::id);
+        sort.sort(&mut self.rows);
+        self.update_visible(selected_id);
+    }
+
+    pub fn handle_filter_key(&mut self, code: KeyCode) {
+        let selected_id = self.selected().map(T::id);
+        if self.filter.handle_key(code) {
+            self.update_visible(selected_id);
+        }
+    }
+
+    /// Recomputes the rows passing the filter, keeping the row with `selected_id` selected if it
+    /// is still visible and falling back to the first row otherwise.
+    fn update_visible(&mut self, selected_id: Option<i64>) {
+        self.visible = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| {
+                let cells = r.cells();
+                self.filter
+                    .matches(&cells.iter().map(String::as_str).collect::<Vec<&str>>())
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let row = self
+            .visible
+            .iter()
+            .position(|&i| Some(self.rows[i].id()) == selected_id);
+        if self.visible.is_empty() {
+            self.state.select(None);
+        } else {
+            self.state.select(Some(row.unwrap_or(0)));
+        }
+    }
+
+    /// Handles the actions every list table shares: moving the selection, sorting by the selected
+    /// column, and using the filter. Returns false for any other action.
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Back if self.filter.is_active() => self.handle_filter_key(KeyCode::Esc),
+            Action::Down => self.state.select_next(),
+            Action::Up => self.state.select_previous(),
+            Action::Left => self.state.select_previous_column(),
+            Action::Right => self.state.select_next_column(),
+            Action::Top => self.state.select_first(),
+            Action::Bottom => self.state.select(self.visible.len().checked_sub(1)),
+            Action::Sort => self.toggle_sort(self.state.selected_column().unwrap_or(0)),
+            Action::Filter => self.filter.open(),
+            Action::NextMatch if self.filter.is_active() => {
+                self.state
+                    .select(wrap_selection(self.state.selected(), 1, self.visible.len()))
+            }
+            Action::PreviousMatch if self.filter.is_active() => self.state.select(wrap_selection(
+                self.state.selected(),
+                -1,
+                self.visible.len(),
+            )),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Scrolls the selection with the wheel and selects the row under a left click. Returns the
+    /// clicked row and whether a click without modifiers completed a double click.
+    pub fn handle_mouse(&mut self, mouse: &MouseEvent) -> Option<(usize, bool)> {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.state.select_next(),
+            MouseEventKind::ScrollUp => self.state.select_previous(),
+            _ if mouse::is_left_click(mouse) => {
+                let offset = self.state.offset();
+                let row = mouse::row_at(self.area, 1, offset, mouse)
+                    .filter(|&row| row < self.visible.len())?;
+                self.state.select(Some(row));
+                return Some((row, mouse.modifiers.is_empty() && self.clicks.click(row)));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Draws the filter bar with the number of rows passing it.
+    pub fn render_filter(&self, frame: &mut Frame, area: Rect) {
+        self.filter
+            .render(frame, area, self.visible.len(), self.rows.len());
+    }
+}
+
+/// Moves a selection by `step` rows, wrapping around at either end.
+pub fn wrap_selection(selected: Option<usize>, step: isize, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let current = selected.unwrap_or(0).min(len - 1) as isize;
+    Some((current + step).rem_euclid(len as isize) as usize)
+}
+
+#[cfg(test)]
+mod filter_tests {
+    use super::*;
+    #[test]
+    fn test_editing() {
+        let mut my_filter = FilterBar::default();
+        assert!(!my_filter.is_active());
+        my_filter.open();
+        assert!(my_filter.is_active());
+        assert!(my_filter.handle_key(KeyCode::Char('a')));
+        assert!(my_filter.handle_key(KeyCode::Char('b')));
+        assert!(!my_filter.handle_key(KeyCode::Left));
+        assert!(my_filter.handle_key(KeyCode::Backspace));
+        assert_eq!(my_filter.query, "b");
+        assert!(!my_filter.handle_key(KeyCode::Enter));
+        assert!(!my_filter.is_editing());
+        assert!(my_filter.is_active());
+
+        my_filter.open();
+        assert!(my_filter.handle_key(KeyCode::Esc));
+        assert!(!my_filter.is_active());
+        assert_eq!(my_filter.query, "");
+    }
+
+    #[test]
+    fn test_matches() {
+        let mut my_filter = FilterBar::default();
+        assert!(my_filter.matches(&["anything"]));
+        my_filter.open();
+        my_filter.handle_key(KeyCode::Char('S'));
+        my_filter.handle_key(KeyCode::Char('c'));
+        assert!(my_filter.matches(&["101", "M3 screws", ""]));
+        assert!(my_filter.matches(&["101", "", "Scissors"]));
+        assert!(!my_filter.matches(&["101", "bolts", "garage"]));
+    }
+
+    struct TestRow(i64, &'static str);
+
+    impl Sortable for TestRow {
+        fn compare(&self, other: &Self, _column: usize) -> std::cmp::Ordering {
+            self.1.cmp(other.1)
+        }
+    }
+
+    impl ListRow for TestRow {
+        fn id(&self) -> i64 {
+            self.0
+        }
+        fn cells(&self) -> Vec<String> {
+            vec![self.0.to_string(), self.1.to_string()]
+        }
+    }
+
+    #[test]
+    fn test_list_table() {
+        let mut my_table = ListTable::default();
+        my_table.set_rows(vec![
+            TestRow(1, "bolt"),
+            TestRow(2, "axe"),
+            TestRow(3, "nut"),
+        ]);
+        assert_eq!(my_table.visible, vec![0, 1, 2]);
+        assert!(my_table.handle_action(Action::Down));
+        assert_eq!(my_table.selected().map(|r| r.0), Some(2));
+
+        //the selection follows its row through sorting, filtering and reloading
+        assert!(my_table.handle_action(Action::Sort));
+        assert_eq!(my_table.selected().map(|r| r.0), Some(2));
+        assert_eq!(my_table.state.selected(), Some(0));
+        assert!(my_table.handle_action(Action::Filter));
+        my_table.handle_filter_key(KeyCode::Char('t'));
+        assert_eq!(
+            my_table.visible_rows().map(|r| r.0).collect::<Vec<i64>>(),
+            vec![1, 3]
+        );
+        assert_eq!(my_table.selected().map(|r| r.0), Some(1));
+        my_table.set_rows(vec![
+            TestRow(3, "nut"),
+            TestRow(1, "bolt"),
+            TestRow(4, "tack"),
+        ]);
+        assert_eq!(
+            my_table.visible_rows().map(|r| r.0).collect::<Vec<i64>>(),
+            vec![1, 3, 4]
+        );
+        assert_eq!(my_table.selected().map(|r| r.0), Some(1));
+
+        assert!(my_table.handle_action(Action::NextMatch));
+        assert_eq!(my_table.selected().map(|r| r.0), Some(3));
+        assert!(my_table.handle_action(Action::Back));
+        assert!(!my_table.filter.is_active());
+        assert!(!my_table.handle_action(Action::Back));
+        assert!(!my_table.handle_action(Action::NextMatch));
+    }
+
+    #[test]
+    fn test_wrap_selection() {
+        assert_eq!(wrap_selection(Some(0), 1, 3), Some(1));
+        assert_eq!(wrap_selection(Some(2), 1, 3), Some(0));
+        assert_eq!(wrap_selection(Some(0), -1, 3), Some(2));
+        assert_eq!(wrap_selection(None, 1, 3), Some(1));
+        assert_eq!(wrap_selection(Some(5), -1, 3), Some(1));
+        assert_eq!(wrap_selection(Some(0), 1, 0), None);
+    }
+}
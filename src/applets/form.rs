@@ -0,0 +1,843 @@
+use super::help::{self, HelpOverlay};
+use super::modal::{Modal, ModalAction};
+use super::mouse;
+use crate::db::inventory::Inventory;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crate::validation;
+use crossterm::event::{Event, KeyCode, KeyEvent, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::Style;
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Padding, Paragraph};
+use std::error;
+use std::fmt;
+
+/// Checks a non-empty field value, returning a message describing the problem on failure.
+pub type Validator = fn(&str, &Inventory) -> Result<(), String>;
+/// Writes a validated form to the database.
+pub type SaveAction = fn(&Form, &Inventory) -> Result<(), Box<dyn error::Error>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    Integer,
+}
+
+pub struct Field {
+    label: &'static str,
+    kind: FieldKind,
+    value: String,
+    /// The value when the form was created or last marked clean.
+    initial: String,
+    required: bool,
+    read_only: bool,
+    grow: bool,
+    validators: Vec<Validator>,
+    pub(super) error: Option<String>,
+}
+
+impl Field {
+    fn new(label: &'static str, kind: FieldKind) -> Self {
+        Self {
+            label,
+            kind,
+            value: String::new(),
+            initial: String::new(),
+            required: false,
+            read_only: false,
+            grow: false,
+            validators: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn text(label: &'static str) -> Self {
+        Self::new(label, FieldKind::Text)
+    }
+
+    pub fn integer(label: &'static str) -> Self {
+        Self::new(label, FieldKind::Integer)
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Read only fields are displayed but skipped when cycling focus.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Lets the field take up any space left over once the other fields are laid out.
+    pub fn grow(mut self) -> Self {
+        self.grow = true;
+        self
+    }
+
+    pub fn validator(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    fn validate(&self, db: &Inventory) -> Result<(), String> {
+        if self.required {
+            validation::not_blank(self.label, &self.value)?;
+        }
+        if self.value.is_empty() {
+            return Ok(());
+        }
+        if self.kind == FieldKind::Integer && self.value.parse::<i64>().is_err() {
+            return Err(format!("Failed to parse {}", self.label));
+        }
+        for validator in &self.validators {
+            validator(&self.value, db)?;
+        }
+        Ok(())
+    }
+
+    fn byte_offset(&self, cursor_position: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor_position)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+
+    fn char_len(&self) -> usize {
+        self.value.chars().count()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Focus {
+    Field(usize),
+    Cancel,
+    Save,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FormAction {
+    None,
+    Cancel,
+    Save,
+}
+
+#[derive(Debug)]
+pub struct FormError {
+    form: &'static str,
+    error_text: String,
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Error: {}", self.form, self.error_text)
+    }
+}
+
+impl error::Error for FormError {}
+
+/// A list of typed fields followed by Cancel and Save buttons.
+///
+/// The form owns focus cycling, text editing and rendering. Applets describe their fields,
+/// hand over a save action and react to the returned `FormAction`.
+pub struct Form {
+    title: &'static str,
+    pub(super) fields: Vec<Field>,
+    pub(super) focus: Focus,
+    pub(super) cursor_position: usize,
+    on_save: SaveAction,
+    /// Where each field and then the two buttons were last drawn, for mouse hit testing.
+    areas: Vec<Rect>,
+    keys: KeyReader,
+    help: HelpOverlay,
+    /// Asks what to do with unsaved changes when leaving a dirty form.
+    confirm: Option<Modal>,
+}
+
+/// The buttons of the unsaved changes modal.
+const CONFIRM_BUTTONS: &[&str] = &["Save", "Discard", "Keep editing"];
+const CONFIRM_SAVE: usize = 0;
+const CONFIRM_DISCARD: usize = 1;
+const CONFIRM_KEEP: usize = 2;
+
+/// The actions a form handles, as listed in the help overlay.
+const ACTIONS: &[Action] = &[
+    Action::NextField,
+    Action::PreviousField,
+    Action::Save,
+    Action::Cancel,
+    Action::Help,
+];
+const HINTS: &[Action] = &[
+    Action::NextField,
+    Action::Save,
+    Action::Cancel,
+    Action::Help,
+];
+
+impl Form {
+    pub fn new(title: &'static str, on_save: SaveAction) -> Self {
+        Self {
+            title,
+            fields: Vec::new(),
+            focus: Focus::Cancel,
+            cursor_position: 0,
+            on_save,
+            areas: Vec::new(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
+            confirm: None,
+        }
+    }
+
+    pub fn field(mut self, field: Field) -> Self {
+        self.fields.push(field);
+        self.focus = self.focus_order()[0];
+        self
+    }
+
+    pub fn value(&self, field: usize) -> &str {
+        &self.fields[field].value
+    }
+
+    /// Replaces a field value. Any inline error is cleared until the field is checked again.
+    pub fn set_value(&mut self, field: usize, value: impl Into<String>) {
+        self.fields[field].value = value.into();
+        self.fields[field].error = None;
+        if self.focus == Focus::Field(field) {
+            self.cursor_position = self.fields[field].char_len();
+        }
+    }
+
+    /// Returns the field value, treating an empty field as `None`.
+    pub fn optional_text(&self, field: usize) -> Option<String> {
+        let value = self.value(field);
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    /// Returns the parsed field value, or `None` if it is empty or not a number.
+    pub fn integer(&self, field: usize) -> Option<i64> {
+        self.value(field).parse::<i64>().ok()
+    }
+
+    pub fn error(&self, msg: &str) -> Box<FormError> {
+        Box::new(FormError {
+            form: self.title,
+            error_text: msg.to_string(),
+        })
+    }
+
+    /// True if any field differs from its value when the form was created or last marked clean.
+    pub fn is_dirty(&self) -> bool {
+        self.fields.iter().any(|f| f.value != f.initial)
+    }
+
+    /// Takes the current values as the unchanged state, e.g. once a record has been loaded.
+    pub fn mark_clean(&mut self) {
+        for field in &mut self.fields {
+            field.initial = field.value.clone();
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.fields.iter().any(|f| f.error.is_some())
+    }
+
+    fn check_field(&mut self, field: usize, db: &Inventory) {
+        let field = &mut self.fields[field];
+        field.error = field.validate(db).err();
+    }
+
+    /// Checks every field, updating the inline errors, and returns the first failure.
+    pub fn validate(&mut self, db: &Inventory) -> Result<(), Box<dyn error::Error>> {
+        for i in 0..self.fields.len() {
+            self.check_field(i, db);
+        }
+        match self.fields.iter().find_map(|f| f.error.as_deref()) {
+            Some(msg) => Err(self.error(msg)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn submit(&mut self, db: &Inventory) -> Result<(), Box<dyn error::Error>> {
+        self.validate(db)?;
+        (self.on_save)(self, db)
+    }
+
+    fn focus_order(&self) -> Vec<Focus> {
+        let mut order: Vec<Focus> = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| !f.read_only)
+            .map(|(i, _)| Focus::Field(i))
+            .collect();
+        order.push(Focus::Cancel);
+        order.push(Focus::Save);
+        order
+    }
+
+    fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+        self.cursor_position = match focus {
+            Focus::Field(i) => self.fields[i].char_len(),
+            _ => 0,
+        };
+    }
+
+    pub fn next(&mut self) {
+        let order = self.focus_order();
+        let pos = order.iter().position(|f| *f == self.focus).unwrap_or(0);
+        self.set_focus(order[(pos + 1) % order.len()]);
+    }
+
+    pub fn previous(&mut self) {
+        let order = self.focus_order();
+        let pos = order.iter().position(|f| *f == self.focus).unwrap_or(0);
+        self.set_focus(order[(pos + order.len() - 1) % order.len()]);
+    }
+
+    /// Handles a key press. Fields are checked as they are edited and when focus leaves them,
+    /// and `FormAction::Save` is only returned once every field passes validation.
+    pub fn handle_key(&mut self, key: KeyEvent, db: &Inventory) -> FormAction {
+        if self.help.dismiss(&Event::Key(key)) {
+            return FormAction::None;
+        }
+        if let Some(confirm) = &mut self.confirm {
+            let action = confirm.handle_key(key);
+            return self.handle_confirm(action, db);
+        }
+        let old_focus = self.focus;
+        match self.keys.action(Context::Form, key) {
+            Some(Action::Cancel) => return self.cancel(),
+            Some(Action::Save) => {
+                self.set_focus(Focus::Save);
+                let action = self.try_save(db);
+                self.check_left_field(old_focus, db);
+                return action;
+            }
+            Some(Action::NextField) => self.next(),
+            Some(Action::PreviousField) => self.previous(),
+            Some(Action::Help) => self.help.open(),
+            _ if self.keys.is_pending() => {}
+            _ => match self.focus {
+                Focus::Field(i) => {
+                    let changed = self.edit_field(i, key.code);
+                    if changed {
+                        self.check_field(i, db);
+                    }
+                }
+                Focus::Cancel if key.code == KeyCode::Enter => return self.cancel(),
+                Focus::Save if key.code == KeyCode::Enter => return self.try_save(db),
+                _ => {}
+            },
+        }
+        self.check_left_field(old_focus, db);
+        FormAction::None
+    }
+
+    /// Handles a mouse event. Clicking a field focuses it and places the cursor under the
+    /// pointer, clicking a button presses it.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, db: &Inventory) -> FormAction {
+        if self.help.dismiss(&Event::Mouse(mouse)) {
+            return FormAction::None;
+        }
+        if let Some(confirm) = &mut self.confirm {
+            let action = confirm.handle_mouse(mouse);
+            return self.handle_confirm(action, db);
+        }
+        if !mouse::is_left_click(&mouse) {
+            return FormAction::None;
+        }
+        let Some(target) = self.areas.iter().position(|a| mouse::contains(*a, &mouse)) else {
+            return FormAction::None;
+        };
+        let old_focus = self.focus;
+        match target.checked_sub(self.fields.len()) {
+            None if !self.fields[target].read_only => {
+                self.set_focus(Focus::Field(target));
+                let column = mouse.column.saturating_sub(self.areas[target].x + 1) as usize;
+                self.cursor_position = column.min(self.fields[target].char_len());
+            }
+            Some(0) => return self.cancel(),
+            Some(1) => {
+                self.set_focus(Focus::Save);
+                let action = self.try_save(db);
+                self.check_left_field(old_focus, db);
+                return action;
+            }
+            _ => {}
+        }
+        self.check_left_field(old_focus, db);
+        FormAction::None
+    }
+
+    /// True if the form can be closed as it is. A dirty form asks about its changes instead, and
+    /// returns the answer as a `FormAction` from the next key press or click.
+    pub fn request_leave(&mut self) -> bool {
+        self.cancel() == FormAction::Cancel
+    }
+
+    /// True while the unsaved changes modal is open.
+    pub fn is_asking_to_leave(&self) -> bool {
+        self.confirm.is_some()
+    }
+
+    /// Leaves a clean form straight away, and asks about a dirty one first.
+    fn cancel(&mut self) -> FormAction {
+        if !self.is_dirty() {
+            return FormAction::Cancel;
+        }
+        self.confirm = Some(
+            Modal::new(
+                "Unsaved Changes",
+                "This form has unsaved changes. Save them before leaving?",
+                CONFIRM_BUTTONS,
+            )
+            .with_selected(CONFIRM_KEEP),
+        );
+        FormAction::None
+    }
+
+    fn handle_confirm(&mut self, action: ModalAction, db: &Inventory) -> FormAction {
+        match action {
+            ModalAction::None => FormAction::None,
+            ModalAction::Choose(CONFIRM_SAVE) => {
+                self.confirm = None;
+                self.set_focus(Focus::Save);
+                self.try_save(db)
+            }
+            ModalAction::Choose(CONFIRM_DISCARD) => {
+                self.confirm = None;
+                FormAction::Cancel
+            }
+            ModalAction::Choose(_) | ModalAction::Dismiss => {
+                self.confirm = None;
+                FormAction::None
+            }
+        }
+    }
+
+    /// Returns `FormAction::Save` if every field is valid, otherwise focuses the first error.
+    fn try_save(&mut self, db: &Inventory) -> FormAction {
+        if self.validate(db).is_ok() {
+            return FormAction::Save;
+        }
+        if let Some(i) = self.fields.iter().position(|f| f.error.is_some()) {
+            self.set_focus(Focus::Field(i));
+        }
+        FormAction::None
+    }
+
+    fn check_left_field(&mut self, old_focus: Focus, db: &Inventory) {
+        if let Focus::Field(i) = old_focus
+            && old_focus != self.focus
+        {
+            self.check_field(i, db);
+        }
+    }
+
+    /// Applies an editing key to a field, returning true if the value changed.
+    fn edit_field(&mut self, i: usize, code: KeyCode) -> bool {
+        let field = &mut self.fields[i];
+        match code {
+            KeyCode::Char(c) => {
+                let offset = field.byte_offset(self.cursor_position);
+                field.value.insert(offset, c);
+                self.cursor_position += 1;
+                return true;
+            }
+            KeyCode::Backspace if self.cursor_position != 0 => {
+                self.cursor_position -= 1;
+                let offset = field.byte_offset(self.cursor_position);
+                field.value.remove(offset);
+                return true;
+            }
+            KeyCode::Delete if self.cursor_position < field.char_len() => {
+                let offset = field.byte_offset(self.cursor_position);
+                field.value.remove(offset);
+                return true;
+            }
+            KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
+            KeyCode::Right => {
+                self.cursor_position = (self.cursor_position + 1).min(field.char_len())
+            }
+            KeyCode::Home => self.cursor_position = 0,
+            KeyCode::End => self.cursor_position = field.char_len(),
+            KeyCode::Enter => self.next(),
+            _ => {}
+        }
+        false
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let border = Block::bordered()
+            .title_bottom(help::hint(Context::Form, HINTS))
+            .border_type(ratatui::widgets::BorderType::Thick)
+            .padding(Padding::horizontal(1));
+
+        let mut constraints: Vec<Constraint> = self
+            .fields
+            .iter()
+            .map(|f| {
+                if f.grow {
+                    Constraint::Min(3)
+                } else {
+                    Constraint::Length(3)
+                }
+            })
+            .collect();
+        constraints.push(Constraint::Length(3));
+        constraints.push(Constraint::Length(3));
+        let areas = Layout::vertical(constraints).split(border.inner(frame.area()));
+        self.areas = areas.to_vec();
+        frame.render_widget(border, frame.area());
+
+        for (i, (field, area)) in self.fields.iter().zip(areas.iter()).enumerate() {
+            let theme = theme::current();
+            let style = if field.read_only {
+                theme.read_only
+            } else if self.focus == Focus::Field(i) {
+                theme.focus
+            } else {
+                theme.text
+            };
+            let mut block = Block::bordered().title(field.label);
+            if let Some(error) = &field.error {
+                block = block
+                    .border_style(theme.error)
+                    .title_bottom(Line::styled(error.as_str(), theme.error));
+            }
+            let widget = Paragraph::new(field.value.as_str())
+                .style(style)
+                .block(block);
+            frame.render_widget(widget, *area);
+            if self.focus == Focus::Field(i) && self.confirm.is_none() {
+                frame.set_cursor_position(Position::new(
+                    area.x + self.cursor_position as u16 + 1,
+                    area.y + 1,
+                ));
+            }
+        }
+
+        let save_label = if self.has_errors() {
+            "Save Changes (fix errors first)"
+        } else {
+            "Save Changes"
+        };
+        let buttons = [(Focus::Cancel, "Cancel"), (Focus::Save, save_label)];
+        for ((focus, label), area) in buttons.into_iter().zip(areas[self.fields.len()..].iter()) {
+            let mut style = if self.focus == focus {
+                theme::current().focus.bold()
+            } else {
+                Style::default().bold()
+            };
+            if focus == Focus::Save && self.has_errors() {
+                style = style.patch(theme::current().disabled);
+            }
+            let button = Paragraph::new(label).style(style).block(Block::bordered());
+            frame.render_widget(button, *area);
+        }
+        if let Some(confirm) = &mut self.confirm {
+            confirm.render(frame);
+        }
+        self.help.render(frame, self.title, Context::Form, ACTIONS);
+    }
+}
+
+#[cfg(test)]
+mod form_tests {
+    use super::*;
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn save_nothing(_form: &Form, _db: &Inventory) -> Result<(), Box<dyn error::Error>> {
+        Ok(())
+    }
+
+    fn test_form() -> Form {
+        Form::new("Test", save_nothing)
+            .field(Field::integer("ID").read_only())
+            .field(Field::text("Name").required())
+            .field(Field::integer("Number"))
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_focus_cycle() {
+        let mut my_form = test_form();
+        assert_eq!(my_form.focus, Focus::Field(1));
+        my_form.next();
+        assert_eq!(my_form.focus, Focus::Field(2));
+        my_form.next();
+        assert_eq!(my_form.focus, Focus::Cancel);
+        my_form.next();
+        assert_eq!(my_form.focus, Focus::Save);
+        my_form.next();
+        assert_eq!(my_form.focus, Focus::Field(1));
+        my_form.previous();
+        assert_eq!(my_form.focus, Focus::Save);
+    }
+
+    #[test]
+    fn test_editing() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+        my_form.handle_key(key(KeyCode::Char('a')), &my_inv);
+        my_form.handle_key(key(KeyCode::Char('é')), &my_inv);
+        my_form.handle_key(key(KeyCode::Char('c')), &my_inv);
+        assert_eq!(my_form.value(1), "aéc");
+        my_form.handle_key(key(KeyCode::Left), &my_inv);
+        my_form.handle_key(key(KeyCode::Backspace), &my_inv);
+        assert_eq!(my_form.value(1), "ac");
+        assert_eq!(my_form.cursor_position, 1);
+        my_form.handle_key(key(KeyCode::Home), &my_inv);
+        my_form.handle_key(key(KeyCode::Delete), &my_inv);
+        assert_eq!(my_form.value(1), "c");
+        assert_eq!(my_form.value(0), "");
+    }
+
+    #[test]
+    fn test_actions() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Esc), &my_inv),
+            FormAction::Cancel
+        );
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::None
+        );
+        assert_eq!(my_form.focus, Focus::Field(2));
+        my_form.next();
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::Cancel
+        );
+        my_form.next();
+
+        //Save is refused while the required name is empty, and focus jumps to it
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::None
+        );
+        assert_eq!(my_form.focus, Focus::Field(1));
+        assert!(my_form.has_errors());
+
+        my_form.handle_key(key(KeyCode::Char('n')), &my_inv);
+        assert!(!my_form.has_errors());
+        my_form.previous();
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::Save
+        );
+
+        //the save binding works from any field
+        my_form.set_focus(Focus::Field(1));
+        assert_eq!(
+            my_form.handle_key(
+                KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+                &my_inv
+            ),
+            FormAction::Save
+        );
+        assert_eq!(my_form.value(1), "n");
+    }
+
+    #[test]
+    fn test_unsaved_changes() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+        my_form.set_value(0, "1");
+        my_form.mark_clean();
+        assert!(!my_form.is_dirty());
+
+        //typing and then undoing it leaves the form clean
+        my_form.handle_key(key(KeyCode::Char('x')), &my_inv);
+        assert!(my_form.is_dirty());
+        my_form.handle_key(key(KeyCode::Backspace), &my_inv);
+        assert!(!my_form.is_dirty());
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Esc), &my_inv),
+            FormAction::Cancel
+        );
+
+        //keep editing is the default, it closes the modal and leaves the values alone
+        my_form.handle_key(key(KeyCode::Char('x')), &my_inv);
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Esc), &my_inv),
+            FormAction::None
+        );
+        assert_eq!(
+            my_form.confirm.as_ref().map(|c| c.selected),
+            Some(CONFIRM_KEEP)
+        );
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::None
+        );
+        assert!(my_form.confirm.is_none());
+        assert_eq!(my_form.value(1), "x");
+
+        //save validates the form like the Save button
+        my_form.handle_key(key(KeyCode::Char('x')), &my_inv);
+        my_form.set_value(2, "abc");
+        my_form.handle_key(key(KeyCode::Esc), &my_inv);
+        my_form.handle_key(key(KeyCode::Right), &my_inv);
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::None
+        );
+        assert_eq!(my_form.focus, Focus::Field(2));
+        my_form.set_value(2, "3");
+        my_form.handle_key(key(KeyCode::Esc), &my_inv);
+        my_form.handle_key(key(KeyCode::Right), &my_inv);
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::Save
+        );
+
+        //discard leaves without saving
+        my_form.handle_key(key(KeyCode::Esc), &my_inv);
+        my_form.handle_key(key(KeyCode::Left), &my_inv);
+        assert_eq!(
+            my_form.handle_key(key(KeyCode::Enter), &my_inv),
+            FormAction::Cancel
+        );
+        assert!(my_form.confirm.is_none());
+    }
+
+    #[test]
+    fn test_help() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+        my_form.handle_key(key(KeyCode::F(1)), &my_inv);
+        assert!(my_form.help.is_open());
+
+        //the key that closes the help is not typed into the field
+        my_form.handle_key(key(KeyCode::Char('x')), &my_inv);
+        assert!(!my_form.help.is_open());
+        assert_eq!(my_form.value(1), "");
+
+        //'?' is just a character in a form
+        my_form.handle_key(key(KeyCode::Char('?')), &my_inv);
+        assert!(!my_form.help.is_open());
+        assert_eq!(my_form.value(1), "?");
+    }
+
+    #[test]
+    fn test_inline_errors() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+
+        //Leaving an empty required field flags it
+        my_form.handle_key(key(KeyCode::Tab), &my_inv);
+        assert_eq!(
+            my_form.fields[1].error.as_deref(),
+            Some("Name cannot be empty")
+        );
+        assert_eq!(my_form.fields[2].error.as_deref(), None);
+
+        //Errors update as you type
+        my_form.handle_key(key(KeyCode::Char('x')), &my_inv);
+        assert_eq!(
+            my_form.fields[2].error.as_deref(),
+            Some("Failed to parse Number")
+        );
+        my_form.handle_key(key(KeyCode::Backspace), &my_inv);
+        assert_eq!(my_form.fields[2].error.as_deref(), None);
+        my_form.handle_key(key(KeyCode::Char('7')), &my_inv);
+        assert_eq!(my_form.fields[2].error.as_deref(), None);
+
+        //spaces alone do not fill a required field
+        my_form.handle_key(key(KeyCode::BackTab), &my_inv);
+        my_form.handle_key(key(KeyCode::Char(' ')), &my_inv);
+        assert_eq!(
+            my_form.fields[1].error.as_deref(),
+            Some("Name cannot be empty")
+        );
+        my_form.handle_key(key(KeyCode::Char('n')), &my_inv);
+        assert_eq!(my_form.fields[1].error.as_deref(), None);
+        assert!(!my_form.has_errors());
+    }
+
+    #[test]
+    fn test_validate() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+        assert!(my_form.validate(&my_inv).is_err());
+        assert!(my_form.has_errors());
+        my_form.set_value(1, "name");
+        assert!(my_form.validate(&my_inv).is_ok());
+        assert!(!my_form.has_errors());
+        my_form.set_value(2, "nan");
+        assert!(my_form.validate(&my_inv).is_err());
+        assert_eq!(
+            my_form.fields[2].error.as_deref(),
+            Some("Failed to parse Number")
+        );
+        my_form.set_value(2, "12");
+        assert!(my_form.validate(&my_inv).is_ok());
+        assert_eq!(my_form.integer(2), Some(12));
+        assert_eq!(my_form.optional_text(0), None);
+    }
+
+    #[test]
+    fn test_mouse() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let mut my_form = test_form();
+        let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+        terminal.draw(|frame| my_form.render(frame)).unwrap();
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let at = |form: &Form, target: usize| (form.areas[target].x, form.areas[target].y + 1);
+        my_form.set_value(2, "12345");
+
+        let (x, y) = at(&my_form, 2);
+        assert_eq!(
+            my_form.handle_mouse(click(x + 10, y), &my_inv),
+            FormAction::None
+        );
+        assert_eq!(my_form.focus, Focus::Field(2));
+        assert_eq!(my_form.cursor_position, 5);
+        assert_eq!(
+            my_form.fields[1].error.as_deref(),
+            Some("Name cannot be empty")
+        );
+        my_form.handle_mouse(click(x + 3, y), &my_inv);
+        assert_eq!(my_form.cursor_position, 2);
+
+        //read only fields can't take focus
+        let (x, y) = at(&my_form, 0);
+        my_form.handle_mouse(click(x + 1, y), &my_inv);
+        assert_eq!(my_form.focus, Focus::Field(2));
+
+        //Save refuses while errors remain, Cancel asks about the unsaved changes
+        let (x, y) = at(&my_form, 4);
+        assert_eq!(my_form.handle_mouse(click(x, y), &my_inv), FormAction::None);
+        assert_eq!(my_form.focus, Focus::Field(1));
+        let (cancel_x, cancel_y) = at(&my_form, 3);
+        assert_eq!(
+            my_form.handle_mouse(click(cancel_x, cancel_y), &my_inv),
+            FormAction::None
+        );
+        assert!(my_form.confirm.is_some());
+        my_form.handle_key(key(KeyCode::Esc), &my_inv);
+        assert!(my_form.confirm.is_none());
+        my_form.set_value(1, "name");
+        assert_eq!(my_form.handle_mouse(click(x, y), &my_inv), FormAction::Save);
+    }
+}
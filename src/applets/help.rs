@@ -0,0 +1,158 @@
+use super::mouse;
+use crate::keymap::{self, Action, Context};
+use crate::theme;
+use crossterm::event::Event;
+use ratatui::Frame;
+use ratatui::layout::Constraint;
+use ratatui::widgets::{Block, Clear, Padding, Row, Table};
+
+/// Actions the `App` handles for every applet, listed after the applet's own.
+const GLOBAL_ACTIONS: &[Action] = &[
+    Action::Palette,
+    Action::Messages,
+    Action::Home,
+    Action::Breadcrumbs,
+];
+
+/// A popup listing the keys bound to an applet's actions.
+///
+/// Applets pass the same action list they handle, so the popup follows the keymap.
+#[derive(Default)]
+pub struct HelpOverlay {
+    open: bool,
+}
+
+impl HelpOverlay {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// While the overlay is open it takes every event, closing on a key press or click.
+    /// Returns true if the event was used by the overlay.
+    pub fn dismiss(&mut self, event: &Event) -> bool {
+        if !self.open {
+            return false;
+        }
+        let closes = match event {
+            Event::Key(key) => key.is_press(),
+            Event::Mouse(mouse) => mouse::is_left_click(mouse),
+            _ => false,
+        };
+        if closes {
+            self.open = false;
+        }
+        true
+    }
+
+    pub fn render(&self, frame: &mut Frame, title: &str, context: Context, actions: &[Action]) {
+        if !self.open {
+            return;
+        }
+        let rows: Vec<Row> = actions
+            .iter()
+            .chain(GLOBAL_ACTIONS)
+            .map(|a| Row::new([keys(*a, context), a.description().to_string()]))
+            .collect();
+        let area = frame.area().centered(
+            Constraint::Max(64),
+            Constraint::Max((actions.len() + GLOBAL_ACTIONS.len()) as u16 + 2),
+        );
+        let table = Table::new(rows, [Constraint::Length(18), Constraint::Fill(1)])
+            .block(
+                Block::bordered()
+                    .title(format!("Help - {title}"))
+                    .title_bottom("Press any key to close")
+                    .border_type(ratatui::widgets::BorderType::Thick)
+                    .padding(Padding::horizontal(1)),
+            )
+            .style(theme::current().text);
+        frame.render_widget(Clear, area);
+        frame.render_widget(table, area);
+    }
+}
+
+/// The keys bound to `action` in `context`, e.g. "k, Up".
+pub fn keys(action: Action, context: Context) -> String {
+    let keys: Vec<String> = keymap::current()
+        .keys(action, context)
+        .map(|k| k.to_string())
+        .collect();
+    keys.join(", ")
+}
+
+/// A compact line for the bottom border naming the first key of each action, e.g.
+/// "s sort  q quit  ? help".
+pub fn hint(context: Context, actions: &[Action]) -> String {
+    let hints: Vec<String> = actions
+        .iter()
+        .filter_map(|a| {
+            keymap::current()
+                .keys(*a, context)
+                .next()
+                .map(|k| format!("{k} {}", a.name().replace('_', " ")))
+        })
+        .collect();
+    hints.join("  ")
+}
+
+#[cfg(test)]
+mod help_tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_hint() {
+        assert_eq!(
+            hint(Context::List, &[Action::Sort, Action::Quit, Action::Help]),
+            "s sort  q quit  ? help"
+        );
+        assert_eq!(
+            hint(Context::Form, &[Action::NextField, Action::Help]),
+            "Tab next field  F1 help"
+        );
+        assert_eq!(keys(Action::Help, Context::List), "?, F1");
+        assert_eq!(keys(Action::Help, Context::Form), "F1");
+    }
+
+    #[test]
+    fn test_dismiss() {
+        let press = Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        let mut my_help = HelpOverlay::default();
+        assert!(!my_help.dismiss(&press));
+
+        my_help.open();
+        assert!(my_help.dismiss(&Event::FocusGained));
+        assert!(my_help.is_open());
+        assert!(my_help.dismiss(&press));
+        assert!(!my_help.is_open());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut my_help = HelpOverlay::default();
+        my_help.open();
+        terminal
+            .draw(|frame| {
+                my_help.render(frame, "Test", Context::List, &[Action::Sort, Action::Help])
+            })
+            .unwrap();
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(text.contains("Help - Test"));
+        assert!(text.contains("Sort by the selected column"));
+        assert!(text.contains("?, F1"));
+        assert!(text.contains("Go back to the dashboard"));
+    }
+}
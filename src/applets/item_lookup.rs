@@ -1,8 +1,13 @@
 use super::applet::Applet;
+use super::context::EventContext;
+use super::help::{self, HelpOverlay};
 use crate::AppState;
 use crate::db::inventory;
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
+use crate::events::AppEvent;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
 use ratatui::layout::{Constraint, Layout, Position};
 use ratatui::style::Style;
 use ratatui::widgets::{Block, Paragraph};
@@ -13,8 +18,13 @@ pub struct ItemLookupApplet {
     next_state: AppState,
     cursor_position: u16,
     id: String,
+    keys: KeyReader,
+    help: HelpOverlay,
 }
 
+const ACTIONS: &[Action] = &[Action::Save, Action::Cancel, Action::Help];
+const HINTS: &[Action] = &[Action::Cancel, Action::Help];
+
 #[derive(Debug)]
 struct ItemLookupError {
     error_text: String,
@@ -42,6 +52,8 @@ impl ItemLookupApplet {
             next_state: AppState::NoChange,
             cursor_position: 0,
             id: String::default(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
         }
     }
 
@@ -61,15 +73,8 @@ impl ItemLookupApplet {
 }
 
 impl Applet for ItemLookupApplet {
-    fn run(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-        db: &inventory::Inventory,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.next_state = AppState::NoChange;
-        let border = Block::bordered()
-            .title_top("Inventory Manager")
-            .title_bottom("Press 'q' or Esc to exit");
+    fn render(&mut self, frame: &mut Frame) {
+        let border = Block::bordered().title_bottom(help::hint(Context::Form, HINTS));
         let vertical = Layout::vertical([
             Constraint::Fill(1),
             Constraint::Length(1),
@@ -80,29 +85,55 @@ impl Applet for ItemLookupApplet {
             .style(Style::default())
             .centered();
         let id_widget = Paragraph::new(self.id.clone())
-            .style(Style::default().yellow())
+            .style(theme::current().focus)
             .block(Block::bordered().title("Item ID"));
 
-        terminal.draw(|frame| {
-            let inner_area = border.inner(frame.area());
-            let [_, l1_area, id_area, _] = vertical.areas(inner_area);
-            let cent_id_area = id_area.centered_horizontally(Constraint::Length(20));
-            frame.render_widget(border, frame.area());
-            frame.render_widget(line1, l1_area);
-            frame.render_widget(id_widget, cent_id_area);
-            frame.set_cursor_position(Position::new(
-                cent_id_area.x + self.cursor_position + 1,
-                cent_id_area.y + 1,
-            ));
-        })?;
-
-        if let Some(key) = event::read()?.as_key_press_event() {
+        let inner_area = border.inner(frame.area());
+        let [_, l1_area, id_area, _] = vertical.areas(inner_area);
+        let cent_id_area = id_area.centered_horizontally(Constraint::Length(20));
+        frame.render_widget(border, frame.area());
+        frame.render_widget(line1, l1_area);
+        frame.render_widget(id_widget, cent_id_area);
+        frame.set_cursor_position(Position::new(
+            cent_id_area.x + self.cursor_position + 1,
+            cent_id_area.y + 1,
+        ));
+        self.help
+            .render(frame, "Item Lookup", Context::Form, ACTIONS);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        let db = ctx.db;
+
+        if self.help.dismiss(event) {
+            return Ok(());
+        }
+        if let Some(key) = event.as_key_press_event() {
+            match self.keys.action(Context::Form, key) {
+                Some(Action::Cancel) => self.next_state = AppState::Exit,
+                Some(Action::Save) => self.find_item(db)?,
+                Some(Action::Help) => self.help.open(),
+                _ => {}
+            }
+            if self.next_state != AppState::NoChange
+                || self.keys.is_pending()
+                || self.help.is_open()
+            {
+                return Ok(());
+            }
             match key.code {
                 KeyCode::Char(c) => {
                     self.id.insert(self.cursor_position.into(), c);
                     self.cursor_position += 1;
                 }
-                KeyCode::Esc => self.next_state = AppState::Exit,
                 KeyCode::Left => self.cursor_position = self.cursor_position.saturating_sub(1),
                 KeyCode::Right => {
                     self.cursor_position = self
@@ -110,16 +141,12 @@ impl Applet for ItemLookupApplet {
                         .saturating_add(1)
                         .min(self.id.len() as u16)
                 }
-                KeyCode::Backspace => {
-                    if self.cursor_position != 0 {
-                        self.cursor_position -= 1;
-                        self.id.remove(self.cursor_position.into());
-                    }
+                KeyCode::Backspace if self.cursor_position != 0 => {
+                    self.cursor_position -= 1;
+                    self.id.remove(self.cursor_position.into());
                 }
-                KeyCode::Delete => {
-                    if self.cursor_position != self.id.len() as u16 {
-                        self.id.remove(self.cursor_position.into());
-                    }
+                KeyCode::Delete if self.cursor_position != self.id.len() as u16 => {
+                    self.id.remove(self.cursor_position.into());
                 }
                 KeyCode::Enter => self.find_item(db)?,
                 _ => {}
@@ -128,13 +155,18 @@ impl Applet for ItemLookupApplet {
 
         Ok(())
     }
+    fn title(&self) -> String {
+        "Item Lookup".to_string()
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
 }
 #[cfg(test)]
 mod item_lookup_tests {
+    use super::super::driver::Driver;
     use super::*;
+    use crate::seed::fixtures;
     #[test]
     fn test_creation() {
         let my_applet = ItemLookupApplet::new();
@@ -146,7 +178,7 @@ mod item_lookup_tests {
     #[test]
     fn test_parsing() {
         let my_inv = inventory::Inventory::open_in_memory().unwrap();
-        fill_db(&my_inv);
+        fixtures::load(&my_inv, fixtures::NUMBERED);
 
         let mut my_applet = ItemLookupApplet::new();
 
@@ -164,15 +196,23 @@ mod item_lookup_tests {
         assert_eq!(my_applet.next_state, AppState::EditItem(101));
     }
 
-    fn fill_db(my_inv: &inventory::Inventory) {
-        for i in 0..5 {
-            let item = inventory::Item {
-                id: i + 100,
-                name: format!("item{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-                location_id: None,
-            };
-            assert!(my_inv.add_item(&item).is_ok());
-        }
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = inventory::Inventory::open_in_memory().unwrap();
+        let item = inventory::Item {
+            id: 104,
+            name: "screws".to_string(),
+            comment: None,
+            location_id: None,
+        };
+        assert!(my_inv.add_item(&item).is_ok());
+        let mut my_driver = Driver::new(ItemLookupApplet::new(), my_inv);
+        my_driver.assert_screen_contains("Enter the Desired Item ID");
+
+        my_driver.type_text("105").keys("Enter");
+        my_driver.assert_screen_contains("Item ID does not exist");
+        assert_eq!(my_driver.last_state(), AppState::NoChange);
+        my_driver.keys("Backspace").type_text("4").keys("Enter");
+        assert_eq!(my_driver.last_state(), AppState::EditItem(104));
     }
 }
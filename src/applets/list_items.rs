@@ -1,179 +1,760 @@
 use super::applet::Applet;
+use super::bulk::{self, BulkAction, BulkEditor};
+use super::context::EventContext;
+use super::filter::{ListRow, ListTable};
+use super::help::{self, HelpOverlay};
+use super::notifications::Level;
+use super::preview::{self, PreviewPane};
+use super::sort::{self, Sortable};
 use crate::AppState;
-use crate::db::inventory::{Inventory, Item, Location};
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
-use ratatui::style::Style;
-use ratatui::widgets::{Block, Padding, Row, Table, TableState};
+use crate::db::inventory::{Inventory, Item, ItemChange};
+use crate::events::AppEvent;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::{Event, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Padding, Row, Table};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+
+/// An item with the path of its location, as shown in the table.
+struct ItemRow {
+    item: Item,
+    location: String,
+}
+
+impl Sortable for ItemRow {
+    fn compare(&self, other: &Self, column: usize) -> Ordering {
+        let (a, b) = (&self.item, &other.item);
+        match column {
+            0 => a.id.cmp(&b.id),
+            1 => sort::compare_text(&a.name, &b.name),
+            2 => sort::compare_text(
+                a.comment.as_deref().unwrap_or_default(),
+                b.comment.as_deref().unwrap_or_default(),
+            ),
+            _ => sort::compare_text(&self.location, &other.location),
+        }
+    }
+}
 
 pub struct ListItemsApplet {
-    table_state: TableState,
-    items: Vec<Item>,
-    location_strings: Vec<String>,
+    table: ListTable<ItemRow>,
+    tags: HashMap<i64, Vec<String>>,
+    /// IDs of the items marked for a bulk action. Marks survive sorting, filtering and refreshes.
+    marked: BTreeSet<i64>,
+    /// The item last marked with `Mark`, where `MarkRange` starts from.
+    anchor: Option<i64>,
+    bulk: Option<BulkEditor>,
+    preview: PreviewPane,
+    keys: KeyReader,
+    help: HelpOverlay,
     next_state: AppState,
 }
 
+/// The actions listed in the help overlay, all handled by `handle_action`.
+const ACTIONS: &[Action] = &[
+    Action::Up,
+    Action::Down,
+    Action::Left,
+    Action::Right,
+    Action::Top,
+    Action::Bottom,
+    Action::Edit,
+    Action::Select,
+    Action::Sort,
+    Action::Filter,
+    Action::NextMatch,
+    Action::PreviousMatch,
+    Action::Mark,
+    Action::MarkRange,
+    Action::MarkAll,
+    Action::Bulk,
+    Action::Delete,
+    Action::Preview,
+    Action::GrowPreview,
+    Action::ShrinkPreview,
+    Action::Back,
+    Action::Quit,
+    Action::Help,
+];
+const HINTS: &[Action] = &[
+    Action::Sort,
+    Action::Filter,
+    Action::Mark,
+    Action::Bulk,
+    Action::Preview,
+    Action::Quit,
+    Action::Help,
+];
+
 impl Default for ListItemsApplet {
     fn default() -> Self {
         Self {
-            table_state: TableState::default().with_selected_cell(Some((0, 0))),
-            items: Vec::new(),
-            location_strings: Vec::new(),
+            table: ListTable::default(),
+            tags: HashMap::new(),
+            marked: BTreeSet::new(),
+            anchor: None,
+            bulk: None,
+            preview: PreviewPane::default(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
             next_state: AppState::NoChange,
         }
     }
 }
 
-impl Applet for ListItemsApplet {
-    fn run(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-        _db: &Inventory,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.next_state = AppState::NoChange;
+impl ListRow for ItemRow {
+    fn id(&self) -> i64 {
+        self.item.id
+    }
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.item.id.to_string(),
+            self.item.name.clone(),
+            self.item.comment.clone().unwrap_or_default(),
+            self.location.clone(),
+        ]
+    }
+}
+
+impl ListItemsApplet {
+    fn selected_item(&self) -> Option<&Item> {
+        self.table.selected().map(|r| &r.item)
+    }
+
+    fn toggle_mark(&mut self, row: usize) {
+        let Some(&idx) = self.table.visible.get(row) else {
+            return;
+        };
+        let id = self.table.rows[idx].item.id;
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.anchor = Some(id);
+    }
+
+    /// Marks every visible row between the anchor and `row`.
+    fn mark_range(&mut self, row: usize) {
+        let anchor = self
+            .table
+            .visible
+            .iter()
+            .position(|&i| Some(self.table.rows[i].item.id) == self.anchor)
+            .unwrap_or(row);
+        let (start, end) = (anchor.min(row), anchor.max(row));
+        for &idx in self.table.visible.get(start..=end).unwrap_or_default() {
+            self.marked.insert(self.table.rows[idx].item.id);
+        }
+    }
 
-        let header = Row::new(vec!["Item ID", "Name", "Comment", "Location"]);
+    /// Marks every row passing the filter, or unmarks them if they are all marked already.
+    fn mark_all(&mut self) {
+        let ids: Vec<i64> = self
+            .table
+            .visible
+            .iter()
+            .map(|&i| self.table.rows[i].item.id)
+            .collect();
+        if ids.iter().all(|id| self.marked.contains(id)) {
+            for id in ids {
+                self.marked.remove(&id);
+            }
+        } else {
+            self.marked.extend(ids);
+        }
+    }
+
+    /// The items a bulk action applies to: the marked ones, or the selected one if none are.
+    fn bulk_targets(&self) -> Vec<&Item> {
+        if self.marked.is_empty() {
+            return self.selected_item().into_iter().collect();
+        }
+        self.table
+            .rows
+            .iter()
+            .map(|r| &r.item)
+            .filter(|i| self.marked.contains(&i.id))
+            .collect()
+    }
+
+    fn open_bulk(&mut self, open: fn(Vec<String>) -> BulkEditor) {
+        let names: Vec<String> = self.bulk_targets().iter().map(|i| i.name.clone()).collect();
+        if !names.is_empty() {
+            self.bulk = Some(open(names));
+        }
+    }
+
+    /// Applies `change` to the bulk targets in one transaction and reloads the list.
+    fn apply_bulk(&mut self, change: &ItemChange, db: &Inventory) -> rusqlite::Result<usize> {
+        let ids: Vec<i64> = self.bulk_targets().iter().map(|i| i.id).collect();
+        let result = db.update_items(&ids, change);
+        if result.is_ok() {
+            self.marked.clear();
+            self.anchor = None;
+        }
+        self.refresh(db);
+        result
+    }
+
+    /// The full details of the selected item for the preview pane.
+    fn preview_lines(&self) -> Vec<Line<'_>> {
+        let Some(ItemRow { item, location }) = self.table.selected() else {
+            return vec![Line::from("No item selected")];
+        };
+        let location = match item.location_id {
+            Some(id) => format!("{location} (#{id})"),
+            None => "None".to_string(),
+        };
+        let tags = match self.tags.get(&item.id) {
+            Some(tags) => tags.join(", "),
+            None => "None".to_string(),
+        };
+        let mut lines = vec![
+            preview::field("Name", item.name.as_str()),
+            preview::field("Item ID", item.id.to_string()),
+            preview::field("Location", location),
+            preview::field("Tags", tags),
+            Line::default(),
+            preview::field("Comment", ""),
+        ];
+        lines.extend(
+            item.comment
+                .as_deref()
+                .unwrap_or_default()
+                .lines()
+                .map(Line::from),
+        );
+        lines
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.table.filter.is_editing() {
+            self.table.handle_filter_key(key.code);
+            return;
+        }
+        if let Some(action) = self.keys.action(Context::List, key) {
+            self.handle_action(action);
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        if self.table.handle_action(action) {
+            return;
+        }
+        match action {
+            Action::Back if !self.marked.is_empty() => self.marked.clear(),
+            Action::Quit | Action::Back => self.next_state = AppState::Exit,
+            Action::Help => self.help.open(),
+            Action::Mark => {
+                if let Some(row) = self.table.state.selected() {
+                    self.toggle_mark(row);
+                    self.table.state.select_next();
+                }
+            }
+            Action::MarkRange => {
+                if let Some(row) = self.table.state.selected() {
+                    self.mark_range(row);
+                }
+            }
+            Action::MarkAll => self.mark_all(),
+            Action::Preview => self.preview.toggle(),
+            Action::GrowPreview if self.preview.is_open() => self.preview.grow(),
+            Action::ShrinkPreview if self.preview.is_open() => self.preview.shrink(),
+            Action::Bulk => self.open_bulk(BulkEditor::new),
+            Action::Delete => self.open_bulk(BulkEditor::delete),
+            Action::Edit | Action::Select => {
+                if let Some(item) = self.selected_item() {
+                    self.next_state = AppState::EditItem(item.id)
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let Some((row, double)) = self.table.handle_mouse(&mouse) else {
+            return;
+        };
+        if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+            self.mark_range(row);
+        } else if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+            self.toggle_mark(row);
+        } else if double && let Some(item) = self.selected_item() {
+            self.next_state = AppState::EditItem(item.id)
+        }
+    }
+}
+
+impl Applet for ListItemsApplet {
+    fn render(&mut self, frame: &mut Frame) {
+        let header = sort::header(&["Item ID", "Name", "Comment", "Location"], self.table.sort);
         let mut rows: Vec<Row> = Vec::new();
 
-        if self.items.is_empty() {
+        if self.table.rows.is_empty() {
             rows.push(Row::new(["DB ERROR", "DB ERROR", "DB ERROR", "DB ERROR"]))
         } else {
-            rows.append(
-                &mut self
-                    .items
-                    .iter()
-                    .zip(self.location_strings.iter())
-                    .map(|(i, l)| {
-                        Row::new([
-                            format!("{}", i.id),
-                            format!("{}", i.name),
-                            format!("{}", i.comment.clone().unwrap_or_default()),
-                            format!("{}", l),
-                        ])
-                    })
-                    .collect::<Vec<Row>>(),
-            );
+            rows.extend(self.table.visible_rows().map(|r| {
+                let row = Row::new(r.cells());
+                if self.marked.contains(&r.item.id) {
+                    row.style(theme::current().marked)
+                } else {
+                    row
+                }
+            }));
         }
 
+        let mut block = Block::bordered().title_bottom(help::hint(Context::List, HINTS));
+        if !self.marked.is_empty() {
+            block = block
+                .title_bottom(Line::from(format!("{} marked", self.marked.len())).right_aligned());
+        }
         let widths: Vec<u16> = Vec::new();
         let table = Table::new(rows, widths)
             .block(
-                Block::bordered()
-                    .title("Inventory Manager - List Items")
-                    .title_bottom("Press 'q' or Esc to exit")
+                block
                     .border_type(ratatui::widgets::BorderType::Thick)
                     .padding(Padding::horizontal(1)),
             )
-            .style(Style::new().white())
-            .cell_highlight_style(Style::new().red())
-            .row_highlight_style(Style::new().reversed())
+            .style(theme::current().text)
+            .cell_highlight_style(theme::current().cell_highlight)
+            .row_highlight_style(theme::current().row_highlight)
             .highlight_symbol(">>")
             .header(header);
 
-        terminal.draw(|frame| {
-            frame.render_stateful_widget(table, frame.area(), &mut self.table_state)
-        })?;
-
-        if let Some(key) = event::read()?.as_key_press_event() {
-            match key.code {
-                KeyCode::Char('q') | KeyCode::Esc => self.next_state = AppState::Exit,
-                KeyCode::Down => self.table_state.select_next(),
-                KeyCode::Up => self.table_state.select_previous(),
-                KeyCode::Left => self.table_state.select_previous_column(),
-                KeyCode::Right => self.table_state.select_next_column(),
-                KeyCode::Char('e') | KeyCode::Enter => {
-                    self.next_state =
-                        AppState::EditItem(self.items[self.table_state.selected().unwrap_or(0)].id)
+        let filter_height = if self.table.filter.is_active() { 1 } else { 0 };
+        let [table_area, filter_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(filter_height)])
+                .areas(frame.area());
+        let (table_area, preview_area) = self.preview.split(table_area);
+        self.table.area = table_area;
+        frame.render_stateful_widget(table, table_area, &mut self.table.state);
+        if let Some(area) = preview_area {
+            self.preview
+                .render(frame, area, "Preview", self.preview_lines());
+        }
+        if self.table.filter.is_active() {
+            self.table.render_filter(frame, filter_area);
+        }
+        if let Some(bulk) = self.bulk.as_mut() {
+            bulk.render(frame);
+        }
+        self.help
+            .render(frame, "List Items", Context::List, ACTIONS);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        let db = ctx.db;
+
+        if self.help.dismiss(event) {
+            return Ok(());
+        }
+        if let Some(editor) = self.bulk.as_mut() {
+            let action = match *event {
+                Event::Key(key) if key.is_press() => editor.handle_key(key, db),
+                Event::Mouse(mouse) => editor.handle_mouse(mouse),
+                _ => BulkAction::None,
+            };
+            match action {
+                BulkAction::Apply(change) => {
+                    self.bulk = None;
+                    match self.apply_bulk(&change, db) {
+                        Ok(0) => {
+                            ctx.notify(Level::Warning, "Nothing to change, no item was updated")
+                        }
+                        Ok(count) => ctx.notify(Level::Success, bulk::done_message(&change, count)),
+                        Err(e) => ctx.notify(
+                            Level::Error,
+                            format!("Bulk change failed, nothing was changed: {e}"),
+                        ),
+                    }
                 }
-                _ => {}
+                BulkAction::Close => self.bulk = None,
+                BulkAction::None => {}
             }
+            return Ok(());
+        }
+        match *event {
+            Event::Key(key) if key.is_press() => self.handle_key(key),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
         }
         Ok(())
     }
 
+    fn title(&self) -> String {
+        "List Items".to_string()
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
     fn refresh(&mut self, db: &Inventory) {
-        self.items = db.get_all_items().unwrap_or_default();
-        self.location_strings = self
-            .items
-            .iter()
-            .map(|i| {
-                db.search_location_id(i.location_id.unwrap_or(0))
-                    .map(|item| item.name)
-                    .unwrap_or_else(|| "".to_string())
+        let paths = db.location_paths().unwrap_or_default();
+        let rows = db
+            .get_all_items()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| ItemRow {
+                location: item
+                    .location_id
+                    .and_then(|id| paths.get(&id).cloned())
+                    .unwrap_or_default(),
+                item,
             })
             .collect();
+        self.table.set_rows(rows);
+        self.tags = db.all_item_tags().unwrap_or_default();
+        let ids: BTreeSet<i64> = self.table.rows.iter().map(|r| r.item.id).collect();
+        self.marked.retain(|id| ids.contains(id));
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::driver::Driver;
+    use super::super::sort::SortOrder;
     use super::*;
+    use crate::db::inventory::Location;
+    use crate::seed::fixtures;
+    use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+    use ratatui::layout::Rect;
     #[test]
     fn test_default() {
         let my_applet = ListItemsApplet::default();
-        assert!(my_applet.items.is_empty());
-        assert!(my_applet.location_strings.is_empty());
+        assert!(my_applet.table.rows.is_empty());
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert_eq!(my_applet.table_state.selected(), Some(0));
-        assert_eq!(my_applet.table_state.selected_column(), Some(0));
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+        assert_eq!(my_applet.table.state.selected_column(), Some(0));
     }
 
     #[test]
     fn test_refresh() {
-        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
-        for i in 0..5 {
-            let loc = Location {
-                id: i,
-                name: format!("location{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-            };
-            let item = Item {
-                id: i + 100,
-                name: format!("item{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-                location_id: Some(i),
-            };
-            assert!(my_inv.add_location(&loc).is_ok());
-            assert!(my_inv.add_item(&item).is_ok());
-        }
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
         let mut my_applet = ListItemsApplet::default();
         my_applet.refresh(&my_inv);
-        assert!(!my_applet.items.is_empty());
-        assert!(!my_applet.location_strings.is_empty());
-        assert_eq!(my_applet.items.len(), 5);
-        assert_eq!(my_applet.location_strings.len(), 5);
+        assert_eq!(my_applet.table.rows.len(), 5);
+        assert_eq!(my_applet.table.rows[0].location, "location0");
+        assert_eq!(my_applet.next_state, AppState::NoChange);
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+        assert_eq!(my_applet.table.state.selected_column(), Some(0));
+    }
+    #[test]
+    fn test_refresh_no_locations() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::STATS);
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+        assert_eq!(my_applet.table.rows.len(), 4);
+        assert_eq!(my_applet.table.rows[0].location, "location1");
+        assert_eq!(my_applet.table.rows[3].location, "");
+        assert_eq!(my_applet.next_state, AppState::NoChange);
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+        assert_eq!(my_applet.table.state.selected_column(), Some(0));
+    }
+
+    #[test]
+    fn test_sort() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::SORTING);
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+        let ids =
+            |a: &ListItemsApplet| a.table.rows.iter().map(|r| r.item.id).collect::<Vec<i64>>();
+        assert_eq!(ids(&my_applet), vec![98, 99, 100]);
+
+        my_applet.table.toggle_sort(0);
+        assert_eq!(ids(&my_applet), vec![98, 99, 100]);
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+
+        //the selected item follows the sort
+        my_applet.table.toggle_sort(0);
+        assert_eq!(ids(&my_applet), vec![100, 99, 98]);
+        assert_eq!(my_applet.table.sort.unwrap().order, SortOrder::Descending);
+        assert_eq!(my_applet.table.state.selected(), Some(2));
+
+        my_applet.table.toggle_sort(1);
+        assert_eq!(ids(&my_applet), vec![99, 100, 98]);
+
+        my_applet.table.toggle_sort(3);
+        assert_eq!(ids(&my_applet), vec![100, 98, 99]);
         assert_eq!(
-            my_applet.location_strings.first(),
-            Some(&"location0".to_string())
+            my_applet
+                .table
+                .rows
+                .iter()
+                .map(|r| r.location.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["Apple", "banana", "cherry"]
         );
+
+        //sort is kept across refresh
+        my_applet.refresh(&my_inv);
+        assert_eq!(ids(&my_applet), vec![100, 98, 99]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+        my_applet.table.state.select(Some(2));
+
+        my_applet.table.filter.open();
+        my_applet.table.handle_filter_key(KeyCode::Char('2'));
+        assert_eq!(my_applet.table.visible, vec![2]);
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(102));
+
+        //matches on the location column too, and keeps the selection when it is still visible
+        my_applet.table.handle_filter_key(KeyCode::Backspace);
+        my_applet.table.handle_filter_key(KeyCode::Char('L'));
+        my_applet.table.handle_filter_key(KeyCode::Char('O'));
+        assert_eq!(my_applet.table.visible.len(), 5);
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(102));
+
+        //falls back to the first match when the selected row is filtered out
+        my_applet.table.handle_filter_key(KeyCode::Char('c'));
+        my_applet.table.handle_filter_key(KeyCode::Char('a'));
+        my_applet.table.handle_filter_key(KeyCode::Char('t'));
+        my_applet.table.handle_filter_key(KeyCode::Char('i'));
+        my_applet.table.handle_filter_key(KeyCode::Char('o'));
+        my_applet.table.handle_filter_key(KeyCode::Char('n'));
+        my_applet.table.handle_filter_key(KeyCode::Char('4'));
+        assert_eq!(my_applet.table.visible, vec![4]);
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+
+        my_applet.table.handle_filter_key(KeyCode::Char('x'));
+        assert!(my_applet.table.visible.is_empty());
+        assert_eq!(my_applet.selected_item(), None);
+
+        my_applet.table.handle_filter_key(KeyCode::Esc);
+        assert!(!my_applet.table.filter.is_active());
+        assert_eq!(my_applet.table.visible.len(), 5);
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_actions() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+
+        my_applet.handle_action(Action::Bottom);
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(104));
+        my_applet.handle_action(Action::Top);
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(100));
+        my_applet.handle_action(Action::Edit);
+        assert_eq!(my_applet.next_state, AppState::EditItem(100));
+
+        //back clears an active filter before leaving
+        my_applet.next_state = AppState::NoChange;
+        my_applet.handle_action(Action::Filter);
+        my_applet.table.handle_filter_key(KeyCode::Char('3'));
+        my_applet.table.handle_filter_key(KeyCode::Enter);
+        my_applet.handle_action(Action::Back);
+        assert!(!my_applet.table.filter.is_active());
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert_eq!(my_applet.table_state.selected(), Some(0));
-        assert_eq!(my_applet.table_state.selected_column(), Some(0));
+        my_applet.handle_action(Action::Back);
+        assert_eq!(my_applet.next_state, AppState::Exit);
     }
+
     #[test]
-    fn test_refresh_no_locations() {
-        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
-        for i in 0..5 {
-            let item = Item {
-                id: i + 100,
-                name: format!("item{i}").to_string(),
-                comment: Some(format!("comment{i}").to_string()),
-                location_id: None,
-            };
-            assert!(my_inv.add_item(&item).is_ok());
-        }
+    fn test_mouse() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+        my_applet.table.area = Rect::new(0, 0, 40, 20);
+        let mouse = |kind, row| MouseEvent {
+            kind,
+            column: 5,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let click = MouseEventKind::Down(MouseButton::Left);
+
+        //border and header take the first two rows
+        my_applet.handle_mouse(mouse(click, 4));
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(102));
+        assert_eq!(my_applet.next_state, AppState::NoChange);
+        my_applet.handle_mouse(mouse(MouseEventKind::ScrollDown, 0));
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(103));
+
+        //clicks below the last row are ignored
+        my_applet.handle_mouse(mouse(click, 12));
+        assert_eq!(my_applet.selected_item().map(|i| i.id), Some(103));
+
+        my_applet.handle_mouse(mouse(click, 3));
+        my_applet.handle_mouse(mouse(click, 3));
+        assert_eq!(my_applet.next_state, AppState::EditItem(101));
+    }
+
+    #[test]
+    fn test_marks() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
         let mut my_applet = ListItemsApplet::default();
         my_applet.refresh(&my_inv);
-        assert!(!my_applet.items.is_empty());
-        assert!(!my_applet.location_strings.is_empty());
-        assert_eq!(my_applet.items.len(), 5);
-        assert_eq!(my_applet.location_strings.len(), 5);
-        assert_eq!(my_applet.location_strings.first(), Some(&"".to_string()));
+        let marked = |a: &ListItemsApplet| a.marked.iter().copied().collect::<Vec<i64>>();
+
+        //mark moves down so rows can be marked in a run
+        my_applet.handle_action(Action::Mark);
+        my_applet.handle_action(Action::Mark);
+        assert_eq!(marked(&my_applet), vec![100, 101]);
+        my_applet.handle_action(Action::Up);
+        my_applet.handle_action(Action::Mark);
+        assert_eq!(marked(&my_applet), vec![100]);
+
+        //the range starts from the last row marked or unmarked
+        my_applet.handle_action(Action::Down);
+        my_applet.handle_action(Action::Down);
+        my_applet.handle_action(Action::MarkRange);
+        assert_eq!(marked(&my_applet), vec![100, 101, 102, 103, 104]);
+
+        //back clears the marks before leaving
+        my_applet.handle_action(Action::Back);
+        assert!(my_applet.marked.is_empty());
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert_eq!(my_applet.table_state.selected(), Some(0));
-        assert_eq!(my_applet.table_state.selected_column(), Some(0));
+
+        //mark all only marks rows passing the filter, and toggles
+        my_applet.table.filter.open();
+        my_applet.table.handle_filter_key(KeyCode::Char('4'));
+        my_applet.table.handle_filter_key(KeyCode::Enter);
+        my_applet.handle_action(Action::MarkAll);
+        assert_eq!(marked(&my_applet), vec![104]);
+        my_applet.handle_action(Action::MarkAll);
+        assert!(my_applet.marked.is_empty());
+
+        //ctrl and shift clicks mark rows
+        my_applet.table.handle_filter_key(KeyCode::Esc);
+        my_applet.table.area = Rect::new(0, 0, 40, 20);
+        let click = |row, modifiers| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row,
+            modifiers,
+        };
+        my_applet.handle_mouse(click(3, KeyModifiers::CONTROL));
+        my_applet.handle_mouse(click(5, KeyModifiers::SHIFT));
+        assert_eq!(marked(&my_applet), vec![101, 102, 103]);
+        assert_eq!(my_applet.next_state, AppState::NoChange);
+    }
+
+    #[test]
+    fn test_bulk() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+
+        //without marks the selected row is the target
+        assert_eq!(
+            my_applet.apply_bulk(&ItemChange::Move(Some(1)), &my_inv),
+            Ok(1)
+        );
+        assert_eq!(my_applet.table.rows[0].location, "location1");
+
+        my_applet.handle_action(Action::Down);
+        my_applet.handle_action(Action::Mark);
+        my_applet.handle_action(Action::Mark);
+        my_applet.handle_action(Action::Bulk);
+        assert!(my_applet.bulk.is_some());
+
+        //a failed change leaves the marks for another try
+        assert!(
+            my_applet
+                .apply_bulk(&ItemChange::Move(Some(9)), &my_inv)
+                .is_err()
+        );
+        assert_eq!(my_applet.marked.len(), 2);
+        //only the rows that really changed are counted
+        let tag = ItemChange::AddTag("spare".to_string());
+        assert_eq!(my_applet.apply_bulk(&tag, &my_inv), Ok(2));
+        my_applet.marked.extend([101, 102]);
+        assert_eq!(my_applet.apply_bulk(&tag, &my_inv), Ok(0));
+        my_applet.marked.extend([101, 102]);
+        assert_eq!(my_applet.apply_bulk(&ItemChange::Delete, &my_inv), Ok(2));
+        assert!(my_applet.marked.is_empty());
+        let ids: Vec<i64> = my_applet.table.rows.iter().map(|r| r.item.id).collect();
+        assert_eq!(ids, vec![100, 103, 104]);
+    }
+
+    #[test]
+    fn test_preview() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let loc = Location {
+            id: 7,
+            name: "Garage".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        let item = Item {
+            id: 104,
+            name: "M3 screws".to_string(),
+            comment: Some("first line\nsecond line".to_string()),
+            location_id: Some(7),
+        };
+        assert!(my_inv.add_location(&loc).is_ok());
+        assert!(my_inv.add_item(&item).is_ok());
+        let tag = ItemChange::AddTag("metric".to_string());
+        assert!(my_inv.update_items(&[104], &tag).is_ok());
+        let mut my_applet = ListItemsApplet::default();
+        my_applet.refresh(&my_inv);
+
+        my_applet.handle_action(Action::Preview);
+        assert!(my_applet.preview.is_open());
+        let text: Vec<String> = my_applet
+            .preview_lines()
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            text,
+            vec![
+                "Name: M3 screws",
+                "Item ID: 104",
+                "Location: Garage (#7)",
+                "Tags: metric",
+                "",
+                "Comment: ",
+                "first line",
+                "second line",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_driver = Driver::new(ListItemsApplet::default(), my_inv);
+        my_driver.assert_screen_contains("item4");
+
+        //filter down to one item and open it
+        my_driver.keys("/").type_text("item3").keys("Enter e");
+        assert_eq!(my_driver.last_state(), AppState::EditItem(103));
+
+        //clear the filter, then delete two marked items in one go
+        my_driver.keys("Esc Home Space Down Space Delete");
+        my_driver.assert_screen_contains("Confirm Bulk Change");
+        my_driver.keys("Enter");
+        my_driver.assert_screen_contains("Deleted 2 items");
+        assert_eq!(my_driver.db.get_all_items().unwrap().len(), 3);
+        assert!(my_driver.find("item0").is_none());
+
+        my_driver.keys("q");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
     }
 }
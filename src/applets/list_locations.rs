@@ -1,111 +1,273 @@
 use super::applet::Applet;
+use super::context::EventContext;
+use super::filter::{ListRow, ListTable};
+use super::help::{self, HelpOverlay};
+use super::sort::{self, Sortable};
 use crate::AppState;
 use crate::db::inventory::{Inventory, Location};
-use crossterm::event::{self, KeyCode};
-use ratatui::DefaultTerminal;
-use ratatui::style::Style;
-use ratatui::widgets::{Block, Padding, Row, Table, TableState};
+use crate::events::AppEvent;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::{Event, KeyEvent, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::{Block, Padding, Row, Table};
+use std::cmp::Ordering;
 
 pub struct ListLocationsApplet {
-    table_state: TableState,
-    locations: Vec<Location>,
+    table: ListTable<Location>,
+    keys: KeyReader,
+    help: HelpOverlay,
     next_state: AppState,
 }
 
+/// The actions listed in the help overlay, all handled by `handle_action`.
+const ACTIONS: &[Action] = &[
+    Action::Up,
+    Action::Down,
+    Action::Left,
+    Action::Right,
+    Action::Top,
+    Action::Bottom,
+    Action::Edit,
+    Action::Select,
+    Action::Sort,
+    Action::Filter,
+    Action::NextMatch,
+    Action::PreviousMatch,
+    Action::Back,
+    Action::Quit,
+    Action::Help,
+];
+const HINTS: &[Action] = &[Action::Sort, Action::Filter, Action::Quit, Action::Help];
+
 impl Default for ListLocationsApplet {
     fn default() -> Self {
         Self {
-            table_state: TableState::default().with_selected_cell(Some((0, 0))),
+            table: ListTable::default(),
             next_state: AppState::NoChange,
-            locations: Vec::new(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
         }
     }
 }
 
-impl Applet for ListLocationsApplet {
-    fn run(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-        _db: &Inventory,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        self.next_state = AppState::NoChange;
+impl ListLocationsApplet {
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.table.filter.is_editing() {
+            self.table.handle_filter_key(key.code);
+            return;
+        }
+        if let Some(action) = self.keys.action(Context::List, key) {
+            self.handle_action(action);
+        }
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        if self.table.handle_action(action) {
+            return;
+        }
+        match action {
+            Action::Quit | Action::Back => self.next_state = AppState::Exit,
+            Action::Help => self.help.open(),
+            Action::Edit | Action::Select => {
+                if let Some(location) = self.table.selected() {
+                    self.next_state = AppState::EditLocation(location.id)
+                }
+            }
+            _ => {}
+        }
+    }
 
-        //let data = db.get_all_locations().unwrap_or_default();
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if let Some((_, true)) = self.table.handle_mouse(&mouse)
+            && let Some(location) = self.table.selected()
+        {
+            self.next_state = AppState::EditLocation(location.id)
+        }
+    }
+}
+
+impl Sortable for Location {
+    fn compare(&self, other: &Self, column: usize) -> Ordering {
+        match column {
+            0 => self.id.cmp(&other.id),
+            1 => sort::compare_text(&self.name, &other.name),
+            _ => sort::compare_text(
+                self.comment.as_deref().unwrap_or_default(),
+                other.comment.as_deref().unwrap_or_default(),
+            ),
+        }
+    }
+}
 
-        let header = Row::new(vec!["Location ID", "Name", "Comment"]);
+impl ListRow for Location {
+    fn id(&self) -> i64 {
+        self.id
+    }
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.comment.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl Applet for ListLocationsApplet {
+    fn render(&mut self, frame: &mut Frame) {
+        let header = sort::header(&["Location ID", "Name", "Comment"], self.table.sort);
         let mut rows = Vec::new();
 
-        if self.locations.is_empty() {
+        if self.table.rows.is_empty() {
             rows.push(Row::new(["DB ERROR", "DB ERROR", "DB_ERROR"]));
         } else {
-            rows.append(
-                &mut self
-                    .locations
-                    .iter()
-                    .map(|l| {
-                        Row::new([
-                            format!("{}", l.id),
-                            format!("{}", l.name),
-                            format!("{}", l.comment.clone().unwrap_or("".to_string())),
-                        ])
-                    })
-                    .collect::<Vec<Row>>(),
-            );
+            rows.extend(self.table.visible_rows().map(|l| Row::new(l.cells())));
         }
         let widths: Vec<u16> = Vec::new();
         let table = Table::new(rows, widths)
             .block(
                 Block::bordered()
-                    .title("Inventory Manager - List Locations")
-                    .title_bottom("Press 'q' or Esc to exit")
+                    .title_bottom(help::hint(Context::List, HINTS))
                     .border_type(ratatui::widgets::BorderType::Thick)
                     .padding(Padding::horizontal(1)),
             )
-            .style(Style::new().white())
-            .cell_highlight_style(Style::new().red())
-            .row_highlight_style(Style::new().reversed())
+            .style(theme::current().text)
+            .cell_highlight_style(theme::current().cell_highlight)
+            .row_highlight_style(theme::current().row_highlight)
             .highlight_symbol(">>")
             .header(header);
 
-        terminal.draw(|frame| {
-            frame.render_stateful_widget(table, frame.area(), &mut self.table_state)
-        })?;
-
-        if let Some(key) = event::read()?.as_key_press_event() {
-            match key.code {
-                KeyCode::Char('q') | KeyCode::Esc => self.next_state = AppState::Exit,
-                KeyCode::Down => self.table_state.select_next(),
-                KeyCode::Up => self.table_state.select_previous(),
-                KeyCode::Left => self.table_state.select_previous_column(),
-                KeyCode::Right => self.table_state.select_next_column(),
-                KeyCode::Char('e') | KeyCode::Enter => {
-                    self.next_state = AppState::EditLocation(
-                        self.locations[self.table_state.selected().unwrap_or(0)].id,
-                    )
-                }
-                _ => {}
-            }
+        let filter_height = if self.table.filter.is_active() { 1 } else { 0 };
+        let [table_area, filter_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(filter_height)])
+                .areas(frame.area());
+        self.table.area = table_area;
+        frame.render_stateful_widget(table, table_area, &mut self.table.state);
+        if self.table.filter.is_active() {
+            self.table.render_filter(frame, filter_area);
+        }
+        self.help
+            .render(frame, "List Locations", Context::List, ACTIONS);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        _ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+
+        if self.help.dismiss(event) {
+            return Ok(());
+        }
+        match *event {
+            Event::Key(key) if key.is_press() => self.handle_key(key),
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
         }
         Ok(())
     }
 
+    fn title(&self) -> String {
+        "List Locations".to_string()
+    }
     fn get_next_state(&self) -> AppState {
         self.next_state.clone()
     }
     fn refresh(&mut self, db: &Inventory) {
-        self.locations = db.get_all_locations().unwrap_or_default();
+        self.table
+            .set_rows(db.get_all_locations().unwrap_or_default());
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::driver::Driver;
     use super::*;
+    use crate::seed::fixtures;
+    use crossterm::event::KeyCode;
     #[test]
     fn test_default() {
         let my_applet = ListLocationsApplet::default();
-        assert!(my_applet.locations.is_empty());
+        assert!(my_applet.table.rows.is_empty());
         assert_eq!(my_applet.next_state, AppState::NoChange);
-        assert_eq!(my_applet.table_state.selected(), Some(0));
-        assert_eq!(my_applet.table_state.selected_column(), Some(0));
+        assert_eq!(my_applet.table.state.selected(), Some(0));
+        assert_eq!(my_applet.table.state.selected_column(), Some(0));
+    }
+
+    #[test]
+    fn test_sort() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::SORTING);
+        let mut my_applet = ListLocationsApplet::default();
+        my_applet.refresh(&my_inv);
+        let ids = |a: &ListLocationsApplet| a.table.rows.iter().map(|l| l.id).collect::<Vec<i64>>();
+        assert_eq!(ids(&my_applet), vec![0, 1, 2]);
+
+        my_applet.table.toggle_sort(1);
+        assert_eq!(ids(&my_applet), vec![1, 0, 2]);
+        assert_eq!(my_applet.table.state.selected(), Some(1));
+
+        my_applet.table.toggle_sort(1);
+        assert_eq!(ids(&my_applet), vec![2, 0, 1]);
+
+        my_applet.table.toggle_sort(2);
+        assert_eq!(ids(&my_applet), vec![2, 1, 0]);
+
+        //sort is kept across refresh
+        my_applet.refresh(&my_inv);
+        assert_eq!(ids(&my_applet), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        let mut my_applet = ListLocationsApplet::default();
+        my_applet.refresh(&my_inv);
+        my_applet.table.state.select(Some(3));
+
+        my_applet.table.filter.open();
+        my_applet.table.handle_filter_key(KeyCode::Char('C'));
+        assert_eq!(my_applet.table.visible.len(), 5);
+        assert_eq!(my_applet.table.selected().map(|l| l.id), Some(3));
+        my_applet.table.handle_filter_key(KeyCode::Char('o'));
+        my_applet.table.handle_filter_key(KeyCode::Char('m'));
+        my_applet.table.handle_filter_key(KeyCode::Char('m'));
+        my_applet.table.handle_filter_key(KeyCode::Char('e'));
+        my_applet.table.handle_filter_key(KeyCode::Char('n'));
+        my_applet.table.handle_filter_key(KeyCode::Char('t'));
+        my_applet.table.handle_filter_key(KeyCode::Char('1'));
+        assert_eq!(my_applet.table.visible, vec![1]);
+        assert_eq!(my_applet.table.selected().map(|l| l.id), Some(1));
+
+        //the filter survives a refresh
+        my_applet.table.handle_filter_key(KeyCode::Enter);
+        my_applet.refresh(&my_inv);
+        assert_eq!(my_applet.table.visible, vec![1]);
+        assert!(my_applet.table.filter.is_active());
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::SORTING);
+        let mut my_driver = Driver::new(ListLocationsApplet::default(), my_inv);
+        my_driver.assert_screen_contains("cherry");
+
+        //sort by name and open the first row
+        my_driver.keys("Right s Home Enter");
+        assert_eq!(my_driver.last_state(), AppState::EditLocation(1));
+
+        //double clicking a row opens it too
+        my_driver.click_text("cherry").click_text("cherry");
+        assert_eq!(my_driver.last_state(), AppState::EditLocation(2));
+
+        my_driver.keys("Esc");
+        assert_eq!(my_driver.last_state(), AppState::Exit);
     }
 }
@@ -0,0 +1,495 @@
+use super::applet::Applet;
+use super::context::EventContext;
+use super::help::{self, HelpOverlay};
+use super::mouse::{self, ClickTracker};
+use super::notifications::Level;
+use crate::AppState;
+use crate::db::inventory::{Inventory, Item, Location};
+use crate::events::AppEvent;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::{Event, MouseEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListState, Padding};
+use std::collections::{HashMap, HashSet};
+
+/// The row above the top level locations, where locations are pasted to move them to the top.
+const ROOT_NAME: &str = "Top level";
+
+pub struct LocationTreeApplet {
+    locations: HashMap<i64, Location>,
+    /// The IDs of each location's children sorted by name, `None` holding the top level.
+    children: HashMap<Option<i64>, Vec<i64>>,
+    /// Items in each location and its sublocations.
+    totals: HashMap<i64, usize>,
+    /// Items directly in each location, `None` holding items without a location.
+    items: HashMap<Option<i64>, Vec<Item>>,
+    expanded: HashSet<i64>,
+    /// The visible rows as (depth, location ID), with `None` for the top level row.
+    rows: Vec<(usize, Option<i64>)>,
+    /// The location waiting to be pasted somewhere else.
+    cut: Option<i64>,
+    list_state: ListState,
+    list_area: Rect,
+    clicks: ClickTracker,
+    keys: KeyReader,
+    help: HelpOverlay,
+    next_state: AppState,
+}
+
+const ACTIONS: &[Action] = &[
+    Action::Up,
+    Action::Down,
+    Action::Top,
+    Action::Bottom,
+    Action::Right,
+    Action::Left,
+    Action::Select,
+    Action::Edit,
+    Action::Cut,
+    Action::Paste,
+    Action::Back,
+    Action::Quit,
+    Action::Help,
+];
+const HINTS: &[Action] = &[
+    Action::Select,
+    Action::Edit,
+    Action::Cut,
+    Action::Paste,
+    Action::Quit,
+    Action::Help,
+];
+
+impl Default for LocationTreeApplet {
+    fn default() -> Self {
+        Self {
+            locations: HashMap::new(),
+            children: HashMap::new(),
+            totals: HashMap::new(),
+            items: HashMap::new(),
+            expanded: HashSet::new(),
+            rows: vec![(0, None)],
+            cut: None,
+            list_state: ListState::default().with_selected(Some(0)),
+            list_area: Rect::default(),
+            clicks: ClickTracker::default(),
+            keys: KeyReader::default(),
+            help: HelpOverlay::default(),
+            next_state: AppState::NoChange,
+        }
+    }
+}
+
+impl LocationTreeApplet {
+    /// The selected row, `Some(None)` being the top level row.
+    fn selected(&self) -> Option<Option<i64>> {
+        self.list_state
+            .selected()
+            .and_then(|row| self.rows.get(row))
+            .map(|(_, id)| *id)
+    }
+
+    fn has_children(&self, id: i64) -> bool {
+        self.children.get(&Some(id)).is_some_and(|c| !c.is_empty())
+    }
+
+    /// Adds up the items in `id` and everything below it. `seen` guards against loops.
+    fn total(&mut self, id: i64, seen: &mut HashSet<i64>) -> usize {
+        if !seen.insert(id) {
+            return 0;
+        }
+        let mut total = self.items.get(&Some(id)).map_or(0, Vec::len);
+        for child in self.children.get(&Some(id)).cloned().unwrap_or_default() {
+            total += self.total(child, seen);
+        }
+        self.totals.insert(id, total);
+        total
+    }
+
+    /// Recomputes the visible rows from the expanded locations, keeping the selection on the
+    /// same location if it is still visible.
+    fn update_rows(&mut self) {
+        let selected = self.selected();
+        self.rows = vec![(0, None)];
+        let mut stack: Vec<(usize, i64)> = self
+            .children
+            .get(&None)
+            .map(|c| c.iter().rev().map(|&id| (1, id)).collect())
+            .unwrap_or_default();
+        let mut seen = HashSet::new();
+        while let Some((depth, id)) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            self.rows.push((depth, Some(id)));
+            if self.expanded.contains(&id)
+                && let Some(children) = self.children.get(&Some(id))
+            {
+                stack.extend(children.iter().rev().map(|&child| (depth + 1, child)));
+            }
+        }
+        let row = self.rows.iter().position(|(_, id)| Some(*id) == selected);
+        self.list_state.select(Some(row.unwrap_or(0)));
+    }
+
+    fn expand(&mut self) {
+        match self.selected() {
+            Some(Some(id)) if self.has_children(id) && !self.expanded.contains(&id) => {
+                self.expanded.insert(id);
+                self.update_rows();
+            }
+            Some(Some(id)) if self.expanded.contains(&id) => self.list_state.select_next(),
+            Some(None) => self.list_state.select_next(),
+            _ => {}
+        }
+    }
+
+    /// Collapses the selected location, or moves to its parent if it already is.
+    fn collapse(&mut self) {
+        let Some(Some(id)) = self.selected() else {
+            return;
+        };
+        if self.expanded.remove(&id) {
+            self.update_rows();
+            return;
+        }
+        let parent = self.locations.get(&id).and_then(|l| l.parent_id);
+        let row = self.rows.iter().position(|(_, row_id)| *row_id == parent);
+        self.list_state.select(row);
+    }
+
+    fn toggle(&mut self) {
+        match self.selected() {
+            Some(Some(id)) if self.expanded.contains(&id) => self.collapse(),
+            _ => self.expand(),
+        }
+    }
+
+    /// Moves the cut location inside the selected one, returning the message to show, or `None`
+    /// if nothing was cut.
+    fn paste(&mut self, db: &Inventory) -> Result<Option<String>, rusqlite::Error> {
+        let (Some(cut), Some(parent)) = (self.cut, self.selected()) else {
+            return Ok(None);
+        };
+        db.set_location_parent(cut, parent)?;
+        self.cut = None;
+        if let Some(parent) = parent {
+            self.expanded.insert(parent);
+        }
+        self.refresh(db);
+        let name = |id: Option<i64>| match id.and_then(|id| self.locations.get(&id)) {
+            Some(location) => location.name.clone(),
+            None => ROOT_NAME.to_lowercase(),
+        };
+        Ok(Some(format!(
+            "Moved {} to {}",
+            name(Some(cut)),
+            name(parent)
+        )))
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Back if self.cut.is_some() => self.cut = None,
+            Action::Quit | Action::Back => self.next_state = AppState::Exit,
+            Action::Down => self.list_state.select_next(),
+            Action::Up => self.list_state.select_previous(),
+            Action::Top => self.list_state.select_first(),
+            Action::Bottom => self.list_state.select(self.rows.len().checked_sub(1)),
+            Action::Right => self.expand(),
+            Action::Left => self.collapse(),
+            Action::Select => self.toggle(),
+            Action::Edit => {
+                if let Some(Some(id)) = self.selected() {
+                    self.next_state = AppState::EditLocation(id);
+                }
+            }
+            Action::Cut => self.cut = self.selected().flatten(),
+            Action::Help => self.help.open(),
+            _ => {}
+        }
+    }
+
+    fn row_line(&self, depth: usize, id: Option<i64>) -> Line<'static> {
+        let Some(id) = id else {
+            let unplaced = self.items.get(&None).map_or(0, Vec::len);
+            return Line::from(format!("{ROOT_NAME} ({unplaced} without a location)"));
+        };
+        let marker = if !self.has_children(id) {
+            "  "
+        } else if self.expanded.contains(&id) {
+            "▾ "
+        } else {
+            "▸ "
+        };
+        let name = self
+            .locations
+            .get(&id)
+            .map_or(String::new(), |l| l.name.clone());
+        let total = self.totals.get(&id).copied().unwrap_or_default();
+        let mut spans = vec![
+            Span::raw(format!("{}{marker}", "  ".repeat(depth - 1))),
+            Span::raw(name),
+            Span::styled(format!(" ({total})"), theme::current().disabled),
+        ];
+        if self.cut == Some(id) {
+            spans[1] = spans[1].clone().style(theme::current().marked);
+            spans.push(Span::styled(" (cut)", theme::current().marked));
+        }
+        Line::from(spans)
+    }
+
+    fn items_title(&self) -> String {
+        match self.selected().flatten() {
+            Some(id) => format!(
+                "Items in {}",
+                self.locations.get(&id).map_or("", |l| l.name.as_str())
+            ),
+            None => "Items without a location".to_string(),
+        }
+    }
+}
+
+impl Applet for LocationTreeApplet {
+    fn render(&mut self, frame: &mut Frame) {
+        let tree = List::new(
+            self.rows
+                .iter()
+                .map(|(depth, id)| self.row_line(*depth, *id)),
+        )
+        .block(
+            Block::bordered()
+                .title_bottom(help::hint(Context::List, HINTS))
+                .border_type(ratatui::widgets::BorderType::Thick)
+                .padding(Padding::horizontal(1)),
+        )
+        .style(theme::current().text)
+        .highlight_style(theme::current().row_highlight)
+        .highlight_symbol(">>");
+        let selected = self.selected().flatten();
+        let items = List::new(
+            self.items
+                .get(&selected)
+                .into_iter()
+                .flatten()
+                .map(|i| format!("{} (#{})", i.name, i.id)),
+        )
+        .block(
+            Block::bordered()
+                .title(self.items_title())
+                .border_type(ratatui::widgets::BorderType::Thick)
+                .padding(Padding::horizontal(1)),
+        )
+        .style(theme::current().text);
+
+        let [tree_area, items_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(frame.area());
+        self.list_area = tree_area;
+        frame.render_stateful_widget(tree, tree_area, &mut self.list_state);
+        frame.render_widget(items, items_area);
+        self.help
+            .render(frame, "Location Tree", Context::List, ACTIONS);
+    }
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        ctx: &mut EventContext,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.next_state = AppState::NoChange;
+        let AppEvent::Input(event) = event else {
+            return Ok(());
+        };
+        let db = ctx.db;
+
+        if self.help.dismiss(event) {
+            return Ok(());
+        }
+        match *event {
+            Event::Key(key) if key.is_press() => match self.keys.action(Context::List, key) {
+                Some(Action::Paste) => match self.paste(db) {
+                    Ok(Some(message)) => ctx.notify(Level::Success, message),
+                    Ok(None) => ctx.notify(Level::Info, "Cut a location first"),
+                    Err(e) => ctx.notify(Level::Error, format!("Could not move location: {e}")),
+                },
+                Some(action) => self.handle_action(action),
+                None => {}
+            },
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::ScrollDown => self.list_state.select_next(),
+                MouseEventKind::ScrollUp => self.list_state.select_previous(),
+                _ if mouse::is_left_click(&mouse) => {
+                    let offset = self.list_state.offset();
+                    if let Some(row) = mouse::row_at(self.list_area, 0, offset, &mouse)
+                        && row < self.rows.len()
+                    {
+                        self.list_state.select(Some(row));
+                        if self.clicks.click(row) {
+                            self.toggle();
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn title(&self) -> String {
+        "Location Tree".to_string()
+    }
+    fn get_next_state(&self) -> AppState {
+        self.next_state.clone()
+    }
+    fn refresh(&mut self, db: &Inventory) {
+        let mut locations = db.get_all_locations().unwrap_or_default();
+        locations.sort_by_key(|l| l.name.to_lowercase());
+        self.children.clear();
+        for location in &locations {
+            //locations whose parent is missing are shown at the top level
+            let parent = location
+                .parent_id
+                .filter(|p| locations.iter().any(|l| l.id == *p));
+            self.children.entry(parent).or_default().push(location.id);
+        }
+        self.locations = locations.into_iter().map(|l| (l.id, l)).collect();
+
+        self.items.clear();
+        for item in db.get_all_items().unwrap_or_default() {
+            self.items.entry(item.location_id).or_default().push(item);
+        }
+        self.totals.clear();
+        let mut seen = HashSet::new();
+        for id in self.children.get(&None).cloned().unwrap_or_default() {
+            self.total(id, &mut seen);
+        }
+        self.expanded.retain(|id| self.locations.contains_key(id));
+        if self.cut.is_some_and(|id| !self.locations.contains_key(&id)) {
+            self.cut = None;
+        }
+        self.update_rows();
+    }
+}
+
+#[cfg(test)]
+mod location_tree_tests {
+    use super::super::driver::Driver;
+    use super::*;
+    use crate::seed::fixtures;
+
+    fn row_ids(my_applet: &LocationTreeApplet) -> Vec<Option<i64>> {
+        my_applet.rows.iter().map(|(_, id)| *id).collect()
+    }
+
+    #[test]
+    fn test_refresh() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let mut my_applet = LocationTreeApplet::default();
+        my_applet.refresh(&my_inv);
+
+        //sorted by name with everything collapsed
+        assert_eq!(row_ids(&my_applet), vec![None, Some(4), Some(1)]);
+        assert_eq!(my_applet.totals[&1], 3);
+        assert_eq!(my_applet.totals[&2], 2);
+        assert_eq!(my_applet.totals[&4], 0);
+        assert_eq!(my_applet.items[&None].len(), 1);
+        assert_eq!(my_applet.row_line(1, Some(1)).to_string(), "▸ Garage (3)");
+    }
+
+    #[test]
+    fn test_expand_collapse() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let mut my_applet = LocationTreeApplet::default();
+        my_applet.refresh(&my_inv);
+
+        my_applet.handle_action(Action::Bottom);
+        my_applet.handle_action(Action::Right);
+        assert_eq!(row_ids(&my_applet), vec![None, Some(4), Some(1), Some(2)]);
+        my_applet.handle_action(Action::Right);
+        assert_eq!(my_applet.selected(), Some(Some(2)));
+        my_applet.handle_action(Action::Select);
+        assert_eq!(row_ids(&my_applet).len(), 5);
+        assert_eq!(my_applet.row_line(3, Some(3)).to_string(), "      Bin (2)");
+
+        //left on a collapsed location goes to its parent, then collapses it
+        my_applet.handle_action(Action::Down);
+        my_applet.handle_action(Action::Left);
+        assert_eq!(my_applet.selected(), Some(Some(2)));
+        my_applet.handle_action(Action::Left);
+        my_applet.handle_action(Action::Left);
+        assert_eq!(my_applet.selected(), Some(Some(1)));
+        my_applet.handle_action(Action::Left);
+        assert_eq!(row_ids(&my_applet), vec![None, Some(4), Some(1)]);
+        assert_eq!(my_applet.selected(), Some(Some(1)));
+
+        my_applet.handle_action(Action::Edit);
+        assert_eq!(my_applet.next_state, AppState::EditLocation(1));
+    }
+
+    #[test]
+    fn test_cut_paste() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let mut my_applet = LocationTreeApplet::default();
+        my_applet.refresh(&my_inv);
+
+        //cut the garage and paste it into the attic
+        my_applet.handle_action(Action::Bottom);
+        my_applet.handle_action(Action::Cut);
+        assert_eq!(my_applet.cut, Some(1));
+        my_applet.handle_action(Action::Up);
+        assert_eq!(
+            my_applet.paste(&my_inv),
+            Ok(Some("Moved Garage to Attic".to_string()))
+        );
+        assert_eq!(my_inv.search_location_id(1).unwrap().parent_id, Some(4));
+        assert_eq!(my_applet.totals[&4], 3);
+        assert_eq!(row_ids(&my_applet), vec![None, Some(4), Some(1)]);
+
+        //a location can not be pasted inside itself
+        my_applet.handle_action(Action::Bottom);
+        my_applet.handle_action(Action::Cut);
+        assert!(my_applet.paste(&my_inv).is_err());
+        assert_eq!(my_applet.cut, Some(1));
+
+        //pasting on the top level row moves it back out
+        my_applet.handle_action(Action::Top);
+        assert!(my_applet.paste(&my_inv).is_ok());
+        assert_eq!(my_inv.search_location_id(1).unwrap().parent_id, None);
+        assert_eq!(my_applet.cut, None);
+
+        my_applet.handle_action(Action::Cut);
+        assert_eq!(my_applet.cut, None);
+        assert_eq!(my_applet.paste(&my_inv), Ok(None));
+        my_applet.handle_action(Action::Back);
+        assert_eq!(my_applet.next_state, AppState::Exit);
+    }
+
+    #[test]
+    fn test_end_to_end() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let mut my_driver = Driver::new(LocationTreeApplet::default(), my_inv);
+        my_driver.assert_screen_contains("▸ Garage (3)");
+        assert!(my_driver.find("Shelf").is_none());
+
+        //expand the garage, then move the shelf to the top level
+        my_driver.keys("End Right Down x");
+        my_driver.assert_screen_contains("Shelf (2) (cut)");
+        my_driver.keys("Home v");
+        my_driver.assert_screen_contains("Moved Shelf to top level");
+        assert_eq!(my_driver.db.search_location_id(2).unwrap().parent_id, None);
+        my_driver.assert_screen_contains("▸ Shelf (2)");
+        my_driver.assert_screen_contains("  Garage (1)");
+
+        my_driver.keys("End e");
+        assert_eq!(my_driver.last_state(), AppState::EditLocation(2));
+    }
+}
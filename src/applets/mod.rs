@@ -1,21 +1,39 @@
 pub mod applet;
+pub mod breadcrumbs;
+pub mod bulk;
+pub mod context;
 pub mod create_item;
 pub mod create_location;
+pub mod dashboard;
+#[cfg(test)]
+pub mod driver;
 pub mod edit_item;
 pub mod edit_location;
 pub mod error;
+pub mod export;
+pub mod filter;
+pub mod form;
+pub mod help;
 pub mod item_lookup;
 pub mod list_items;
 pub mod list_locations;
-pub mod top_menu;
+pub mod location_tree;
+pub mod modal;
+pub mod mouse;
+pub mod notifications;
+pub mod palette;
+pub mod preview;
+pub mod sort;
 
 pub use self::applet::Applet;
 pub use self::create_item::CreateItemApplet;
 pub use self::create_location::CreateLocationApplet;
+pub use self::dashboard::DashboardApplet;
 pub use self::edit_item::EditItemApplet;
 pub use self::edit_location::EditLocationApplet;
 pub use self::error::ErrorApplet;
+pub use self::export::ExportApplet;
 pub use self::item_lookup::ItemLookupApplet;
 pub use self::list_items::ListItemsApplet;
 pub use self::list_locations::ListLocationsApplet;
-pub use self::top_menu::TopMenuApplet;
+pub use self::location_tree::LocationTreeApplet;
@@ -0,0 +1,168 @@
+use super::mouse;
+use crate::keymap::{Action, Context, KeyReader};
+use crate::theme;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Clear, Padding, Paragraph, Wrap};
+
+#[derive(Debug, PartialEq)]
+pub enum ModalAction {
+    None,
+    /// The button with this index was pressed.
+    Choose(usize),
+    /// The modal was closed without picking a button.
+    Dismiss,
+}
+
+/// A popup asking a question with a row of buttons, drawn over the applet that owns it.
+pub struct Modal {
+    title: &'static str,
+    message: String,
+    buttons: &'static [&'static str],
+    pub(super) selected: usize,
+    /// Where each button was last drawn, for mouse hit testing.
+    areas: Vec<Rect>,
+    keys: KeyReader,
+}
+
+impl Modal {
+    pub fn new(title: &'static str, message: &str, buttons: &'static [&'static str]) -> Self {
+        Self {
+            title,
+            message: message.to_string(),
+            buttons,
+            selected: 0,
+            areas: Vec::new(),
+            keys: KeyReader::default(),
+        }
+    }
+
+    /// Selects the button at `index` to start with, e.g. the safest answer to the question.
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.selected = index;
+        self
+    }
+
+    fn step(&mut self, step: usize) {
+        self.selected = (self.selected + step) % self.buttons.len();
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ModalAction {
+        match self.keys.action(Context::Form, key) {
+            Some(Action::Cancel) => return ModalAction::Dismiss,
+            Some(Action::NextField) => self.step(1),
+            Some(Action::PreviousField) => self.step(self.buttons.len() - 1),
+            _ => match key.code {
+                KeyCode::Right => self.step(1),
+                KeyCode::Left => self.step(self.buttons.len() - 1),
+                KeyCode::Enter => return ModalAction::Choose(self.selected),
+                _ => {}
+            },
+        }
+        ModalAction::None
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> ModalAction {
+        if !mouse::is_left_click(&mouse) {
+            return ModalAction::None;
+        }
+        match self.areas.iter().position(|a| mouse::contains(*a, &mouse)) {
+            Some(i) => ModalAction::Choose(i),
+            None => ModalAction::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let area = frame
+            .area()
+            .centered(Constraint::Max(64), Constraint::Length(8));
+        let border = Block::bordered()
+            .title(self.title)
+            .border_type(ratatui::widgets::BorderType::Thick)
+            .padding(Padding::horizontal(1));
+        let [message_area, buttons_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(3)])
+                .areas(border.inner(area));
+        frame.render_widget(Clear, area);
+        frame.render_widget(border, area);
+        frame.render_widget(
+            Paragraph::new(self.message.as_str())
+                .style(theme::current().text)
+                .wrap(Wrap { trim: true }),
+            message_area,
+        );
+
+        let areas = Layout::horizontal(vec![Constraint::Fill(1); self.buttons.len()])
+            .spacing(1)
+            .split(buttons_area);
+        self.areas = areas.to_vec();
+        for (i, (label, area)) in self.buttons.iter().zip(areas.iter()).enumerate() {
+            let style = if i == self.selected {
+                theme::current().focus.bold()
+            } else {
+                Style::default().bold()
+            };
+            let button = Paragraph::new(*label)
+                .style(style)
+                .centered()
+                .block(Block::bordered());
+            frame.render_widget(button, *area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod modal_tests {
+    use super::*;
+    use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut my_modal = Modal::new("Test", "Question?", &["Yes", "No", "Maybe"]);
+        assert_eq!(my_modal.selected, 0);
+        assert_eq!(my_modal.handle_key(key(KeyCode::Left)), ModalAction::None);
+        assert_eq!(my_modal.selected, 2);
+        my_modal.handle_key(key(KeyCode::Tab));
+        my_modal.handle_key(key(KeyCode::Right));
+        assert_eq!(my_modal.selected, 1);
+        assert_eq!(
+            my_modal.handle_key(key(KeyCode::Enter)),
+            ModalAction::Choose(1)
+        );
+        assert_eq!(my_modal.handle_key(key(KeyCode::Esc)), ModalAction::Dismiss);
+
+        let mut my_modal = Modal::new("Test", "Question?", &["Yes", "No"]).with_selected(1);
+        assert_eq!(
+            my_modal.handle_key(key(KeyCode::Enter)),
+            ModalAction::Choose(1)
+        );
+    }
+
+    #[test]
+    fn test_mouse() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        let mut my_modal = Modal::new("Test", "Question?", &["Yes", "No"]);
+        terminal.draw(|frame| my_modal.render(frame)).unwrap();
+
+        let no = my_modal.areas[1];
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(
+            my_modal.handle_mouse(click(no.x + 1, no.y + 1)),
+            ModalAction::Choose(1)
+        );
+        assert_eq!(my_modal.handle_mouse(click(0, 0)), ModalAction::None);
+    }
+}
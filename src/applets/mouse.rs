@@ -0,0 +1,99 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use std::time::{Duration, Instant};
+
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// Detects double clicks by remembering the time and target of the previous click.
+///
+/// A target is whatever identifies what was clicked, usually a row index.
+pub struct ClickTracker<T = usize> {
+    last: Option<(Instant, T)>,
+}
+
+impl<T> Default for ClickTracker<T> {
+    fn default() -> Self {
+        Self { last: None }
+    }
+}
+
+impl<T: Copy + PartialEq> ClickTracker<T> {
+    /// Records a click on `target`, returning true if it completes a double click.
+    pub fn click(&mut self, target: T) -> bool {
+        self.click_at(target, Instant::now())
+    }
+
+    fn click_at(&mut self, target: T, now: Instant) -> bool {
+        let double = matches!(
+            self.last,
+            Some((time, last_target))
+                if last_target == target && now.duration_since(time) <= DOUBLE_CLICK_TIME
+        );
+        self.last = if double { None } else { Some((now, target)) };
+        double
+    }
+}
+
+pub fn is_left_click(mouse: &MouseEvent) -> bool {
+    mouse.kind == MouseEventKind::Down(MouseButton::Left)
+}
+
+pub fn contains(area: Rect, mouse: &MouseEvent) -> bool {
+    area.contains(Position::new(mouse.column, mouse.row))
+}
+
+/// Maps a click inside a bordered list or table to the index of the row under it.
+///
+/// `header` is the number of lines above the first row inside the border and `offset` is the
+/// scroll offset of the widget state.
+pub fn row_at(area: Rect, header: u16, offset: usize, mouse: &MouseEvent) -> Option<usize> {
+    let first_row = area.y + 1 + header;
+    if !contains(area, mouse) || mouse.row < first_row || mouse.row >= area.bottom() - 1 {
+        return None;
+    }
+    Some(offset + (mouse.row - first_row) as usize)
+}
+
+#[cfg(test)]
+mod mouse_tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn click(column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut my_tracker = ClickTracker::default();
+        let start = Instant::now();
+        assert!(!my_tracker.click_at(1, start));
+        assert!(my_tracker.click_at(1, start + Duration::from_millis(100)));
+        assert!(!my_tracker.click_at(1, start + Duration::from_millis(200)));
+        assert!(!my_tracker.click_at(2, start + Duration::from_millis(300)));
+        assert!(!my_tracker.click_at(2, start + Duration::from_millis(1000)));
+
+        //targets made of several parts only match as a whole
+        let mut my_tracker = ClickTracker::default();
+        assert!(!my_tracker.click_at((0, 1000), start));
+        assert!(!my_tracker.click_at((1, 0), start + Duration::from_millis(100)));
+        assert!(my_tracker.click_at((1, 0), start + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn test_row_at() {
+        let area = Rect::new(0, 0, 20, 10);
+        assert_eq!(row_at(area, 1, 0, &click(5, 0)), None);
+        assert_eq!(row_at(area, 1, 0, &click(5, 1)), None);
+        assert_eq!(row_at(area, 1, 0, &click(5, 2)), Some(0));
+        assert_eq!(row_at(area, 1, 4, &click(5, 3)), Some(5));
+        assert_eq!(row_at(area, 0, 0, &click(5, 1)), Some(0));
+        assert_eq!(row_at(area, 1, 0, &click(5, 9)), None);
+        assert_eq!(row_at(area, 1, 0, &click(25, 3)), None);
+    }
+}
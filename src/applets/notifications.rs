@@ -0,0 +1,253 @@
+use crate::keymap::{self, Action, Context};
+use crate::theme;
+use crossterm::event::{Event, KeyCode};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListState, Padding, Paragraph, Wrap};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const TOAST_TIMEOUT: Duration = Duration::from_secs(4);
+/// At most this many toasts are drawn at once, newest at the bottom.
+const MAX_TOASTS: usize = 3;
+/// The message log keeps this many of the most recent messages.
+const LOG_LIMIT: usize = 200;
+const TOAST_WIDTH: u16 = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn label(self) -> &'static str {
+        match self {
+            Level::Info => "Info",
+            Level::Success => "Success",
+            Level::Warning => "Warning",
+            Level::Error => "Error",
+        }
+    }
+
+    fn style(self) -> Style {
+        let theme = theme::current();
+        match self {
+            Level::Info => theme.info,
+            Level::Success => theme.success,
+            Level::Warning => theme.warning,
+            Level::Error => theme.error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub level: Level,
+    pub text: String,
+}
+
+/// Short lived toasts drawn over the current applet, plus a log of every message.
+///
+/// Owned by the `App` so messages survive the applet that raised them.
+#[derive(Default)]
+pub struct Notifications {
+    toasts: VecDeque<(Instant, Message)>,
+    log: VecDeque<Message>,
+    log_open: bool,
+    log_state: ListState,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, text: impl Into<String>) {
+        self.push_at(level, text, Instant::now());
+    }
+
+    fn push_at(&mut self, level: Level, text: impl Into<String>, now: Instant) {
+        let message = Message {
+            level,
+            text: text.into(),
+        };
+        self.toasts
+            .push_back((now + TOAST_TIMEOUT, message.clone()));
+        self.log.push_back(message);
+        if self.log.len() > LOG_LIMIT {
+            self.log.pop_front();
+        }
+    }
+
+    /// Drops the toasts that have timed out by `now`.
+    pub fn expire(&mut self, now: Instant) {
+        while self
+            .toasts
+            .front()
+            .is_some_and(|(deadline, _)| *deadline <= now)
+        {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// Handles the message log key, and every event while the log is open. Returns true if the
+    /// event was used.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        let Some(key) = event.as_key_press_event() else {
+            return self.log_open;
+        };
+        let toggle = keymap::current().matches(Action::Messages, Context::Form, key)
+            || keymap::current().matches(Action::Messages, Context::List, key);
+        if !self.log_open {
+            if toggle {
+                self.log_open = true;
+                self.log_state.select(self.log.len().checked_sub(1));
+            }
+            return toggle;
+        }
+        match key.code {
+            _ if toggle => self.log_open = false,
+            KeyCode::Esc | KeyCode::Char('q') => self.log_open = false,
+            KeyCode::Up => self.log_state.select_previous(),
+            KeyCode::Down => self.log_state.select_next(),
+            KeyCode::Home => self.log_state.select_first(),
+            KeyCode::End => self.log_state.select(self.log.len().checked_sub(1)),
+            _ => {}
+        }
+        true
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        if self.log_open {
+            self.render_log(frame);
+            return;
+        }
+        let area = frame.area();
+        let width = TOAST_WIDTH.min(area.width);
+        let mut bottom = area.bottom().saturating_sub(1);
+        for (_, message) in self.toasts.iter().rev().take(MAX_TOASTS) {
+            let paragraph = Paragraph::new(message.text.as_str())
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::bordered()
+                        .title(message.level.label())
+                        .border_style(message.level.style())
+                        .padding(Padding::horizontal(1)),
+                );
+            let text_width = width.saturating_sub(4).max(1);
+            let lines = (message.text.chars().count() as u16).div_ceil(text_width);
+            let height = lines.clamp(1, 4) + 2;
+            if bottom < area.y + height {
+                break;
+            }
+            let toast = Rect::new(area.right() - width, bottom - height, width, height);
+            frame.render_widget(Clear, toast);
+            frame.render_widget(paragraph, toast);
+            bottom -= height;
+        }
+    }
+
+    fn render_log(&mut self, frame: &mut Frame) {
+        let area = frame
+            .area()
+            .centered(Constraint::Percentage(80), Constraint::Percentage(80));
+        let items = self.log.iter().map(|m| {
+            Line::from(vec![
+                Span::styled(format!("{:<8}", m.level.label()), m.level.style()),
+                Span::raw(m.text.as_str()),
+            ])
+        });
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title("Messages")
+                    .title_bottom("Up/Down to scroll, Esc to close")
+                    .border_type(ratatui::widgets::BorderType::Thick)
+                    .padding(Padding::horizontal(1)),
+            )
+            .style(theme::current().text)
+            .highlight_style(theme::current().selection);
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.log_state);
+    }
+}
+
+#[cfg(test)]
+mod notifications_tests {
+    use super::*;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+
+    fn press(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_expire() {
+        let mut my_notes = Notifications::default();
+        let start = Instant::now();
+        assert_eq!(my_notes.toasts.front().map(|(deadline, _)| *deadline), None);
+        my_notes.push_at(Level::Success, "saved", start);
+        my_notes.push_at(Level::Error, "failed", start + Duration::from_secs(1));
+        assert_eq!(
+            my_notes.toasts.front().map(|(deadline, _)| *deadline),
+            Some(start + TOAST_TIMEOUT)
+        );
+
+        my_notes.expire(start + TOAST_TIMEOUT);
+        assert_eq!(my_notes.toasts.len(), 1);
+        my_notes.expire(start + TOAST_TIMEOUT + Duration::from_secs(1));
+        assert_eq!(my_notes.toasts.front().map(|(deadline, _)| *deadline), None);
+
+        //the log keeps everything
+        let log: Vec<&str> = my_notes.log.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(log, vec!["saved", "failed"]);
+    }
+
+    #[test]
+    fn test_levels() {
+        let levels = [Level::Info, Level::Success, Level::Warning, Level::Error];
+        let labels: Vec<&str> = levels.iter().map(|l| l.label()).collect();
+        assert_eq!(labels, vec!["Info", "Success", "Warning", "Error"]);
+        //each level stands out from the others
+        for (i, level) in levels.iter().enumerate() {
+            for other in &levels[i + 1..] {
+                assert_ne!(level.style(), other.style());
+            }
+        }
+
+        let mut my_notes = Notifications::default();
+        my_notes.push(Level::Info, "Cut a location first");
+        assert_eq!(my_notes.log.back().map(|m| m.level), Some(Level::Info));
+    }
+
+    #[test]
+    fn test_log_limit() {
+        let mut my_notes = Notifications::default();
+        for i in 0..LOG_LIMIT + 5 {
+            my_notes.push(Level::Success, format!("message{i}"));
+        }
+        assert_eq!(my_notes.log.len(), LOG_LIMIT);
+        assert_eq!(
+            my_notes.log.iter().next().map(|m| m.text.as_str()),
+            Some("message5")
+        );
+    }
+
+    #[test]
+    fn test_log_keys() {
+        let mut my_notes = Notifications::default();
+        my_notes.push(Level::Success, "one");
+        my_notes.push(Level::Success, "two");
+        assert!(!my_notes.handle_event(&press(KeyCode::Esc)));
+
+        assert!(my_notes.handle_event(&press(KeyCode::F(2))));
+        assert!(my_notes.log_open);
+        assert_eq!(my_notes.log_state.selected(), Some(1));
+        assert!(my_notes.handle_event(&press(KeyCode::Up)));
+        assert_eq!(my_notes.log_state.selected(), Some(0));
+        assert!(my_notes.handle_event(&press(KeyCode::Char('x'))));
+        assert!(my_notes.handle_event(&press(KeyCode::Esc)));
+        assert!(!my_notes.log_open);
+    }
+}
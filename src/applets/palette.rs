@@ -0,0 +1,323 @@
+use super::mouse;
+use crate::AppState;
+use crate::db::inventory::Inventory;
+use crate::keymap::{self, Action, Context};
+use crate::theme;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListState, Padding, Paragraph};
+
+/// The commands offered before any records, in the same order as the dashboard menu.
+const COMMANDS: &[(&str, AppState)] = &[
+    ("Dashboard", AppState::Dashboard),
+    ("List Locations", AppState::ListLocations),
+    ("Location Tree", AppState::LocationTree),
+    ("List Items", AppState::ListItems),
+    ("Create Location", AppState::CreateLocation),
+    ("Create Item", AppState::CreateItem),
+    ("Item Lookup", AppState::ItemLookup),
+    ("Export to CSV", AppState::Export),
+];
+
+struct Entry {
+    label: String,
+    /// Shown after the label but not matched, e.g. the record ID.
+    detail: String,
+    state: AppState,
+}
+
+/// A popup for jumping straight to a command or record by typing part of its name.
+///
+/// Owned by the `App`, which asks `take_choice` after each run whether an entry was picked. The
+/// records are loaded each time the palette opens, so it costs nothing while closed.
+pub struct Palette {
+    entries: Vec<Entry>,
+    query: String,
+    /// Indices into `entries` of the entries matching the query, best match first.
+    matches: Vec<usize>,
+    list_state: ListState,
+    list_area: Rect,
+    popup_area: Rect,
+    open: bool,
+    choice: Option<AppState>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            entries: commands(),
+            query: String::new(),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            list_area: Rect::default(),
+            popup_area: Rect::default(),
+            open: false,
+            choice: None,
+        }
+    }
+}
+
+fn commands() -> Vec<Entry> {
+    COMMANDS
+        .iter()
+        .map(|(label, state)| Entry {
+            label: label.to_string(),
+            detail: String::new(),
+            state: state.clone(),
+        })
+        .collect()
+}
+
+/// Scores how well `query` matches `text` as a case insensitive subsequence, or `None` if it
+/// does not match. Runs of consecutive characters and matches at the start of a word score
+/// higher, skipped characters score lower.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut start = 0;
+    let mut last = None;
+    for q in query.to_lowercase().chars() {
+        let found = (start..text.len()).find(|&i| text[i] == q)?;
+        score += 1;
+        if found > 0 && last == Some(found - 1) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - start) as i32;
+        last = Some(found);
+        start = found + 1;
+    }
+    Some(score)
+}
+
+impl Palette {
+    /// Loads the records offered by the palette.
+    fn load(&mut self, db: &Inventory) {
+        self.entries = commands();
+        for item in db.get_all_items().unwrap_or_default() {
+            self.entries.push(Entry {
+                label: format!("item: {}", item.name),
+                detail: format!("#{}", item.id),
+                state: AppState::EditItem(item.id),
+            });
+        }
+        for location in db.get_all_locations().unwrap_or_default() {
+            self.entries.push(Entry {
+                label: format!("location: {}", location.name),
+                detail: format!("#{}", location.id),
+                state: AppState::EditLocation(location.id),
+            });
+        }
+    }
+
+    /// The state for the entry the user picked, if any.
+    pub fn take_choice(&mut self) -> Option<AppState> {
+        self.choice.take()
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i32, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| fuzzy_score(&self.query, &e.label).map(|score| (score, i)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.list_state = ListState::default();
+        if !self.matches.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    fn choose(&mut self, row: usize) {
+        if let Some(&i) = self.matches.get(row) {
+            self.choice = Some(self.entries[i].state.clone());
+            self.open = false;
+        }
+    }
+
+    /// Handles the palette key, and every event while the palette is open. Returns true if the
+    /// event was used.
+    pub fn handle_event(&mut self, event: &Event, db: &Inventory) -> bool {
+        match event {
+            Event::Key(key) if key.is_press() => self.handle_key(*key, db),
+            Event::Mouse(mouse) if self.open => {
+                self.handle_mouse(*mouse);
+                true
+            }
+            _ => self.open,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, db: &Inventory) -> bool {
+        let toggle = keymap::current().matches(Action::Palette, Context::List, key)
+            || keymap::current().matches(Action::Palette, Context::Form, key);
+        if !self.open {
+            if toggle {
+                self.open = true;
+                self.query.clear();
+                self.load(db);
+                self.update_matches();
+            }
+            return toggle;
+        }
+        match key.code {
+            _ if toggle => self.open = false,
+            KeyCode::Esc => self.open = false,
+            KeyCode::Enter => self.choose(self.list_state.selected().unwrap_or(0)),
+            KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Down => self.list_state.select_next(),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.list_state.select_next(),
+            MouseEventKind::ScrollUp => self.list_state.select_previous(),
+            _ if mouse::is_left_click(&mouse) => {
+                if mouse::contains(self.list_area, &mouse) {
+                    let row = self.list_state.offset() + (mouse.row - self.list_area.y) as usize;
+                    self.choose(row);
+                } else if !mouse::contains(self.popup_area, &mouse) {
+                    self.open = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        if !self.open {
+            return;
+        }
+        let area = frame
+            .area()
+            .centered(Constraint::Max(64), Constraint::Max(16));
+        let block = Block::bordered()
+            .title("Go to")
+            .title_bottom(format!("{}/{}", self.matches.len(), self.entries.len()))
+            .border_type(ratatui::widgets::BorderType::Thick)
+            .padding(Padding::horizontal(1));
+        let [input_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(block.inner(area));
+        self.popup_area = area;
+        self.list_area = list_area;
+
+        let items = self.matches.iter().map(|&i| {
+            let entry = &self.entries[i];
+            Line::from(vec![
+                Span::raw(entry.label.as_str()),
+                Span::raw(" "),
+                Span::styled(entry.detail.as_str(), theme::current().disabled),
+            ])
+        });
+        let list = List::new(items).highlight_style(theme::current().selection);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block.style(theme::current().text), area);
+        frame.render_widget(
+            Paragraph::new(format!("> {}", self.query)).style(theme::current().focus),
+            input_area,
+        );
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+        frame.set_cursor_position(Position::new(
+            input_area.x + 2 + self.query.chars().count() as u16,
+            input_area.y,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
+    use crate::db::inventory::{Item, Location};
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn type_text(my_palette: &mut Palette, text: &str, db: &Inventory) {
+        for c in text.chars() {
+            assert!(my_palette.handle_event(&press(KeyCode::Char(c), KeyModifiers::NONE), db));
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "List Items"), None);
+        assert!(fuzzy_score("li", "List Items").is_some());
+        //prefixes beat scattered matches
+        assert!(fuzzy_score("cre", "Create Item") > fuzzy_score("cre", "Item Lookup Create"));
+        assert!(fuzzy_score("ci", "Create Item") > fuzzy_score("ci", "Location ID"));
+        assert!(fuzzy_score("LOC", "location: Garage").is_some());
+    }
+
+    #[test]
+    fn test_choose() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let garage = Location {
+            id: 7,
+            name: "Garage".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        let screws = Item {
+            id: 104,
+            name: "M3 screws".to_string(),
+            comment: None,
+            location_id: Some(7),
+        };
+        assert!(my_inv.add_location(&garage).is_ok());
+        let mut my_palette = Palette::default();
+        //records added before the palette opens are offered
+        assert!(my_inv.add_item(&screws).is_ok());
+
+        //keys are left alone while closed
+        assert!(!my_palette.handle_event(&press(KeyCode::Char('m'), KeyModifiers::NONE), &my_inv));
+        assert!(
+            my_palette.handle_event(&press(KeyCode::Char('p'), KeyModifiers::CONTROL), &my_inv)
+        );
+        assert_eq!(my_palette.matches.len(), COMMANDS.len() + 2);
+
+        type_text(&mut my_palette, "m3 scr", &my_inv);
+        assert!(my_palette.handle_event(&press(KeyCode::Enter, KeyModifiers::NONE), &my_inv));
+        assert_eq!(my_palette.take_choice(), Some(AppState::EditItem(104)));
+        assert!(!my_palette.open);
+
+        my_palette.handle_event(&press(KeyCode::Char('p'), KeyModifiers::CONTROL), &my_inv);
+        type_text(&mut my_palette, "garagx", &my_inv);
+        assert!(my_palette.matches.is_empty());
+        my_palette.handle_event(&press(KeyCode::Backspace, KeyModifiers::NONE), &my_inv);
+        my_palette.handle_event(&press(KeyCode::Enter, KeyModifiers::NONE), &my_inv);
+        assert_eq!(my_palette.take_choice(), Some(AppState::EditLocation(7)));
+
+        my_palette.handle_event(&press(KeyCode::Char('p'), KeyModifiers::CONTROL), &my_inv);
+        type_text(&mut my_palette, "crloc", &my_inv);
+        my_palette.handle_event(&press(KeyCode::Esc, KeyModifiers::NONE), &my_inv);
+        assert_eq!(my_palette.take_choice(), None);
+        my_palette.handle_event(&press(KeyCode::Char('p'), KeyModifiers::CONTROL), &my_inv);
+        assert!(my_palette.query.is_empty());
+        type_text(&mut my_palette, "crloc", &my_inv);
+        my_palette.handle_event(&press(KeyCode::Enter, KeyModifiers::NONE), &my_inv);
+        assert_eq!(my_palette.take_choice(), Some(AppState::CreateLocation));
+    }
+}
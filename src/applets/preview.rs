@@ -0,0 +1,138 @@
+use crate::theme;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Padding, Paragraph, Wrap};
+
+/// The share of the screen the pane starts with, and the limits of resizing, in percent.
+const DEFAULT_SIZE: u16 = 40;
+const MIN_SIZE: u16 = 20;
+const MAX_SIZE: u16 = 80;
+const STEP: u16 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dock {
+    Right,
+    Bottom,
+}
+
+/// A pane next to a list showing the full details of the highlighted row.
+///
+/// The owning applet decides what goes in it, the pane only tracks where it is docked and how
+/// big it is.
+pub struct PreviewPane {
+    dock: Option<Dock>,
+    /// Percentage of the screen width or height, depending on the dock.
+    size: u16,
+}
+
+impl Default for PreviewPane {
+    fn default() -> Self {
+        Self {
+            dock: None,
+            size: DEFAULT_SIZE,
+        }
+    }
+}
+
+/// A bold label followed by its value, e.g. "Location: Garage".
+pub fn field<'a>(label: &'a str, value: impl Into<String>) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(format!("{label}: "), theme::current().read_only),
+        Span::raw(value.into()),
+    ])
+}
+
+impl PreviewPane {
+    pub fn is_open(&self) -> bool {
+        self.dock.is_some()
+    }
+
+    /// Cycles between hidden, docked on the right and docked at the bottom.
+    pub fn toggle(&mut self) {
+        self.dock = match self.dock {
+            None => Some(Dock::Right),
+            Some(Dock::Right) => Some(Dock::Bottom),
+            Some(Dock::Bottom) => None,
+        };
+    }
+
+    pub fn grow(&mut self) {
+        self.size = (self.size + STEP).min(MAX_SIZE);
+    }
+
+    pub fn shrink(&mut self) {
+        self.size = self.size.saturating_sub(STEP).max(MIN_SIZE);
+    }
+
+    /// Splits `area` into the list and, if the pane is open, the pane.
+    pub fn split(&self, area: Rect) -> (Rect, Option<Rect>) {
+        let constraints = [
+            Constraint::Percentage(100 - self.size),
+            Constraint::Percentage(self.size),
+        ];
+        match self.dock {
+            None => (area, None),
+            Some(Dock::Right) => {
+                let [list, pane] = Layout::horizontal(constraints).areas(area);
+                (list, Some(pane))
+            }
+            Some(Dock::Bottom) => {
+                let [list, pane] = Layout::vertical(constraints).areas(area);
+                (list, Some(pane))
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, title: &str, lines: Vec<Line>) {
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .style(theme::current().text)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_type(ratatui::widgets::BorderType::Thick)
+                    .padding(Padding::horizontal(1)),
+            );
+        frame.render_widget(paragraph, area);
+    }
+}
+
+#[cfg(test)]
+mod preview_tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let area = Rect::new(0, 0, 100, 50);
+        let mut my_pane = PreviewPane::default();
+        assert_eq!(my_pane.split(area), (area, None));
+
+        my_pane.toggle();
+        let (list, pane) = my_pane.split(area);
+        assert_eq!(list, Rect::new(0, 0, 60, 50));
+        assert_eq!(pane, Some(Rect::new(60, 0, 40, 50)));
+
+        my_pane.toggle();
+        my_pane.grow();
+        let (list, pane) = my_pane.split(area);
+        assert_eq!(list.height, 28);
+        assert_eq!(pane.map(|p| p.height), Some(22));
+
+        my_pane.toggle();
+        assert!(!my_pane.is_open());
+    }
+
+    #[test]
+    fn test_resize_limits() {
+        let mut my_pane = PreviewPane::default();
+        for _ in 0..20 {
+            my_pane.grow();
+        }
+        assert_eq!(my_pane.size, MAX_SIZE);
+        for _ in 0..20 {
+            my_pane.shrink();
+        }
+        assert_eq!(my_pane.size, MIN_SIZE);
+    }
+}
@@ -0,0 +1,121 @@
+use ratatui::widgets::Row;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// A record shown as a row of a sortable table.
+pub trait Sortable {
+    /// Compares two records by `column`, in ascending order.
+    fn compare(&self, other: &Self, column: usize) -> Ordering;
+}
+
+/// The column a table is sorted by, shared by the list applets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortState {
+    pub column: usize,
+    pub order: SortOrder,
+}
+
+impl SortState {
+    /// Sorts ascending by a newly picked column, or flips the order if it is already sorted by it.
+    pub fn toggle(current: Option<SortState>, column: usize) -> SortState {
+        match current {
+            Some(sort) if sort.column == column && sort.order == SortOrder::Ascending => {
+                SortState {
+                    column,
+                    order: SortOrder::Descending,
+                }
+            }
+            _ => SortState {
+                column,
+                order: SortOrder::Ascending,
+            },
+        }
+    }
+
+    /// Sorts `rows` by the column, in the sort order.
+    pub fn sort<T: Sortable>(&self, rows: &mut [T]) {
+        rows.sort_by(|a, b| self.apply(a.compare(b, self.column)));
+    }
+
+    /// Applies the sort order to an ascending comparison.
+    pub fn apply(&self, ordering: Ordering) -> Ordering {
+        match self.order {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        }
+    }
+}
+
+pub fn compare_text(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Builds a table header, marking the sorted column with an arrow.
+pub fn header<'a>(titles: &[&str], sort: Option<SortState>) -> Row<'a> {
+    Row::new(titles.iter().enumerate().map(|(i, title)| match sort {
+        Some(SortState {
+            column,
+            order: SortOrder::Ascending,
+        }) if column == i => format!("{title} ▲"),
+        Some(SortState {
+            column,
+            order: SortOrder::Descending,
+        }) if column == i => format!("{title} ▼"),
+        _ => title.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod sort_tests {
+    use super::*;
+    #[test]
+    fn test_toggle() {
+        let sort = SortState::toggle(None, 1);
+        assert_eq!(sort.column, 1);
+        assert_eq!(sort.order, SortOrder::Ascending);
+        let sort = SortState::toggle(Some(sort), 1);
+        assert_eq!(sort.order, SortOrder::Descending);
+        let sort = SortState::toggle(Some(sort), 1);
+        assert_eq!(sort.order, SortOrder::Ascending);
+        let sort = SortState::toggle(Some(sort), 2);
+        assert_eq!(sort.column, 2);
+        assert_eq!(sort.order, SortOrder::Ascending);
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(compare_text("apple", "Banana"), Ordering::Less);
+        assert_eq!(compare_text("APPLE", "apple"), Ordering::Equal);
+        let sort = SortState {
+            column: 0,
+            order: SortOrder::Descending,
+        };
+        assert_eq!(sort.apply(compare_text("a", "b")), Ordering::Greater);
+    }
+
+    impl Sortable for (i64, &str) {
+        fn compare(&self, other: &Self, column: usize) -> Ordering {
+            match column {
+                0 => self.0.cmp(&other.0),
+                _ => compare_text(self.1, other.1),
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut rows = vec![(2, "b"), (1, "C"), (3, "a")];
+        let sort = SortState::toggle(None, 1);
+        sort.sort(&mut rows);
+        assert_eq!(rows, vec![(3, "a"), (2, "b"), (1, "C")]);
+        SortState::toggle(Some(sort), 1).sort(&mut rows);
+        assert_eq!(rows, vec![(1, "C"), (2, "b"), (3, "a")]);
+        SortState::toggle(None, 0).sort(&mut rows);
+        assert_eq!(rows, vec![(1, "C"), (2, "b"), (3, "a")]);
+    }
+}
@@ -0,0 +1,296 @@
+use super::{Args, CliError, ErrorReport, Format, write_json};
+use crate::db::inventory::Inventory;
+use serde::Serialize;
+use std::error;
+use std::fs;
+use std::io::{self, Read, Write};
+
+/// `batch [FILE] [--continue-on-error]`, reading the commands from stdin without a file or for
+/// `-`.
+pub fn batch(
+    args: &[String],
+    db: &Inventory,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &[], &["--continue-on-error"])?;
+    let text = match args.positional(0, 1)?.first() {
+        None | Some(&"-") => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| CliError::new(&format!("Could not read stdin: {e}")))?;
+            text
+        }
+        Some(file) => fs::read_to_string(file)
+            .map_err(|e| CliError::new(&format!("Could not read {file}: {e}")))?,
+    };
+    run_batch(&text, db, format, args.flag("--continue-on-error"), out)
+}
+
+/// What happened to one line of a batch.
+#[derive(Debug, Serialize)]
+struct LineReport {
+    line: usize,
+    command: String,
+    /// What the command printed, if it succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorReport>,
+}
+
+/// Runs every line of `text` as a command, skipping blank lines and `#` comments, and reports
+/// on each. The whole batch is one transaction: the first failure rolls back every line unless
+/// `keep_going` is set, in which case only the failed lines are left out.
+fn run_batch(
+    text: &str,
+    db: &Inventory,
+    format: Format,
+    keep_going: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let batch = db.savepoint()?;
+    let mut reports = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut report = LineReport {
+            line: number + 1,
+            command: line.to_string(),
+            output: None,
+            error: None,
+        };
+        match run_one(line, db, format) {
+            Ok(output) => report.output = Some(output),
+            Err(e) => report.error = Some(ErrorReport::new(e.as_ref())),
+        }
+        let failed = report.error.is_some();
+        reports.push(report);
+        if failed && !keep_going {
+            break;
+        }
+    }
+
+    let failed = reports.iter().filter(|r| r.error.is_some()).count();
+    if format == Format::Text {
+        for report in &reports {
+            match (&report.output, &report.error) {
+                (_, Some(error)) => {
+                    writeln!(out, "line {} failed: {}", report.line, error.message)?
+                }
+                (Some(output), None) if output.lines().count() > 1 => {
+                    write!(out, "line {}:\n{output}", report.line)?
+                }
+                (Some(output), None) => write!(out, "line {}: {output}", report.line)?,
+                (None, None) => writeln!(out, "line {}", report.line)?,
+            }
+        }
+    } else {
+        write_json(out, format, &reports)?;
+    }
+    if failed > 0 && !keep_going {
+        return Err(CliError::new(&format!(
+            "Stopped at line {}, nothing was changed",
+            reports[reports.len() - 1].line
+        )));
+    }
+    batch.commit()?;
+    if failed > 0 {
+        return Err(CliError::new(&format!(
+            "{failed} of {} lines failed, the others were committed",
+            reports.len()
+        )));
+    }
+    if format == Format::Text {
+        writeln!(out, "Committed {} lines", reports.len())?;
+    }
+    Ok(())
+}
+
+/// Runs one line in a savepoint of its own, so a line that fails part way leaves nothing
+/// behind. Returns what the command printed.
+fn run_one(line: &str, db: &Inventory, format: Format) -> Result<String, Box<dyn error::Error>> {
+    let args = split_line(line).map_err(|e| CliError::usage(&e))?;
+    if args.first().is_some_and(|command| command == "batch") {
+        return Err(CliError::usage("A batch can not run another batch"));
+    }
+    let savepoint = db.savepoint()?;
+    let mut output = Vec::new();
+    super::run(&args, db, format, &mut output)?;
+    savepoint.commit()?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// Splits a line into arguments on whitespace, keeping text in single or double quotes
+/// together. Outside single quotes a backslash takes the next character as it is.
+fn split_line(line: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    //whether an argument has started, so that "" gives an empty one
+    let mut started = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => arg.push(c),
+            (_, '\\') => {
+                let Some(next) = chars.next() else {
+                    return Err("Line ends with a backslash".to_string());
+                };
+                arg.push(next);
+                started = true;
+            }
+            (Some(_), c) => arg.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                started = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if started {
+                    args.push(std::mem::take(&mut arg));
+                    started = false;
+                }
+            }
+            (None, c) => {
+                arg.push(c);
+                started = true;
+            }
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("Missing closing {q}"));
+    }
+    if started {
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::super::{EXIT_FAILED, exit_code};
+    use super::*;
+    use crate::seed::fixtures;
+
+    /// Runs `text` as a batch, returning the result and everything written.
+    fn run_text(
+        db: &Inventory,
+        text: &str,
+        format: Format,
+        keep_going: bool,
+    ) -> (Result<(), Box<dyn error::Error>>, String) {
+        let mut out = Vec::new();
+        let result = run_batch(text, db, format, keep_going, &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(
+            split_line(r#"add-item 501 "M3 screw" @12"#).unwrap(),
+            vec!["add-item", "501", "M3 screw", "@12"]
+        );
+        assert_eq!(
+            split_line(r#"  edit 5 -c '' --name it\'s  "#).unwrap(),
+            vec!["edit", "5", "-c", "", "--name", "it's"]
+        );
+        assert_eq!(
+            split_line(r#"edit 5 -c "say \"hi\"""#).unwrap()[3],
+            r#"say "hi""#
+        );
+        assert!(split_line("edit 5 -c \"open").is_err());
+        assert!(split_line("edit 5 \\").is_err());
+    }
+
+    #[test]
+    fn test_batch() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let text = "# restock the bin
+add-item 501 \"M3 screw\" @3
+
+move 501 4
+show 501
+";
+        let (result, out) = run_text(&my_inv, text, Format::Text, false);
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "line 2: Added item 501
+line 4: Moved item 501 to Attic
+line 5:
+Item 501
+name: M3 screw
+comment: \nlocation: Attic (4)
+tags: \nCommitted 3 lines
+"
+        );
+        assert_eq!(my_inv.search_item_id(501).unwrap().location_id, Some(4));
+    }
+
+    #[test]
+    fn test_abort_or_continue() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let text = "add-item 501 screw @3
+add-item 100 taken
+delete 101
+";
+        //the first failure undoes the lines before it and stops
+        let (result, out) = run_text(&my_inv, text, Format::Text, false);
+        let error = result.unwrap_err();
+        assert_eq!(exit_code(error.as_ref()), EXIT_FAILED);
+        assert_eq!(
+            error.to_string(),
+            "CLI Error: Stopped at line 2, nothing was changed"
+        );
+        assert!(out.ends_with("line 2 failed: Item ID already exists\n"));
+        assert!(!my_inv.item_exists(501));
+        assert!(my_inv.item_exists(101));
+
+        let (result, out) = run_text(&my_inv, text, Format::JsonLines, true);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "CLI Error: 1 of 3 lines failed, the others were committed"
+        );
+        let reports: Vec<serde_json::Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0]["output"], "Added item 501\n");
+        assert_eq!(reports[1]["error"]["error"], "failed");
+        assert_eq!(reports[2]["line"], 3);
+        assert!(my_inv.item_exists(501));
+        assert!(!my_inv.item_exists(101));
+    }
+
+    #[test]
+    fn test_line_rollback() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        let path =
+            std::env::temp_dir().join(format!("inventory_batch_{}.json", std::process::id()));
+        let dump = r#"{"version": 1, "locations": [{"id": 20, "name": "Van"}],
+            "items": [{"id": 600, "name": "jack", "location_id": 21}]}"#;
+        fs::write(&path, dump).unwrap();
+        //the restore adds the location before the item fails, which has to be undone as well
+        let text = format!(
+            "restore {} --merge fail\nadd-location 30 Loft",
+            path.display()
+        );
+        let (result, _) = run_text(&my_inv, &text, Format::Text, true);
+        assert!(result.is_err());
+        assert!(!my_inv.location_exists(20));
+        assert!(my_inv.location_exists(30));
+        let _ = fs::remove_file(&path);
+
+        let (result, out) = run_text(&my_inv, "batch other.txt", Format::Text, true);
+        assert!(result.is_err());
+        assert_eq!(out, "line 1 failed: A batch can not run another batch\n");
+    }
+}
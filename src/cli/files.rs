@@ -0,0 +1,229 @@
+use super::{Args, CliError, parse_records};
+use crate::csv;
+use crate::db::inventory::{Inventory, OnConflict};
+use crate::import::{self, ColumnMap};
+use std::error;
+use std::fs;
+use std::io::Write;
+
+/// `export <items|locations> [--search TERM] [--output FILE]`, writing to stdout without a file.
+pub fn export(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--search", "--output"], &[])?;
+    let records = parse_records(args.positional(1, 1)?[0])?;
+    let (text, count) = csv::export(db, records, args.option("--search"))?;
+    match args.option("--output") {
+        Some(file) => {
+            fs::write(file, text)
+                .map_err(|e| CliError::new(&format!("Could not write {file}: {e}")))?;
+            writeln!(out, "Exported {} to {file}", records.count(count))?;
+        }
+        None => out.write_all(text.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// `import <items|locations> FILE [--map field=Column]... [--dry-run]`. Prints the report, and
+/// fails without importing anything if any row has a problem.
+pub fn import(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--map"], &["--dry-run"])?;
+    let positional = args.positional(2, 2)?;
+    let file = positional[1];
+    let mut columns = ColumnMap::new(parse_records(positional[0])?);
+    for mapping in args.all("--map") {
+        columns.set(mapping)?;
+    }
+    let text = fs::read_to_string(file)
+        .map_err(|e| CliError::new(&format!("Could not read {file}: {e}")))?;
+    let report = import::import(db, &text, &columns, args.flag("--dry-run"))?;
+    writeln!(out, "{report}")?;
+    if !report.problems.is_empty() {
+        return Err(CliError::new(&format!("{file} was not imported")));
+    }
+    Ok(())
+}
+
+/// `dump [--output FILE]`, writing the whole inventory as JSON to stdout without a file.
+pub fn dump(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--output"], &[])?;
+    args.positional(0, 0)?;
+    let json = db.export_json()?;
+    match args.option("--output") {
+        Some(file) => {
+            fs::write(file, json + "\n")
+                .map_err(|e| CliError::new(&format!("Could not write {file}: {e}")))?;
+            writeln!(out, "Dumped the inventory to {file}")?;
+        }
+        None => writeln!(out, "{json}")?,
+    }
+    Ok(())
+}
+
+/// `restore FILE [--merge fail|skip|replace|renumber]`. Without `--merge` the inventory must be
+/// empty.
+pub fn restore(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--merge"], &[])?;
+    let file = args.positional(1, 1)?[0];
+    let on_conflict = match args.option("--merge") {
+        None => None,
+        Some("fail") => Some(OnConflict::Fail),
+        Some("skip") => Some(OnConflict::Skip),
+        Some("replace") => Some(OnConflict::Replace),
+        Some("renumber") => Some(OnConflict::Renumber),
+        Some(other) => {
+            return Err(CliError::usage(&format!(
+                "Unknown merge strategy '{other}'"
+            )));
+        }
+    };
+    let json = fs::read_to_string(file)
+        .map_err(|e| CliError::new(&format!("Could not read {file}: {e}")))?;
+    match on_conflict {
+        None => {
+            db.restore_json(&json)?;
+            writeln!(out, "Restored {file}")?;
+        }
+        Some(on_conflict) => {
+            let summary = db.import_json(&json, on_conflict)?;
+            writeln!(
+                out,
+                "Merged {file}: {} added, {} skipped, {} replaced, {} renumbered",
+                summary.added, summary.skipped, summary.replaced, summary.renumbered
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod files_tests {
+    use super::super::run_line;
+    use super::*;
+    use crate::db::inventory::Item;
+
+    #[test]
+    fn test_export() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        for (id, name) in [(100, "tape"), (101, "screws")] {
+            let item = Item {
+                id,
+                name: name.to_string(),
+                comment: None,
+                location_id: None,
+            };
+            assert!(my_inv.add_item(&item).is_ok());
+        }
+
+        let (result, out) = run_line(&my_inv, "export items --search scr");
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "id,name,comment,location_id,location\r\n101,screws,,,\r\n"
+        );
+
+        let path = std::env::temp_dir().join(format!("inventory_cli_{}.csv", std::process::id()));
+        let line = format!("export locations -o {}", path.display());
+        let (result, out) = run_line(&my_inv, &line);
+        assert!(result.is_ok());
+        assert!(out.starts_with("Exported 0 locations"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "id,name,comment,parent_id,parent\r\n"
+        );
+        let _ = fs::remove_file(&path);
+
+        assert!(run_line(&my_inv, "export").0.is_err());
+        assert!(run_line(&my_inv, "export tags").0.is_err());
+        assert!(run_line(&my_inv, "export items --search").0.is_err());
+    }
+
+    #[test]
+    fn test_import() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let path =
+            std::env::temp_dir().join(format!("inventory_import_{}.csv", std::process::id()));
+        fs::write(
+            &path,
+            "Number,Description,Where\n1,tape,Garage\n2,glue,Garage\n",
+        )
+        .unwrap();
+        let line = format!(
+            "import items {} --map id=Number --map name=Description --map location=Where",
+            path.display()
+        );
+
+        let (result, out) = run_line(&my_inv, &format!("{line} --dry-run"));
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "new location: Garage\nReady to import 2 items, creating 1 location\n"
+        );
+        assert!(!my_inv.item_exists(1));
+
+        assert!(run_line(&my_inv, &line).0.is_ok());
+        assert!(my_inv.item_exists(1) && my_inv.item_exists(2));
+
+        //importing again clashes with the IDs just added
+        let (result, out) = run_line(&my_inv, &line);
+        assert!(result.is_err());
+        assert!(out.starts_with("row 2: Item ID already exists"));
+        let _ = fs::remove_file(&path);
+
+        assert!(run_line(&my_inv, "import items").0.is_err());
+        assert!(
+            run_line(&my_inv, "import items x.csv --map size=Size")
+                .0
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_dump_restore() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let item = Item {
+            id: 100,
+            name: "tape".to_string(),
+            comment: None,
+            location_id: None,
+        };
+        assert!(my_inv.add_item(&item).is_ok());
+        let path = std::env::temp_dir().join(format!("inventory_dump_{}.json", std::process::id()));
+        let (result, _) = run_line(&my_inv, &format!("dump -o {}", path.display()));
+        assert!(result.is_ok());
+
+        let other_inv = Inventory::open_in_memory().unwrap();
+        let line = format!("restore {}", path.display());
+        assert!(run_line(&other_inv, &line).0.is_ok());
+        assert!(other_inv.item_exists(100));
+
+        //the inventory is no longer empty, so the second time needs a merge strategy
+        assert!(run_line(&other_inv, &line).0.is_err());
+        let (result, out) = run_line(&other_inv, &format!("{line} --merge renumber"));
+        assert!(result.is_ok());
+        assert!(out.contains("1 renumbered"));
+        assert!(other_inv.item_exists(101));
+        let _ = fs::remove_file(&path);
+
+        assert!(
+            run_line(&other_inv, &format!("{line} --merge merge"))
+                .0
+                .is_err()
+        );
+        assert!(run_line(&other_inv, "dump extra").0.is_err());
+    }
+}
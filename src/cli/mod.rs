@@ -0,0 +1,526 @@
+use crate::csv::Records;
+use crate::db::inventory::{Inventory, RecordKind};
+use serde::Serialize;
+use std::error;
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+
+mod batch;
+mod files;
+mod records;
+
+pub const USAGE: &str = "Usage: inventory [--db FILE] [--seed FILE]... [--format FORMAT] [COMMAND]
+
+Commands:
+  add-item ID NAME [@LOCATION] [--comment TEXT] [--location ID]
+  add-location ID NAME [@PARENT] [--comment TEXT] [--parent ID]
+  list [items|locations] [--location ID]
+  find TERM
+  show [item|location] ID
+  move ITEM LOCATION
+  edit [item|location] ID [--name NAME] [--comment TEXT] [--location ID] [--parent ID]
+  delete [item|location] ID
+  export <items|locations> [--search TERM] [--output FILE]
+  import <items|locations> FILE [--map field=Column]... [--dry-run]
+  dump [--output FILE]
+  restore FILE [--merge fail|skip|replace|renumber]
+  batch [FILE] [--continue-on-error]
+
+Commands that change the inventory need a database, from --db or the config.
+Locations can be given as none to leave an item or location without one.
+FORMAT is text, json or jsonl, and applies to list, find, show, batch and errors.
+batch runs one command per line from FILE, or stdin without one, in a single transaction.
+Without a command the interface starts.";
+
+/// Exit code for a command that failed, e.g. on a validation error.
+pub const EXIT_FAILED: u8 = 1;
+/// Exit code for arguments that could not be understood.
+pub const EXIT_USAGE: u8 = 2;
+/// Exit code for an item or location that does not exist.
+pub const EXIT_NOT_FOUND: u8 = 3;
+/// Exit code for a database that could not be opened, read or written.
+pub const EXIT_STORAGE: u8 = 4;
+
+#[derive(Debug)]
+pub struct CliError {
+    error_text: String,
+    exit_code: u8,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CLI Error: {}", self.error_text)
+    }
+}
+
+impl error::Error for CliError {}
+
+impl CliError {
+    pub fn new(msg: &str) -> Box<Self> {
+        Box::new(CliError {
+            error_text: msg.to_string(),
+            exit_code: EXIT_FAILED,
+        })
+    }
+
+    /// A problem with the arguments, printed with the usage text.
+    pub fn usage(msg: &str) -> Box<Self> {
+        Box::new(CliError {
+            error_text: msg.to_string(),
+            exit_code: EXIT_USAGE,
+        })
+    }
+
+    pub fn not_found(kind: RecordKind, id: i64) -> Box<Self> {
+        Box::new(CliError {
+            error_text: format!("{} {id} does not exist", kind_name(kind)),
+            exit_code: EXIT_NOT_FOUND,
+        })
+    }
+}
+
+fn cli_error<'a>(error: &'a (dyn error::Error + 'static)) -> Option<&'a CliError> {
+    //`?` on a `Box<CliError>` boxes it a second time
+    error
+        .downcast_ref::<CliError>()
+        .or_else(|| error.downcast_ref::<Box<CliError>>().map(|e| e.as_ref()))
+}
+
+/// The name JSON errors give what went wrong, and the matching exit code. Database errors other
+/// than broken constraints are storage failures, anything else not from a `CliError` just failed.
+fn error_kind(error: &(dyn error::Error + 'static)) -> (&'static str, u8) {
+    if let Some(e) = cli_error(error) {
+        return match e.exit_code {
+            EXIT_USAGE => ("usage", EXIT_USAGE),
+            EXIT_NOT_FOUND => ("not_found", EXIT_NOT_FOUND),
+            _ => ("failed", EXIT_FAILED),
+        };
+    }
+    match error.downcast_ref::<rusqlite::Error>() {
+        //the database's way of saying a record it was asked to change does not exist
+        Some(rusqlite::Error::QueryReturnedNoRows) => ("not_found", EXIT_NOT_FOUND),
+        Some(e) if e.sqlite_error_code() != Some(rusqlite::ErrorCode::ConstraintViolation) => {
+            ("storage", EXIT_STORAGE)
+        }
+        _ => ("failed", EXIT_FAILED),
+    }
+}
+
+/// The exit code for `error`.
+pub fn exit_code(error: &(dyn error::Error + 'static)) -> u8 {
+    error_kind(error).1
+}
+
+/// An error as written to stderr in the JSON formats.
+#[derive(Debug, Serialize)]
+struct ErrorReport {
+    /// One of `usage`, `not_found`, `storage` or `failed`.
+    error: &'static str,
+    message: String,
+    exit_code: u8,
+}
+
+impl ErrorReport {
+    fn new(error: &(dyn error::Error + 'static)) -> Self {
+        let (kind, exit_code) = error_kind(error);
+        Self {
+            error: kind,
+            message: cli_error(error).map_or_else(|| error.to_string(), |e| e.error_text.clone()),
+            exit_code,
+        }
+    }
+}
+
+/// Prints `error` to stderr in `format`, with the usage text if the arguments were wrong.
+pub fn print_error(error: &(dyn error::Error + 'static), format: Format) {
+    match format {
+        Format::Text if exit_code(error) == EXIT_USAGE => eprintln!("{error}\n{USAGE}"),
+        Format::Text => eprintln!("{error}"),
+        Format::Json | Format::JsonLines => {
+            eprintln!(
+                "{}",
+                serde_json::to_string(&ErrorReport::new(error)).unwrap()
+            )
+        }
+    }
+}
+
+fn kind_name(kind: RecordKind) -> &'static str {
+    match kind {
+        RecordKind::Item => "Item",
+        RecordKind::Location => "Location",
+    }
+}
+
+/// How commands print what they read and how errors are printed, from `--format FORMAT`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Tables and `key: value` lines for people.
+    #[default]
+    Text,
+    /// One JSON document: an array for lists, an object for a single record.
+    Json,
+    /// JSON Lines, one compact object per record.
+    JsonLines,
+}
+
+impl Format {
+    pub fn parse(text: &str) -> Result<Self, Box<CliError>> {
+        match text {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::JsonLines),
+            _ => Err(CliError::usage(&format!("Unknown format '{text}'"))),
+        }
+    }
+}
+
+/// Options given before the command, which apply to the interface as well.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    /// The database file from `--db FILE`.
+    pub database: Option<PathBuf>,
+    /// Seed files to load before anything else, from `--seed FILE`.
+    pub seeds: Vec<PathBuf>,
+    pub format: Format,
+}
+
+/// Takes the options off the front of `args`, which leave out the program name. Returns them
+/// with the command and its arguments, which are empty if the interface should start.
+pub fn parse_options(args: &[String]) -> Result<(Options, &[String]), Box<CliError>> {
+    let mut options = Options::default();
+    let mut rest = args;
+    loop {
+        match rest {
+            [option, file, tail @ ..] if option == "--db" => {
+                options.database = Some(PathBuf::from(file));
+                rest = tail;
+            }
+            [option, file, tail @ ..] if option == "--seed" => {
+                options.seeds.push(PathBuf::from(file));
+                rest = tail;
+            }
+            [option, format, tail @ ..] if option == "--format" => {
+                options.format = Format::parse(format)?;
+                rest = tail;
+            }
+            [option] if ["--db", "--seed", "--format"].contains(&option.as_str()) => {
+                return Err(CliError::usage(&format!("{option} needs a value")));
+            }
+            _ => break,
+        }
+    }
+    match rest.first().map(String::as_str) {
+        Some("--help" | "-h") => Ok((options, rest)),
+        Some(option) if option.starts_with('-') => {
+            Err(CliError::usage(&format!("Unknown option '{option}'")))
+        }
+        _ => Ok((options, rest)),
+    }
+}
+
+/// The commands that change the inventory.
+const CHANGING: &[&str] = &[
+    "add-item",
+    "add-location",
+    "move",
+    "edit",
+    "delete",
+    "import",
+    "restore",
+    "batch",
+];
+
+/// Refuses a command in `args` that changes the inventory, for when there is no database file
+/// and the changes would be lost as soon as the command finishes. A dry run changes nothing.
+pub fn require_database(args: &[String]) -> Result<(), Box<CliError>> {
+    if args.iter().any(|a| a == "--dry-run" || a == "-d") {
+        return Ok(());
+    }
+    match args.first() {
+        Some(command) if CHANGING.contains(&command.as_str()) => Err(CliError::usage(&format!(
+            "'{command}' changes the inventory, which is not saved without a database; \
+             give one with --db or in the config"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Runs the command given by `args`, which leave out the program name and options, writing its
+/// output to `out` in `format`.
+pub fn run(
+    args: &[String],
+    db: &Inventory,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::usage("No command given"));
+    };
+    match command.as_str() {
+        "add-item" => records::add_item(rest, db, out),
+        "add-location" => records::add_location(rest, db, out),
+        "list" => records::list(rest, db, format, out),
+        "find" => records::find(rest, db, format, out),
+        "show" => records::show(rest, db, format, out),
+        "move" => records::move_item(rest, db, out),
+        "edit" => records::edit(rest, db, out),
+        "delete" => records::delete(rest, db, out),
+        "export" => files::export(rest, db, out),
+        "import" => files::import(rest, db, out),
+        "dump" => files::dump(rest, db, out),
+        "restore" => files::restore(rest, db, out),
+        "batch" => batch::batch(rest, db, format, out),
+        "help" | "--help" | "-h" => Ok(writeln!(out, "{USAGE}")?),
+        _ => Err(CliError::usage(&format!("Unknown command '{command}'"))),
+    }
+}
+
+/// A command's arguments, split into positional arguments and `--name value` options.
+struct Args<'a> {
+    positional: Vec<&'a str>,
+    options: Vec<(&'static str, &'a str)>,
+    flags: Vec<&'static str>,
+}
+
+impl<'a> Args<'a> {
+    /// Splits `args`, accepting the `options` that take a value and the `flags` that do not.
+    /// Each can also be given by its first letter, e.g. `-o` for `--output`.
+    fn parse(
+        args: &'a [String],
+        options: &[&'static str],
+        flags: &[&'static str],
+    ) -> Result<Self, Box<CliError>> {
+        let find = |names: &[&'static str], arg: &str| {
+            names
+                .iter()
+                .copied()
+                .find(|name| arg == *name || (arg.len() == 2 && name[2..].starts_with(&arg[1..])))
+        };
+        let mut parsed = Self {
+            positional: Vec::new(),
+            options: Vec::new(),
+            flags: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') || arg.len() == 1 {
+                parsed.positional.push(arg);
+            } else if let Some(name) = find(options, arg) {
+                let Some(value) = args.next() else {
+                    return Err(CliError::usage(&format!("{name} needs a value")));
+                };
+                parsed.options.push((name, value));
+            } else if let Some(name) = find(flags, arg) {
+                parsed.flags.push(name);
+            } else {
+                return Err(CliError::usage(&format!("Unknown option '{arg}'")));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// The last value given for `name`.
+    fn option(&self, name: &str) -> Option<&'a str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| *option == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Every value given for `name`, in order.
+    fn all(&self, name: &str) -> impl Iterator<Item = &'a str> {
+        self.options
+            .iter()
+            .filter(move |(option, _)| *option == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    /// The positional arguments, failing unless there are between `min` and `max` of them.
+    fn positional(&self, min: usize, max: usize) -> Result<&[&'a str], Box<CliError>> {
+        match self.positional.len() {
+            n if n < min => Err(CliError::usage("Missing arguments")),
+            n if n > max => Err(CliError::usage(&format!(
+                "Unexpected argument '{}'",
+                self.positional[max]
+            ))),
+            _ => Ok(&self.positional),
+        }
+    }
+}
+
+/// Writes one record as a JSON object, pretty printed unless it is for JSON Lines.
+fn write_record(
+    out: &mut impl Write,
+    format: Format,
+    record: &impl Serialize,
+) -> Result<(), Box<dyn error::Error>> {
+    match format {
+        Format::JsonLines => writeln!(out, "{}", serde_json::to_string(record)?)?,
+        _ => writeln!(out, "{}", serde_json::to_string_pretty(record)?)?,
+    }
+    Ok(())
+}
+
+/// Writes `records` as a JSON array, or one line each for JSON Lines.
+fn write_json<T: Serialize>(
+    out: &mut impl Write,
+    format: Format,
+    records: &[T],
+) -> Result<(), Box<dyn error::Error>> {
+    match format {
+        Format::JsonLines => {
+            for record in records {
+                write_record(out, format, record)?;
+            }
+            Ok(())
+        }
+        _ => write_record(out, format, &records),
+    }
+}
+
+/// Parses an ID given on the command line.
+fn parse_id(text: &str, label: &str) -> Result<i64, Box<CliError>> {
+    text.parse()
+        .map_err(|_| CliError::usage(&format!("Failed to parse {label} '{text}'")))
+}
+
+/// Parses `items` or `locations` given on the command line.
+fn parse_records(text: &str) -> Result<Records, Box<CliError>> {
+    Records::parse(text)
+        .map_err(|_| CliError::usage(&format!("Expected items or locations, not '{text}'")))
+}
+
+#[cfg(test)]
+fn split(line: &str) -> Vec<String> {
+    line.split_whitespace().map(str::to_string).collect()
+}
+
+/// Runs `line` as a command, returning the result and everything written.
+#[cfg(test)]
+fn run_line(db: &Inventory, line: &str) -> (Result<(), Box<dyn error::Error>>, String) {
+    let line = split(line);
+    let (options, args) = parse_options(&line).unwrap();
+    let mut out = Vec::new();
+    let result = run(args, db, options.format, &mut out);
+    (result, String::from_utf8(out).unwrap())
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        let line = split("--db stock.db --seed a.toml --seed b.toml export items");
+        let (options, rest) = parse_options(&line).unwrap();
+        assert_eq!(options.database, Some(PathBuf::from("stock.db")));
+        assert_eq!(
+            options.seeds,
+            vec![PathBuf::from("a.toml"), PathBuf::from("b.toml")]
+        );
+        assert_eq!(rest, &split("export items")[..]);
+
+        let line = split("--seed a.toml");
+        let (_, rest) = parse_options(&line).unwrap();
+        assert!(rest.is_empty());
+
+        assert!(parse_options(&split("--seed")).is_err());
+        let error = parse_options(&split("--fast export")).unwrap_err();
+        assert_eq!(error.exit_code, EXIT_USAGE);
+        let line = split("--help");
+        assert_eq!(parse_options(&line).unwrap().1, &line[..]);
+
+        let line = split("--format jsonl list");
+        assert_eq!(parse_options(&line).unwrap().0.format, Format::JsonLines);
+        assert!(parse_options(&split("--format yaml list")).is_err());
+    }
+
+    #[test]
+    fn test_require_database() {
+        assert!(require_database(&split("list items")).is_ok());
+        assert!(require_database(&split("export items")).is_ok());
+        assert!(require_database(&[]).is_ok());
+        assert!(require_database(&split("import items stock.csv --dry-run")).is_ok());
+        for line in [
+            "add-item 100 tape",
+            "move 100 2",
+            "batch",
+            "restore dump.json",
+        ] {
+            let error = require_database(&split(line)).unwrap_err();
+            assert_eq!(error.exit_code, EXIT_USAGE);
+        }
+    }
+
+    #[test]
+    fn test_args() {
+        let line = split("one -o out.csv two --map a=b -m c=d --dry-run");
+        let my_args = Args::parse(&line, &["--output", "--map"], &["--dry-run"]).unwrap();
+        assert_eq!(my_args.positional(2, 2).unwrap(), ["one", "two"]);
+        assert!(my_args.positional(3, 3).is_err());
+        assert!(my_args.positional(0, 1).is_err());
+        assert_eq!(my_args.option("--output"), Some("out.csv"));
+        assert_eq!(my_args.all("--map").collect::<Vec<_>>(), vec!["a=b", "c=d"]);
+        assert!(my_args.flag("--dry-run"));
+
+        assert!(Args::parse(&split("--output"), &["--output"], &[]).is_err());
+        assert!(Args::parse(&split("--force"), &["--output"], &[]).is_err());
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let (result, _) = run_line(&my_inv, "fly away");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+        let (result, _) = run_line(&my_inv, "show 5");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_NOT_FOUND);
+        let (result, _) = run_line(&my_inv, "add-item 5 screws --location 9");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_FAILED);
+    }
+
+    #[test]
+    fn test_error_report() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let (result, _) = run_line(&my_inv, "show location 5");
+        let report = serde_json::to_string(&ErrorReport::new(result.unwrap_err().as_ref()));
+        assert_eq!(
+            report.unwrap(),
+            r#"{"error":"not_found","message":"Location 5 does not exist","exit_code":3}"#
+        );
+
+        //the usage text is left out of the message
+        let (result, _) = run_line(&my_inv, "list --fast");
+        let report = ErrorReport::new(result.unwrap_err().as_ref());
+        assert_eq!(report.error, "usage");
+        assert_eq!(report.message, "Unknown option '--fast'");
+
+        //broken constraints are not storage failures
+        assert!(run_line(&my_inv, "add-location 1 Garage").0.is_ok());
+        assert!(
+            run_line(&my_inv, "add-location 2 Shelf --parent 1")
+                .0
+                .is_ok()
+        );
+        let (result, _) = run_line(&my_inv, "delete location 1");
+        assert_eq!(
+            ErrorReport::new(result.unwrap_err().as_ref()).error,
+            "failed"
+        );
+        let storage: Box<dyn error::Error> = Box::new(rusqlite::Error::InvalidQuery);
+        let report = ErrorReport::new(storage.as_ref());
+        assert_eq!((report.error, report.exit_code), ("storage", EXIT_STORAGE));
+        let missing: Box<dyn error::Error> = Box::new(rusqlite::Error::QueryReturnedNoRows);
+        let report = ErrorReport::new(missing.as_ref());
+        assert_eq!(
+            (report.error, report.exit_code),
+            ("not_found", EXIT_NOT_FOUND)
+        );
+    }
+}
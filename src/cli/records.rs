@@ -0,0 +1,638 @@
+use super::{Args, CliError, Format, parse_id, parse_records, write_json, write_record};
+use crate::csv::Records;
+use crate::db::inventory::{Inventory, Item, ItemChange, Location, RecordKind};
+use crate::validation;
+use serde::Serialize;
+use std::error;
+use std::io::{self, Write};
+
+/// `add-item ID NAME [@LOCATION] [--comment TEXT] [--location ID]`
+pub fn add_item(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--comment", "--location"], &[])?;
+    let positional = args.positional(2, 3)?;
+    let location = at_location(positional.get(2).copied(), &args, "--location")?;
+    let id = parse_id(positional[0], "Item ID")?;
+    validation::item_id_unused(positional[0], db).map_err(|e| CliError::new(&e))?;
+    let item = Item {
+        id,
+        name: check_name(positional[1])?,
+        comment: comment(&args),
+        location_id: location_arg(db, location)?.flatten(),
+    };
+    db.add_item(&item)?;
+    writeln!(out, "Added item {id}")?;
+    Ok(())
+}
+
+/// `add-location ID NAME [@PARENT] [--comment TEXT] [--parent ID]`
+pub fn add_location(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--comment", "--parent"], &[])?;
+    let positional = args.positional(2, 3)?;
+    let parent = at_location(positional.get(2).copied(), &args, "--parent")?;
+    let id = parse_id(positional[0], "Location ID")?;
+    validation::location_id_unused(positional[0], db).map_err(|e| CliError::new(&e))?;
+    let location = Location {
+        id,
+        name: check_name(positional[1])?,
+        comment: comment(&args),
+        parent_id: location_arg(db, parent)?.flatten(),
+    };
+    db.add_location(&location)?;
+    writeln!(out, "Added location {id}")?;
+    Ok(())
+}
+
+/// `list [items|locations] [--location ID]`, listing items when neither is given. `--location`
+/// lists only the items directly in that location.
+pub fn list(
+    args: &[String],
+    db: &Inventory,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &["--location"], &[])?;
+    let records = match args.positional(0, 1)?.first() {
+        Some(records) => parse_records(records)?,
+        None => Records::Items,
+    };
+    match (records, args.option("--location")) {
+        (Records::Items, None) => items(db, db.get_all_items()?, format, out),
+        (Records::Items, Some(location)) => {
+            let id = parse_id(location, "Location ID")?;
+            if !db.location_exists(id) {
+                return Err(CliError::not_found(RecordKind::Location, id));
+            }
+            items(db, db.items_by_location_id(id)?, format, out)
+        }
+        (Records::Locations, None) => locations(db, db.get_all_locations()?, format, out),
+        (Records::Locations, Some(_)) => Err(CliError::usage("--location only lists items")),
+    }
+}
+
+/// A record in `find`'s JSON output, tagged with its kind since items and locations are mixed.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Found<'a> {
+    Item(&'a Item),
+    Location(&'a Location),
+}
+
+/// `find TERM`, listing the items and then the locations whose name or comment contains it.
+/// Fails if nothing does.
+pub fn find(
+    args: &[String],
+    db: &Inventory,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &[], &[])?;
+    let term = args.positional(1, 1)?[0];
+    let mut items = db.search_items(term)?;
+    let mut locations = db.search_locations(term)?;
+    //in JSON no match is just an empty list
+    if items.is_empty() && locations.is_empty() && format == Format::Text {
+        return Err(CliError::new(&format!("Nothing matches '{term}'")));
+    }
+    items.sort_by_key(|item| item.id);
+    locations.sort_by_key(|location| location.id);
+    if format != Format::Text {
+        let found: Vec<Found> = (items.iter().map(Found::Item))
+            .chain(locations.iter().map(Found::Location))
+            .collect();
+        return write_json(out, format, &found);
+    }
+    let found_items = !items.is_empty();
+    if found_items {
+        items_table(db, items, out)?;
+    }
+    if !locations.is_empty() {
+        if found_items {
+            writeln!(out)?;
+        }
+        locations_table(db, locations, out)?;
+    }
+    Ok(())
+}
+
+/// `show [item|location] ID`
+pub fn show(
+    args: &[String],
+    db: &Inventory,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &[], &[])?;
+    let (kind, id) = kind_and_id(args.positional(1, 2)?)?;
+    let paths = db.location_paths()?;
+    let path = |location_id: Option<i64>| match location_id {
+        Some(location_id) => format!("{} ({location_id})", paths[&location_id]),
+        None => "none".to_string(),
+    };
+    match kind {
+        RecordKind::Item => {
+            let item = db
+                .search_item_id(id)
+                .ok_or_else(|| CliError::not_found(kind, id))?;
+            if format != Format::Text {
+                return write_record(out, format, &item);
+            }
+            writeln!(out, "Item {id}")?;
+            writeln!(out, "name: {}", item.name)?;
+            writeln!(out, "comment: {}", item.comment.unwrap_or_default())?;
+            writeln!(out, "location: {}", path(item.location_id))?;
+            writeln!(out, "tags: {}", db.tags_for_item(id)?.join(", "))?;
+        }
+        RecordKind::Location => {
+            let location = db
+                .search_location_id(id)
+                .ok_or_else(|| CliError::not_found(kind, id))?;
+            if format != Format::Text {
+                return write_record(out, format, &location);
+            }
+            let mut items = db.items_by_location_id(id)?;
+            items.sort_by_key(|item| item.id);
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| format!("{} {}", item.id, item.name))
+                .collect();
+            writeln!(out, "Location {id}")?;
+            writeln!(out, "name: {}", location.name)?;
+            writeln!(out, "comment: {}", location.comment.unwrap_or_default())?;
+            writeln!(out, "parent: {}", path(location.parent_id))?;
+            writeln!(out, "items: {}", items.join(", "))?;
+        }
+    }
+    Ok(())
+}
+
+/// `move ITEM LOCATION`, where the location can be `none`.
+pub fn move_item(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &[], &[])?;
+    let positional = args.positional(2, 2)?;
+    let id = parse_id(positional[0], "Item ID")?;
+    if !db.item_exists(id) {
+        return Err(CliError::not_found(RecordKind::Item, id));
+    }
+    match location_arg(db, Some(positional[1]))?.flatten() {
+        Some(location_id) => {
+            db.update_items(&[id], &ItemChange::Move(Some(location_id)))?;
+            writeln!(
+                out,
+                "Moved item {id} to {}",
+                db.location_paths()?[&location_id]
+            )?;
+        }
+        None => {
+            db.update_items(&[id], &ItemChange::Move(None))?;
+            writeln!(out, "Moved item {id} out of its location")?;
+        }
+    }
+    Ok(())
+}
+
+/// `edit [item|location] ID [--name NAME] [--comment TEXT] [--location ID] [--parent ID]`,
+/// changing only the fields given.
+pub fn edit(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(
+        args,
+        &["--name", "--comment", "--location", "--parent"],
+        &[],
+    )?;
+    let (kind, id) = kind_and_id(args.positional(1, 2)?)?;
+    if args.options.is_empty() {
+        return Err(CliError::usage("Nothing to change"));
+    }
+    let name = args.option("--name").map(check_name).transpose()?;
+    let new_comment = args.option("--comment").map(|_| comment(&args));
+    match kind {
+        RecordKind::Item => {
+            if args.option("--parent").is_some() {
+                return Err(CliError::usage("Items have a --location, not a --parent"));
+            }
+            let mut item = db
+                .search_item_id(id)
+                .ok_or_else(|| CliError::not_found(kind, id))?;
+            item.name = name.unwrap_or(item.name);
+            item.comment = new_comment.unwrap_or(item.comment);
+            if let Some(location_id) = location_arg(db, args.option("--location"))? {
+                item.location_id = location_id;
+            }
+            db.edit_item(&item)?;
+        }
+        RecordKind::Location => {
+            if args.option("--location").is_some() {
+                return Err(CliError::usage(
+                    "Locations have a --parent, not a --location",
+                ));
+            }
+            let mut location = db
+                .search_location_id(id)
+                .ok_or_else(|| CliError::not_found(kind, id))?;
+            location.name = name.unwrap_or(location.name);
+            location.comment = new_comment.unwrap_or(location.comment);
+            if let Some(parent_id) = location_arg(db, args.option("--parent"))? {
+                location.parent_id = parent_id;
+            }
+            db.edit_location(&location)?;
+        }
+    }
+    writeln!(out, "Updated {} {id}", kind_word(kind))?;
+    Ok(())
+}
+
+/// `delete [item|location] ID`. A location must be emptied first.
+pub fn delete(
+    args: &[String],
+    db: &Inventory,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let args = Args::parse(args, &[], &[])?;
+    let (kind, id) = kind_and_id(args.positional(1, 2)?)?;
+    match kind {
+        RecordKind::Item if db.item_exists(id) => {
+            db.update_items(&[id], &ItemChange::Delete)?;
+        }
+        RecordKind::Location if db.location_exists(id) => db.delete_location(id)?,
+        _ => return Err(CliError::not_found(kind, id)),
+    }
+    writeln!(out, "Deleted {} {id}", kind_word(kind))?;
+    Ok(())
+}
+
+fn kind_word(kind: RecordKind) -> &'static str {
+    match kind {
+        RecordKind::Item => "item",
+        RecordKind::Location => "location",
+    }
+}
+
+/// Reads `[item|location] ID`, where the kind defaults to item.
+fn kind_and_id(positional: &[&str]) -> Result<(RecordKind, i64), Box<CliError>> {
+    let (kind, id) = match positional {
+        [id] => (RecordKind::Item, id),
+        ["item", id] => (RecordKind::Item, id),
+        ["location", id] => (RecordKind::Location, id),
+        [other, _] => {
+            return Err(CliError::usage(&format!(
+                "Expected item or location, not '{other}'"
+            )));
+        }
+        _ => return Err(CliError::usage("Missing arguments")),
+    };
+    Ok((kind, parse_id(id, "ID")?))
+}
+
+fn check_name(name: &str) -> Result<String, Box<CliError>> {
+    validation::not_blank("Name", name).map_err(|e| CliError::new(&e))?;
+    Ok(name.to_string())
+}
+
+/// The `--comment`, if it was given and is not empty.
+fn comment(args: &Args) -> Option<String> {
+    args.option("--comment")
+        .filter(|comment| !comment.is_empty())
+        .map(str::to_string)
+}
+
+/// Reads a location given either with the `@ID` shorthand, e.g. `add-item 100 screws @12`, or
+/// with `option`. Giving both is a usage error.
+fn at_location<'a>(
+    at: Option<&'a str>,
+    args: &Args<'a>,
+    option: &str,
+) -> Result<Option<&'a str>, Box<CliError>> {
+    let Some(text) = at else {
+        return Ok(args.option(option));
+    };
+    if args.option(option).is_some() {
+        return Err(CliError::usage(&format!(
+            "Give the location as @ID or with {option}, not both"
+        )));
+    }
+    text.strip_prefix('@').map(Some).ok_or_else(|| {
+        CliError::usage(&format!(
+            "Unexpected argument '{text}', locations are given as @ID"
+        ))
+    })
+}
+
+/// Reads a location ID given on the command line, which must exist. Returns `Some(None)` for
+/// `none`, and `None` if no location was given at all.
+fn location_arg(db: &Inventory, text: Option<&str>) -> Result<Option<Option<i64>>, Box<CliError>> {
+    match text {
+        None => Ok(None),
+        Some("none") => Ok(Some(None)),
+        Some(text) => {
+            let id = parse_id(text, "Location ID")?;
+            validation::location_id_exists(text, db)
+                .map_err(|e| CliError::new(&format!("{e}: {id}")))?;
+            Ok(Some(Some(id)))
+        }
+    }
+}
+
+/// Lists `items` sorted by ID, as a table or as JSON.
+fn items(
+    db: &Inventory,
+    mut items: Vec<Item>,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    items.sort_by_key(|item| item.id);
+    match format {
+        Format::Text => items_table(db, items, out),
+        _ => write_json(out, format, &items),
+    }
+}
+
+/// Lists `locations` sorted by ID, as a table or as JSON.
+fn locations(
+    db: &Inventory,
+    mut locations: Vec<Location>,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    locations.sort_by_key(|location| location.id);
+    match format {
+        Format::Text => locations_table(db, locations, out),
+        _ => write_json(out, format, &locations),
+    }
+}
+
+/// Writes `rows` below `header`, padding each column to its widest cell.
+fn table(out: &mut impl Write, header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        writeln!(out, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+fn items_table(
+    db: &Inventory,
+    items: Vec<Item>,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let paths = db.location_paths()?;
+    let rows: Vec<Vec<String>> = items
+        .into_iter()
+        .map(|item| {
+            vec![
+                item.id.to_string(),
+                item.name,
+                item.location_id
+                    .map(|id| paths[&id].clone())
+                    .unwrap_or_default(),
+                item.comment.unwrap_or_default().replace('\n', " "),
+            ]
+        })
+        .collect();
+    table(out, &["ID", "NAME", "LOCATION", "COMMENT"], &rows)?;
+    Ok(())
+}
+
+fn locations_table(
+    db: &Inventory,
+    locations: Vec<Location>,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn error::Error>> {
+    let paths = db.location_paths()?;
+    let counts = db.item_counts()?;
+    let rows: Vec<Vec<String>> = locations
+        .into_iter()
+        .map(|location| {
+            vec![
+                location.id.to_string(),
+                location.name,
+                location
+                    .parent_id
+                    .map(|id| paths[&id].clone())
+                    .unwrap_or_default(),
+                counts.get(&location.id).copied().unwrap_or(0).to_string(),
+                location.comment.unwrap_or_default().replace('\n', " "),
+            ]
+        })
+        .collect();
+    table(out, &["ID", "NAME", "PARENT", "ITEMS", "COMMENT"], &rows)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod records_tests {
+    use super::super::{EXIT_NOT_FOUND, EXIT_USAGE, exit_code, run_line};
+    use super::*;
+    use crate::seed::fixtures;
+
+    #[test]
+    fn test_add() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let (result, out) = run_line(&my_inv, "add-location 1 Garage");
+        assert!(result.is_ok());
+        assert_eq!(out, "Added location 1\n");
+        let (result, out) = run_line(&my_inv, "add-item 100 screws -c metric -l 1");
+        assert!(result.is_ok());
+        assert_eq!(out, "Added item 100\n");
+        let item = my_inv.search_item_id(100).unwrap();
+        assert_eq!(item.comment, Some("metric".to_string()));
+        assert_eq!(item.location_id, Some(1));
+
+        //the same checks as the create forms
+        let (result, _) = run_line(&my_inv, "add-item 100 nails");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "CLI Error: Item ID already exists"
+        );
+        let (result, _) = run_line(&my_inv, "add-location 2 Shelf --parent 5");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "CLI Error: Location ID does not exist: 5"
+        );
+        let (result, _) = run_line(&my_inv, "add-item one nails");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+        assert!(run_line(&my_inv, "add-item 101").0.is_err());
+        assert!(run_line(&my_inv, "add-item 101 nails @1").0.is_ok());
+        assert_eq!(my_inv.search_item_id(101).unwrap().location_id, Some(1));
+        //@ID and --location together are ambiguous
+        let (result, _) = run_line(&my_inv, "add-item 102 nails @1 --location 1");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+        assert!(my_inv.search_item_id(102).is_none());
+        let (result, _) = run_line(&my_inv, "add-location 2 Shelf @1 --parent 1");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+        let (result, _) = run_line(&my_inv, "add-location 2 Shelf 1");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+        assert!(!my_inv.location_exists(2));
+    }
+
+    #[test]
+    fn test_list_find_show() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+
+        let (result, out) = run_line(&my_inv, "list");
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "ID   NAME     LOCATION              COMMENT
+100  item100  Garage › Shelf › Bin
+101  item101  Garage › Shelf › Bin
+102  item102  Garage
+103  item103
+"
+        );
+        let (result, out) = run_line(&my_inv, "list locations");
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "ID  NAME    PARENT          ITEMS  COMMENT
+1   Garage                  1
+2   Shelf   Garage          0
+3   Bin     Garage › Shelf  2
+4   Attic                   0
+"
+        );
+        assert!(run_line(&my_inv, "list tags").0.is_err());
+
+        let (result, out) = run_line(&my_inv, "find 102");
+        assert!(result.is_ok());
+        assert_eq!(out.lines().count(), 2);
+        let (result, out) = run_line(&my_inv, "find Attic");
+        assert!(result.is_ok());
+        assert!(out.starts_with("ID  NAME   PARENT"));
+        assert!(run_line(&my_inv, "find nothing").0.is_err());
+
+        let (result, out) = run_line(&my_inv, "show 100");
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "Item 100\nname: item100\ncomment: \nlocation: Garage › Shelf › Bin (3)\ntags: \n"
+        );
+        let (result, out) = run_line(&my_inv, "show location 3");
+        assert!(result.is_ok());
+        assert!(out.ends_with("parent: Garage › Shelf (2)\nitems: 100 item100, 101 item101\n"));
+        let (result, _) = run_line(&my_inv, "show location 9");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_NOT_FOUND);
+        let (result, _) = run_line(&my_inv, "show tag 1");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+    }
+
+    #[test]
+    fn test_move_edit_delete() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+
+        let (result, out) = run_line(&my_inv, "move 103 4");
+        assert!(result.is_ok());
+        assert_eq!(out, "Moved item 103 to Attic\n");
+        assert!(run_line(&my_inv, "move 103 none").0.is_ok());
+        assert_eq!(my_inv.search_item_id(103).unwrap().location_id, None);
+        assert!(run_line(&my_inv, "move 103 9").0.is_err());
+        let (result, _) = run_line(&my_inv, "move 109 4");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_NOT_FOUND);
+
+        //only the fields given change
+        assert!(run_line(&my_inv, "edit 100 --name bolts").0.is_ok());
+        let item = my_inv.search_item_id(100).unwrap();
+        assert_eq!(item.name, "bolts");
+        assert_eq!(item.location_id, Some(3));
+        assert!(
+            run_line(&my_inv, "edit location 2 -c top -p none")
+                .0
+                .is_ok()
+        );
+        let location = my_inv.search_location_id(2).unwrap();
+        assert_eq!(location.comment, Some("top".to_string()));
+        assert_eq!(location.parent_id, None);
+        //a location can not be moved inside itself
+        assert!(run_line(&my_inv, "edit location 2 --parent 3").0.is_err());
+        assert!(run_line(&my_inv, "edit 100").0.is_err());
+        assert!(run_line(&my_inv, "edit 100 --parent 1").0.is_err());
+
+        assert!(run_line(&my_inv, "delete 100").0.is_ok());
+        assert!(!my_inv.item_exists(100));
+        let (result, _) = run_line(&my_inv, "delete 100");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_NOT_FOUND);
+        //locations have to be emptied first
+        assert!(run_line(&my_inv, "delete location 3").0.is_err());
+        assert!(run_line(&my_inv, "delete location 4").0.is_ok());
+        assert!(!my_inv.location_exists(4));
+    }
+
+    #[test]
+    fn test_json() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+
+        let (result, out) = run_line(&my_inv, "--format jsonl list items --location 3");
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            r#"{"id":100,"name":"item100","comment":null,"location_id":3}
+{"id":101,"name":"item101","comment":null,"location_id":3}
+"#
+        );
+        let (result, out) = run_line(&my_inv, "--format json list locations");
+        assert!(result.is_ok());
+        let locations: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(locations.as_array().unwrap().len(), 4);
+        assert_eq!(locations[2]["parent_id"], 2);
+
+        //find tags each record with its kind
+        let (result, out) = run_line(&my_inv, "--format jsonl find 10");
+        assert!(result.is_ok());
+        assert_eq!(out.lines().count(), 4);
+        assert!(out.starts_with(r#"{"kind":"item","id":100,"#));
+        let (result, out) = run_line(&my_inv, "--format json find Bin");
+        assert!(result.is_ok());
+        let found: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(found[0]["kind"], "location");
+        assert_eq!(found[0]["parent_id"], 2);
+        let (result, out) = run_line(&my_inv, "--format json find nowhere");
+        assert!(result.is_ok());
+        assert_eq!(out, "[]\n");
+        let (result, out) = run_line(&my_inv, "--format jsonl find nowhere");
+        assert!(result.is_ok());
+        assert!(out.is_empty());
+
+        let (result, out) = run_line(&my_inv, "--format jsonl show location 4");
+        assert!(result.is_ok());
+        assert_eq!(
+            out,
+            "{\"id\":4,\"name\":\"Attic\",\"comment\":null,\"parent_id\":null}\n"
+        );
+        let (result, out) = run_line(&my_inv, "--format json show 102");
+        assert!(result.is_ok());
+        assert!(out.starts_with("{\n  \"id\": 102,"));
+
+        let (result, _) = run_line(&my_inv, "list items --location 9");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_NOT_FOUND);
+        let (result, _) = run_line(&my_inv, "list locations --location 1");
+        assert_eq!(exit_code(result.unwrap_err().as_ref()), EXIT_USAGE);
+    }
+}
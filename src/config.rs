@@ -0,0 +1,204 @@
+use crate::keymap::Action;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// User settings read from `config.toml`. Every section is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeConfig,
+    pub keys: KeysConfig,
+    pub data: DataConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// One of the built in palettes: dark, light, high-contrast or no-color.
+    pub name: String,
+    /// Foreground color overrides keyed by style name, e.g. `focus = "blue"`.
+    pub colors: HashMap<String, String>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            name: "dark".to_string(),
+            colors: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    /// Either `default` or `vim`.
+    pub preset: String,
+    /// Replaces the preset's keys for an action, e.g. `quit = ["q", "Ctrl+c"]`.
+    pub bindings: HashMap<Action, Vec<String>>,
+}
+
+impl Default for KeysConfig {
+    fn default() -> Self {
+        Self {
+            preset: "default".to_string(),
+            bindings: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DataConfig {
+    /// The database file to keep the inventory in. Without one it only lasts until the program
+    /// exits.
+    pub database: Option<PathBuf>,
+    /// Seed files loaded into the inventory every time the program starts, in order.
+    pub seeds: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    error_text: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Config Error: {}", self.error_text)
+    }
+}
+
+impl error::Error for ConfigError {}
+
+impl ConfigError {
+    pub fn new(msg: &str) -> Box<ConfigError> {
+        Box::new(ConfigError {
+            error_text: msg.to_string(),
+        })
+    }
+}
+
+impl Config {
+    /// The config file is `$INVENTORY_CONFIG` if set, otherwise `inventory/config.toml` in the
+    /// XDG config directory.
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("INVENTORY_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("inventory").join("config.toml"))
+    }
+
+    /// Reads the config file, or returns `None` if there isn't one.
+    fn read() -> Result<Option<String>, Box<ConfigError>> {
+        match Self::path() {
+            Some(path) if path.exists() => fs::read_to_string(&path)
+                .map(Some)
+                .map_err(|e| ConfigError::new(&format!("Failed to read {}: {e}", path.display()))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Loads the config file, falling back to the defaults if there isn't one.
+    pub fn load() -> Result<Self, Box<ConfigError>> {
+        match Self::read()? {
+            Some(text) => Self::parse(&text),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads only the `[data]` section. The command line has no use for the theme or keys, so a
+    /// mistake in them should not stop it.
+    pub fn load_data() -> Result<DataConfig, Box<ConfigError>> {
+        match Self::read()? {
+            Some(text) => Self::parse_data(&text),
+            None => Ok(DataConfig::default()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Box<ConfigError>> {
+        toml::from_str(text).map_err(|e| ConfigError::new(&e.to_string()))
+    }
+
+    pub fn parse_data(text: &str) -> Result<DataConfig, Box<ConfigError>> {
+        //the other sections are skipped without being checked
+        #[derive(Default, Deserialize)]
+        #[serde(default)]
+        struct DataOnly {
+            data: DataConfig,
+        }
+        toml::from_str::<DataOnly>(text)
+            .map(|config| config.data)
+            .map_err(|e| ConfigError::new(&e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.theme.name, "dark");
+        assert!(config.theme.colors.is_empty());
+        assert_eq!(config.keys.preset, "default");
+        assert!(config.keys.bindings.is_empty());
+        assert!(config.data.database.is_none());
+        assert!(config.data.seeds.is_empty());
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r#"
+            [theme]
+            name = "light"
+
+            [theme.colors]
+            focus = "blue"
+
+            [keys]
+            preset = "vim"
+
+            [keys.bindings]
+            next_match = ["Ctrl+n"]
+
+            [data]
+            database = "stock.db"
+            seeds = ["fixtures/demo.toml"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.theme.name, "light");
+        assert_eq!(config.theme.colors.get("focus"), Some(&"blue".to_string()));
+
+        assert_eq!(config.keys.preset, "vim");
+        assert_eq!(
+            config.keys.bindings.get(&Action::NextMatch),
+            Some(&vec!["Ctrl+n".to_string()])
+        );
+        assert_eq!(config.data.database, Some(PathBuf::from("stock.db")));
+        assert_eq!(config.data.seeds, vec![PathBuf::from("fixtures/demo.toml")]);
+
+        assert!(Config::parse("[theme]\nname = 3").is_err());
+        assert!(Config::parse("[keys.bindings]\nfly = [\"f\"]").is_err());
+    }
+
+    #[test]
+    fn test_parse_data() {
+        //a bad key binding only matters to the interface
+        let data =
+            Config::parse_data("[keys.bindings]\nfly = [\"f\"]\n[data]\ndatabase = \"stock.db\"")
+                .unwrap();
+        assert_eq!(data.database, Some(PathBuf::from("stock.db")));
+        assert!(Config::parse_data("").unwrap().seeds.is_empty());
+        assert!(Config::parse_data("[data]\nseeds = 3").is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::db::inventory::{Inventory, Item, Location};
+use std::borrow::Cow;
+use std::error;
+use std::fmt;
+
+/// Which table an export covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Records {
+    Items,
+    Locations,
+}
+
+#[derive(Debug)]
+pub struct CsvError {
+    error_text: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CSV Error: {}", self.error_text)
+    }
+}
+
+impl error::Error for CsvError {}
+
+impl CsvError {
+    pub fn new(msg: &str) -> Box<Self> {
+        Box::new(CsvError {
+            error_text: msg.to_string(),
+        })
+    }
+}
+
+impl Records {
+    /// Parses `items` or `locations`.
+    pub fn parse(text: &str) -> Result<Self, Box<CsvError>> {
+        match text.trim().to_lowercase().as_str() {
+            "items" => Ok(Records::Items),
+            "locations" => Ok(Records::Locations),
+            _ => Err(CsvError::new(&format!(
+                "Unknown records '{text}', expected items or locations"
+            ))),
+        }
+    }
+
+    /// "1 item" or "N items".
+    pub fn count(self, count: usize) -> String {
+        let name = self.name();
+        if count == 1 {
+            format!("1 {}", &name[..name.len() - 1])
+        } else {
+            format!("{count} {name}")
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Records::Items => "items",
+            Records::Locations => "locations",
+        }
+    }
+}
+
+/// Quotes a field if it holds a comma, quote or line break, doubling any quotes inside.
+pub fn field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// One line of CSV, ended with CRLF as RFC 4180 asks.
+pub fn record(fields: &[&str]) -> String {
+    let fields: Vec<Cow<str>> = fields.iter().map(|f| field(f)).collect();
+    format!("{}\r\n", fields.join(","))
+}
+
+/// Parses CSV text into rows of fields. Quoted fields may hold commas, doubled quotes and line
+/// breaks. Lines may end with CRLF or LF, and blank lines are skipped.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, Box<CsvError>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    //inside a quoted field, and just after the closing quote of one
+    let mut quoted = false;
+    let mut closed = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => {
+                    quoted = false;
+                    closed = true;
+                }
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            ',' => {
+                row.push(std::mem::take(&mut field));
+                closed = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                row.push(std::mem::take(&mut field));
+                if row.len() > 1 || !row[0].is_empty() || closed {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+                closed = false;
+            }
+            '"' if field.is_empty() && !closed => quoted = true,
+            _ if closed => {
+                return Err(CsvError::new(&format!(
+                    "Unexpected text after a closing quote on line {line}"
+                )));
+            }
+            '"' => {
+                return Err(CsvError::new(&format!(
+                    "Unexpected quote on line {line}, quote the whole field instead"
+                )));
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(CsvError::new(&format!(
+            "The quoted field on line {line} is never closed"
+        )));
+    }
+    if !row.is_empty() || !field.is_empty() || closed {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn id_text(id: Option<i64>) -> String {
+    id.map(|id| id.to_string()).unwrap_or_default()
+}
+
+/// `items` as CSV with a header row, sorted by ID. The location column holds the full path of
+/// the item's location, e.g. "Garage › Shelf 2".
+pub fn items_csv(db: &Inventory, items: &[Item]) -> rusqlite::Result<String> {
+    let paths = db.location_paths()?;
+    let mut items: Vec<&Item> = items.iter().collect();
+    items.sort_by_key(|i| i.id);
+    let mut csv = record(&["id", "name", "comment", "location_id", "location"]);
+    for item in items {
+        let location = item.location_id.and_then(|id| paths.get(&id));
+        csv += &record(&[
+            &item.id.to_string(),
+            &item.name,
+            item.comment.as_deref().unwrap_or_default(),
+            &id_text(item.location_id),
+            location.map_or("", String::as_str),
+        ]);
+    }
+    Ok(csv)
+}
+
+/// `locations` as CSV with a header row, sorted by ID. The parent column holds the full path of
+/// the location's parent.
+pub fn locations_csv(db: &Inventory, locations: &[Location]) -> rusqlite::Result<String> {
+    let paths = db.location_paths()?;
+    let mut locations: Vec<&Location> = locations.iter().collect();
+    locations.sort_by_key(|l| l.id);
+    let mut csv = record(&["id", "name", "comment", "parent_id", "parent"]);
+    for location in locations {
+        let parent = location.parent_id.and_then(|id| paths.get(&id));
+        csv += &record(&[
+            &location.id.to_string(),
+            &location.name,
+            location.comment.as_deref().unwrap_or_default(),
+            &id_text(location.parent_id),
+            parent.map_or("", String::as_str),
+        ]);
+    }
+    Ok(csv)
+}
+
+/// Exports every record of a kind, or only those whose name or comment contains `search`.
+/// Returns the CSV and the number of records in it.
+pub fn export(
+    db: &Inventory,
+    records: Records,
+    search: Option<&str>,
+) -> rusqlite::Result<(String, usize)> {
+    match records {
+        Records::Items => {
+            let items = match search {
+                Some(term) => db.search_items(term)?,
+                None => db.get_all_items()?,
+            };
+            Ok((items_csv(db, &items)?, items.len()))
+        }
+        Records::Locations => {
+            let locations = match search {
+                Some(term) => db.search_locations(term)?,
+                None => db.get_all_locations()?,
+            };
+            Ok((locations_csv(db, &locations)?, locations.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod csv_tests {
+    use super::*;
+    use crate::seed::fixtures;
+
+    #[test]
+    fn test_field() {
+        assert_eq!(field("plain"), "plain");
+        assert_eq!(field(""), "");
+        assert_eq!(field("a,b"), "\"a,b\"");
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(record(&["1", "a,b", ""]), "1,\"a,b\",\r\n");
+    }
+
+    #[test]
+    fn test_parse() {
+        let rows = parse("id,name\r\n1,\"a,\"\"b\"\"\"\n\n2,\"two\nlines\"\n3,").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["id", "name"],
+                vec!["1", "a,\"b\""],
+                vec!["2", "two\nlines"],
+                vec!["3", ""],
+            ]
+        );
+        assert_eq!(parse("").unwrap(), Vec::<Vec<String>>::new());
+        assert!(parse("1,\"open\n").is_err());
+        assert!(parse("1,\"a\"b\n").is_err());
+        assert!(parse("1,a\"b\n").is_err());
+
+        //exported text parses back to the same values
+        let line = record(&["say \"hi\"", "a,b", "two\nlines", ""]);
+        assert_eq!(
+            parse(&line).unwrap(),
+            vec![vec!["say \"hi\"", "a,b", "two\nlines", ""]]
+        );
+    }
+
+    #[test]
+    fn test_items_csv() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::QUOTING);
+        let (csv, count) = export(&my_inv, Records::Items, None).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(
+            csv,
+            "id,name,comment,location_id,location\r\n\
+             100,tape,\"two\nlines\",,\r\n\
+             101,screws,\"M3, \"\"pan\"\" head\",2,\"Garage › Shelf, top\"\r\n\
+             102,glue,,1,Garage\r\n"
+        );
+
+        let (csv, count) = export(&my_inv, Records::Items, Some("scr")).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(csv.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_locations_csv() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::QUOTING);
+        let (csv, count) = export(&my_inv, Records::Locations, None).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            csv,
+            "id,name,comment,parent_id,parent\r\n\
+             1,Garage,,,\r\n\
+             2,\"Shelf, top\",,1,Garage\r\n"
+        );
+        assert_eq!(Records::parse(" Locations ").unwrap(), Records::Locations);
+        assert!(Records::parse("tags").is_err());
+        assert_eq!(Records::Locations.count(1), "1 location");
+        assert_eq!(Records::Items.count(0), "0 items");
+    }
+}
@@ -1,11 +1,18 @@
 pub mod inventory {
     use rusqlite::{Connection, Result};
+    use serde::{Deserialize, Serialize};
+    use std::collections::{HashMap, HashSet};
+    use std::error;
+    use std::fmt;
+    use std::ops::Deref;
+    use std::path::Path;
     #[derive(Debug)]
     pub struct Inventory {
         db: Connection,
     }
 
-    #[derive(Debug, PartialEq)]
+    /// Serialized as is for the CLI's JSON output, so changing the fields changes that format.
+    #[derive(Debug, PartialEq, Serialize)]
     pub struct Item {
         pub id: i64,
         pub name: String,
@@ -13,13 +20,241 @@ pub mod inventory {
         pub location_id: Option<i64>,
     }
 
-    #[derive(Debug, PartialEq)]
+    /// Serialized as is for the CLI's JSON output, so changing the fields changes that format.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
     pub struct Location {
         pub id: i64,
         pub name: String,
         pub comment: Option<String>,
+        /// The location this one is inside of, `None` for the top level.
+        pub parent_id: Option<i64>,
+    }
+
+    /// Totals shown on the dashboard, counted by the database rather than by loading every row.
+    #[derive(Debug, Default, PartialEq)]
+    pub struct Stats {
+        pub items: usize,
+        pub locations: usize,
+        /// Items without a location.
+        pub unassigned_items: usize,
+        /// Locations holding neither items nor other locations.
+        pub empty_locations: usize,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum RecordKind {
+        Item,
+        Location,
+    }
+
+    /// A record that was recently added or edited, as returned by `recent_changes`.
+    #[derive(Debug, PartialEq)]
+    pub struct RecentChange {
+        pub kind: RecordKind,
+        pub id: i64,
+        pub name: String,
+        /// True if the record has not been edited since it was added.
+        pub added: bool,
+        /// Local time of the change, e.g. "2025-01-31 18:04".
+        pub when: String,
+    }
+
+    /// SQL for the current UTC time, stored in the created_at and updated_at columns. Millisecond
+    /// precision keeps changes made in quick succession in order.
+    const NOW: &str = "strftime('%Y-%m-%d %H:%M:%f', 'now')";
+
+    /// A change applied to several items at once by `update_items`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum ItemChange {
+        Move(Option<i64>),
+        SetComment(Option<String>),
+        AddTag(String),
+        RemoveTag(String),
+        Delete,
+    }
+
+    /// The version of the JSON written by `export_json`. Bump it whenever the format changes and
+    /// teach `import_json` to read the older versions.
+    pub const JSON_VERSION: u32 = 1;
+
+    /// The whole inventory as written by `export_json`, with everything the database keeps.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Dump {
+        pub version: u32,
+        pub locations: Vec<LocationRecord>,
+        pub items: Vec<ItemRecord>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct LocationRecord {
+        pub id: i64,
+        pub name: String,
+        pub comment: Option<String>,
+        pub parent_id: Option<i64>,
+        pub created_at: Option<String>,
+        pub updated_at: Option<String>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ItemRecord {
+        pub id: i64,
+        pub name: String,
+        pub comment: Option<String>,
+        pub location_id: Option<i64>,
+        #[serde(default)]
+        pub tags: Vec<String>,
+        pub created_at: Option<String>,
+        pub updated_at: Option<String>,
+    }
+
+    /// What `import_json` does with a record whose ID is already taken.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum OnConflict {
+        /// Import nothing.
+        Fail,
+        /// Keep the record in the database and leave out the one from the dump.
+        Skip,
+        /// Overwrite the record in the database with the one from the dump.
+        Replace,
+        /// Give the record from the dump an unused ID, updating everything that refers to it.
+        Renumber,
+    }
+
+    /// How many records `import_json` added, skipped, replaced and renumbered, counting items and
+    /// locations together.
+    #[derive(Debug, Default, PartialEq)]
+    pub struct JsonSummary {
+        pub added: usize,
+        pub skipped: usize,
+        pub replaced: usize,
+        pub renumbered: usize,
+    }
+
+    #[derive(Debug)]
+    pub struct JsonError {
+        error_text: String,
+    }
+
+    impl fmt::Display for JsonError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "JSON Error: {}", self.error_text)
+        }
+    }
+
+    impl error::Error for JsonError {}
+
+    impl JsonError {
+        pub fn new(msg: &str) -> Box<Self> {
+            Box::new(JsonError {
+                error_text: msg.to_string(),
+            })
+        }
+    }
+
+    /// Changes that are kept only if `commit` is called, and rolled back when dropped otherwise.
+    /// Unlike a transaction, savepoints can be opened inside each other, so a batch of changes
+    /// can be undone as a whole even though some of them use a savepoint of their own.
+    pub struct Savepoint<'a> {
+        db: &'a Connection,
+        /// Whether it was opened inside a transaction, which holds back deferred foreign key
+        /// checks until the transaction ends.
+        nested: bool,
+        committed: bool,
+    }
+
+    impl Savepoint<'_> {
+        /// Keeps the changes. If that fails, e.g. on a deferred foreign key, they are rolled back.
+        pub fn commit(mut self) -> Result<()> {
+            if self.nested {
+                let violations: i64 = self.db.query_row(
+                    "SELECT COUNT(*) FROM pragma_foreign_key_check",
+                    [],
+                    |row| row.get(0),
+                )?;
+                if violations > 0 {
+                    return Err(rusqlite::Error::SqliteFailure(
+                        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT_FOREIGNKEY),
+                        Some("FOREIGN KEY constraint failed".to_string()),
+                    ));
+                }
+            }
+            self.db.execute_batch("RELEASE inventory")?;
+            self.committed = true;
+            Ok(())
+        }
+    }
+
+    impl Deref for Savepoint<'_> {
+        type Target = Connection;
+
+        fn deref(&self) -> &Connection {
+            self.db
+        }
+    }
+
+    impl Drop for Savepoint<'_> {
+        fn drop(&mut self) {
+            if !self.committed {
+                let _ = self
+                    .db
+                    .execute_batch("ROLLBACK TO inventory; RELEASE inventory");
+            }
+        }
+    }
+
+    /// The ID each record from a dump gets, or `None` if it is skipped.
+    fn plan_ids(
+        kind: &str,
+        ids: impl Iterator<Item = i64>,
+        existing: &HashSet<i64>,
+        on_conflict: OnConflict,
+        next_id: &mut i64,
+        summary: &mut JsonSummary,
+    ) -> std::result::Result<HashMap<i64, Option<i64>>, Box<JsonError>> {
+        let mut planned = HashMap::new();
+        for id in ids {
+            let new_id = match on_conflict {
+                _ if !existing.contains(&id) => {
+                    summary.added += 1;
+                    Some(id)
+                }
+                OnConflict::Fail => {
+                    return Err(JsonError::new(&format!("{kind} {id} already exists")));
+                }
+                OnConflict::Skip => {
+                    summary.skipped += 1;
+                    None
+                }
+                OnConflict::Replace => {
+                    summary.replaced += 1;
+                    Some(id)
+                }
+                OnConflict::Renumber => {
+                    summary.renumbered += 1;
+                    *next_id += 1;
+                    Some(*next_id - 1)
+                }
+            };
+            if planned.insert(id, new_id).is_some() {
+                return Err(JsonError::new(&format!(
+                    "{kind} {id} appears more than once in the dump"
+                )));
+            }
+        }
+        Ok(planned)
     }
     impl Inventory {
+        /// Opens a savepoint, which on its own works like a transaction.
+        pub fn savepoint(&self) -> Result<Savepoint<'_>> {
+            let nested = !self.db.is_autocommit();
+            self.db.execute_batch("SAVEPOINT inventory")?;
+            Ok(Savepoint {
+                db: &self.db,
+                nested,
+                committed: false,
+            })
+        }
+
         pub fn open_in_memory() -> Result<Self> {
             let db = Connection::open_in_memory()?;
             let inv = Inventory { db };
@@ -28,7 +263,7 @@ pub mod inventory {
             Ok(inv)
         }
 
-        pub fn open_in_file(filename: &str) -> Result<Self> {
+        pub fn open_in_file(filename: impl AsRef<Path>) -> Result<Self> {
             let db = Connection::open(filename)?;
             let inv = Inventory { db };
             inv.init()?;
@@ -41,7 +276,10 @@ pub mod inventory {
                 "CREATE TABLE IF NOT EXISTS locations (
                     id INTEGER PRIMARY KEY,
                     name TEXT,
-                    comment TEXT)",
+                    comment TEXT,
+                    parent_id INTEGER REFERENCES locations(id),
+                    created_at TEXT,
+                    updated_at TEXT )",
                 (),
             )?;
 
@@ -50,9 +288,40 @@ pub mod inventory {
                     id INTEGER PRIMARY KEY,
                     name TEXT,
                     comment TEXT,
-                    location_id INTEGER REFERENCES locations(id) )",
+                    location_id INTEGER REFERENCES locations(id),
+                    created_at TEXT,
+                    updated_at TEXT )",
                 (),
             )?;
+            //databases made by older versions lack the columns added since
+            self.add_missing_column("locations", "parent_id", "INTEGER REFERENCES locations(id)")?;
+            for table in ["locations", "items"] {
+                self.add_missing_column(table, "created_at", "TEXT")?;
+                self.add_missing_column(table, "updated_at", "TEXT")?;
+            }
+
+            self.db.execute(
+                "CREATE TABLE IF NOT EXISTS item_tags (
+                    item_id INTEGER REFERENCES items(id) ON DELETE CASCADE,
+                    tag TEXT,
+                    PRIMARY KEY (item_id, tag) )",
+                (),
+            )?;
+            Ok(())
+        }
+
+        fn add_missing_column(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+            let exists: bool = self.db.query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+                (table, column),
+                |row| row.get(0),
+            )?;
+            if !exists {
+                self.db.execute(
+                    &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+                    (),
+                )?;
+            }
             Ok(())
         }
 
@@ -72,12 +341,15 @@ pub mod inventory {
         }
 
         pub fn get_all_locations(&self) -> Result<Vec<Location>> {
-            let mut stmt = self.db.prepare("SELECT id, name, comment FROM locations")?;
+            let mut stmt = self
+                .db
+                .prepare("SELECT id, name, comment, parent_id FROM locations")?;
             let loc_iter = stmt.query_map([], |row| {
                 Ok(Location {
                     id: row.get(0)?,
                     name: row.get(1)?,
                     comment: row.get(2)?,
+                    parent_id: row.get(3)?,
                 })
             })?;
             Ok(loc_iter.map(|loc| loc.unwrap()).collect())
@@ -85,25 +357,23 @@ pub mod inventory {
 
         pub fn add_item(&self, i: &Item) -> Result<()> {
             self.db.execute(
-                "INSERT INTO items (id, name, comment, location_id) VALUES (?1, ?2, ?3, ?4)",
+                &format!(
+                    "INSERT INTO items (id, name, comment, location_id, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, {NOW}, {NOW})"
+                ),
                 (&i.id, &i.name.as_str(), &i.comment, &i.location_id),
             )?;
             Ok(())
         }
 
         pub fn add_location(&self, l: &Location) -> Result<()> {
-            if l.comment.is_some() {
-                self.db.execute(
-                    "INSERT INTO locations (id, name, comment) VALUES (?1, ?2, ?3)",
-                    (l.id, l.name.as_str(), l.comment.as_ref().unwrap().as_str()),
-                )?;
-            } else {
-                self.db.execute(
-                    "INSERT INTO locations (id, name) VALUES (?1, ?2)",
-                    (l.id, l.name.as_str()),
-                )?;
-            }
-
+            self.db.execute(
+                &format!(
+                    "INSERT INTO locations (id, name, comment, parent_id, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, {NOW}, {NOW})"
+                ),
+                (l.id, l.name.as_str(), &l.comment, &l.parent_id),
+            )?;
             Ok(())
         }
 
@@ -124,7 +394,7 @@ pub mod inventory {
 
         pub fn search_locations(&self, search_term: &str) -> Result<Vec<Location>> {
             let mut stmt = self.db.prepare(
-                "SELECT id, name, comment FROM locations WHERE name LIKE ?1 OR comment LIKE ?2",
+                "SELECT id, name, comment, parent_id FROM locations WHERE name LIKE ?1 OR comment LIKE ?2",
             )?;
             let loc_iter = stmt.query_map(
                 [format!("%{}%", search_term), format!("%{}%", search_term)],
@@ -133,6 +403,7 @@ pub mod inventory {
                         id: row.get(0)?,
                         name: row.get(1)?,
                         comment: row.get(2)?,
+                        parent_id: row.get(3)?,
                     })
                 },
             )?;
@@ -162,34 +433,31 @@ pub mod inventory {
                 .db
                 .prepare("SELECT id, name, comment, location_id FROM items WHERE id = ?1")
                 .ok()?;
-            match stmt.query_row([id], |row| {
+            stmt.query_row([id], |row| {
                 Ok(Item {
                     id: row.get(0)?,
                     name: row.get(1)?,
                     comment: row.get(2)?,
                     location_id: row.get(3)?,
                 })
-            }) {
-                Ok(item) => Some(item),
-                Err(_) => None,
-            }
+            })
+            .ok()
         }
 
         pub fn search_location_id(&self, id: i64) -> Option<Location> {
             let mut stmt = self
                 .db
-                .prepare("SELECT id, name, comment FROM locations WHERE id = ?1")
+                .prepare("SELECT id, name, comment, parent_id FROM locations WHERE id = ?1")
                 .ok()?;
-            match stmt.query_row([id], |row| {
+            stmt.query_row([id], |row| {
                 Ok(Location {
                     id: row.get(0)?,
                     name: row.get(1)?,
                     comment: row.get(2)?,
+                    parent_id: row.get(3)?,
                 })
-            }) {
-                Ok(location) => Some(location),
-                Err(_) => None,
-            }
+            })
+            .ok()
         }
 
         pub fn item_exists(&self, id: i64) -> bool {
@@ -209,7 +477,10 @@ pub mod inventory {
                 return Err(rusqlite::Error::QueryReturnedNoRows);
             }
             self.db.execute(
-                "UPDATE items SET name = ?1, comment = ?2, location_id = ?3 WHERE id = ?4",
+                &format!(
+                    "UPDATE items SET name = ?1, comment = ?2, location_id = ?3, updated_at = {NOW}
+                    WHERE id = ?4"
+                ),
                 (
                     &new_item.name,
                     &new_item.comment,
@@ -224,19 +495,462 @@ pub mod inventory {
             if !self.location_exists(new_location.id) {
                 return Err(rusqlite::Error::QueryReturnedNoRows);
             }
+            self.check_parent(new_location.id, new_location.parent_id)?;
+            self.db.execute(
+                &format!(
+                    "UPDATE locations SET name = ?1, comment=?2, parent_id = ?3, updated_at = {NOW}
+                    WHERE id = ?4"
+                ),
+                (
+                    &new_location.name,
+                    &new_location.comment,
+                    &new_location.parent_id,
+                    &new_location.id,
+                ),
+            )?;
+            Ok(())
+        }
+
+        /// Deletes a location, refusing if it still holds items or other locations.
+        pub fn delete_location(&self, id: i64) -> Result<()> {
+            if !self.location_exists(id) {
+                return Err(rusqlite::Error::QueryReturnedNoRows);
+            }
+            let (items, locations): (i64, i64) = self.db.query_row(
+                "SELECT (SELECT COUNT(*) FROM items WHERE location_id = ?1),
+                    (SELECT COUNT(*) FROM locations WHERE parent_id = ?1)",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+            if items + locations > 0 {
+                return Err(rusqlite::Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some(format!(
+                        "Location {id} still holds {items} items and {locations} locations"
+                    )),
+                ));
+            }
+            self.db
+                .execute("DELETE FROM locations WHERE id = ?1", [id])?;
+            Ok(())
+        }
+
+        /// Moves a location inside `parent_id`, or to the top level for `None`.
+        pub fn set_location_parent(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+            if !self.location_exists(id) {
+                return Err(rusqlite::Error::QueryReturnedNoRows);
+            }
+            self.check_parent(id, parent_id)?;
             self.db.execute(
-                "UPDATE locations SET name = ?1, comment=?2 WHERE id = ?3",
-                (&new_location.name, &new_location.comment, &new_location.id),
+                &format!("UPDATE locations SET parent_id = ?1, updated_at = {NOW} WHERE id = ?2"),
+                (parent_id, id),
+            )?;
+            Ok(())
+        }
+
+        /// Fails if putting location `id` inside `parent_id` would make it its own ancestor.
+        fn check_parent(&self, id: i64, parent_id: Option<i64>) -> Result<()> {
+            let mut ancestor = parent_id;
+            let mut seen = HashSet::new();
+            while let Some(ancestor_id) = ancestor {
+                if ancestor_id == id || !seen.insert(ancestor_id) {
+                    return Err(rusqlite::Error::SqliteFailure(
+                        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                        Some("A location can not be inside itself".to_string()),
+                    ));
+                }
+                ancestor = self
+                    .search_location_id(ancestor_id)
+                    .and_then(|l| l.parent_id);
+            }
+            Ok(())
+        }
+
+        /// The number of items directly in each location. Locations without items are left out.
+        pub fn item_counts(&self) -> Result<HashMap<i64, usize>> {
+            let mut stmt = self.db.prepare(
+                "SELECT location_id, COUNT(*) FROM items WHERE location_id IS NOT NULL GROUP BY location_id",
+            )?;
+            let count_iter =
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?;
+            count_iter.collect()
+        }
+
+        /// The full path of every location, e.g. "Garage › Shelf 2", keyed by location ID.
+        pub fn location_paths(&self) -> Result<HashMap<i64, String>> {
+            let locations: HashMap<i64, Location> = self
+                .get_all_locations()?
+                .into_iter()
+                .map(|l| (l.id, l))
+                .collect();
+            let mut paths = HashMap::new();
+            for location in locations.values() {
+                let mut names = vec![location.name.as_str()];
+                let mut parent = location.parent_id;
+                while let Some(parent_location) = parent.and_then(|id| locations.get(&id)) {
+                    //stop on a cycle, in case one was written outside of this program
+                    if names.len() > locations.len() {
+                        break;
+                    }
+                    names.push(parent_location.name.as_str());
+                    parent = parent_location.parent_id;
+                }
+                names.reverse();
+                paths.insert(location.id, names.join(" › "));
+            }
+            Ok(paths)
+        }
+
+        /// Every item's tags, keyed by item ID. Items without tags are left out.
+        pub fn all_item_tags(&self) -> Result<HashMap<i64, Vec<String>>> {
+            let mut stmt = self
+                .db
+                .prepare("SELECT item_id, tag FROM item_tags ORDER BY item_id, tag")?;
+            let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+            for row in stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))? {
+                let (item_id, tag) = row?;
+                tags.entry(item_id).or_default().push(tag);
+            }
+            Ok(tags)
+        }
+
+        pub fn tags_for_item(&self, item_id: i64) -> Result<Vec<String>> {
+            let mut stmt = self
+                .db
+                .prepare("SELECT tag FROM item_tags WHERE item_id = ?1 ORDER BY tag")?;
+            let tag_iter = stmt.query_map([item_id], |row| row.get(0))?;
+            tag_iter.collect()
+        }
+
+        /// Applies `change` to every item in `ids` in a single transaction, so either all of them
+        /// change or none do. Returns the number of rows changed.
+        pub fn update_items(&self, ids: &[i64], change: &ItemChange) -> Result<usize> {
+            let tx = self.savepoint()?;
+            let mut changed = 0;
+            for id in ids {
+                if !self.item_exists(*id) {
+                    return Err(rusqlite::Error::QueryReturnedNoRows);
+                }
+                let rows = match change {
+                    ItemChange::Move(location_id) => tx.execute(
+                        "UPDATE items SET location_id = ?1 WHERE id = ?2",
+                        (location_id, id),
+                    )?,
+                    ItemChange::SetComment(comment) => {
+                        tx.execute("UPDATE items SET comment = ?1 WHERE id = ?2", (comment, id))?
+                    }
+                    ItemChange::AddTag(tag) => tx.execute(
+                        "INSERT OR IGNORE INTO item_tags (item_id, tag) VALUES (?1, ?2)",
+                        (id, tag),
+                    )?,
+                    ItemChange::RemoveTag(tag) => tx.execute(
+                        "DELETE FROM item_tags WHERE item_id = ?1 AND tag = ?2",
+                        (id, tag),
+                    )?,
+                    ItemChange::Delete => tx.execute("DELETE FROM items WHERE id = ?1", [id])?,
+                };
+                changed += rows;
+                if rows > 0 && *change != ItemChange::Delete {
+                    tx.execute(
+                        &format!("UPDATE items SET updated_at = {NOW} WHERE id = ?1"),
+                        [id],
+                    )?;
+                }
+            }
+            tx.commit()?;
+            Ok(changed)
+        }
+
+        /// Adds `locations` and then `items` in a single transaction, so either all of them are
+        /// added or none are. Foreign keys are only checked at the end, letting a location come
+        /// before the parent it is inside of.
+        pub fn add_records(&self, locations: &[Location], items: &[Item]) -> Result<()> {
+            let tx = self.savepoint()?;
+            tx.execute("PRAGMA defer_foreign_keys = ON", ())?;
+            for location in locations {
+                self.add_location(location)?;
+            }
+            for item in items {
+                self.add_item(item)?;
+            }
+            tx.commit()
+        }
+
+        pub fn stats(&self) -> Result<Stats> {
+            self.db.query_row(
+                "SELECT
+                    (SELECT COUNT(*) FROM items),
+                    (SELECT COUNT(*) FROM locations),
+                    (SELECT COUNT(*) FROM items WHERE location_id IS NULL),
+                    (SELECT COUNT(*) FROM locations l
+                        WHERE NOT EXISTS (SELECT 1 FROM items i WHERE i.location_id = l.id)
+                        AND NOT EXISTS (SELECT 1 FROM locations c WHERE c.parent_id = l.id))",
+                (),
+                |row| {
+                    Ok(Stats {
+                        items: row.get::<_, i64>(0)? as usize,
+                        locations: row.get::<_, i64>(1)? as usize,
+                        unassigned_items: row.get::<_, i64>(2)? as usize,
+                        empty_locations: row.get::<_, i64>(3)? as usize,
+                    })
+                },
+            )
+        }
+
+        /// Up to `limit` locations holding the most items directly, with their item counts.
+        pub fn largest_locations(&self, limit: usize) -> Result<Vec<(Location, usize)>> {
+            let mut stmt = self.db.prepare(
+                "SELECT l.id, l.name, l.comment, l.parent_id, COUNT(*) AS n
+                FROM locations l JOIN items i ON i.location_id = l.id
+                GROUP BY l.id ORDER BY n DESC, l.name LIMIT ?1",
+            )?;
+            let loc_iter = stmt.query_map([limit as i64], |row| {
+                Ok((
+                    Location {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        comment: row.get(2)?,
+                        parent_id: row.get(3)?,
+                    },
+                    row.get::<_, i64>(4)? as usize,
+                ))
+            })?;
+            loc_iter.collect()
+        }
+
+        /// Up to `limit` items and locations, most recently added or edited first. Records from
+        /// before changes were tracked are left out.
+        pub fn recent_changes(&self, limit: usize) -> Result<Vec<RecentChange>> {
+            let mut stmt = self.db.prepare(
+                "SELECT kind, id, name, created_at = updated_at,
+                    strftime('%Y-%m-%d %H:%M', updated_at, 'localtime')
+                FROM (
+                    SELECT 0 AS kind, id, name, created_at, updated_at FROM items
+                    UNION ALL
+                    SELECT 1, id, name, created_at, updated_at FROM locations
+                )
+                WHERE updated_at IS NOT NULL
+                ORDER BY updated_at DESC, kind, id DESC LIMIT ?1",
+            )?;
+            let change_iter = stmt.query_map([limit as i64], |row| {
+                Ok(RecentChange {
+                    kind: match row.get::<_, i64>(0)? {
+                        0 => RecordKind::Item,
+                        _ => RecordKind::Location,
+                    },
+                    id: row.get(1)?,
+                    name: row.get(2)?,
+                    added: row.get::<_, Option<bool>>(3)?.unwrap_or(false),
+                    when: row.get(4)?,
+                })
+            })?;
+            change_iter.collect()
+        }
+
+        /// The whole inventory as versioned JSON, meant for backups and moving data between
+        /// machines. `import_json` reads it back without losing anything.
+        pub fn export_json(&self) -> std::result::Result<String, Box<dyn error::Error>> {
+            let mut stmt = self.db.prepare(
+                "SELECT id, name, comment, parent_id, created_at, updated_at
+                FROM locations ORDER BY id",
+            )?;
+            let locations = stmt
+                .query_map([], |row| {
+                    Ok(LocationRecord {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        comment: row.get(2)?,
+                        parent_id: row.get(3)?,
+                        created_at: row.get(4)?,
+                        updated_at: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<_>>()?;
+            let mut tags = self.all_item_tags()?;
+            let mut stmt = self.db.prepare(
+                "SELECT id, name, comment, location_id, created_at, updated_at
+                FROM items ORDER BY id",
             )?;
+            let items = stmt
+                .query_map([], |row| {
+                    let id = row.get(0)?;
+                    Ok(ItemRecord {
+                        id,
+                        name: row.get(1)?,
+                        comment: row.get(2)?,
+                        location_id: row.get(3)?,
+                        tags: tags.remove(&id).unwrap_or_default(),
+                        created_at: row.get(4)?,
+                        updated_at: row.get(5)?,
+                    })
+                })?
+                .collect::<Result<_>>()?;
+            let dump = Dump {
+                version: JSON_VERSION,
+                locations,
+                items,
+            };
+            Ok(serde_json::to_string_pretty(&dump)?)
+        }
+
+        /// Restores JSON written by `export_json` into an empty inventory.
+        pub fn restore_json(&self, json: &str) -> std::result::Result<(), Box<dyn error::Error>> {
+            if self.stats()? != Stats::default() {
+                return Err(JsonError::new(
+                    "Restoring needs an empty inventory, merge into this one instead",
+                ));
+            }
+            self.import_json(json, OnConflict::Fail)?;
             Ok(())
         }
+
+        /// Merges JSON written by `export_json` into this inventory, handling records whose ID
+        /// is taken as `on_conflict` says. Runs in a single transaction, so on any error nothing
+        /// is imported.
+        pub fn import_json(
+            &self,
+            json: &str,
+            on_conflict: OnConflict,
+        ) -> std::result::Result<JsonSummary, Box<dyn error::Error>> {
+            let dump: Dump = serde_json::from_str(json)
+                .map_err(|e| JsonError::new(&format!("Not an inventory dump: {e}")))?;
+            if dump.version == 0 || dump.version > JSON_VERSION {
+                return Err(JsonError::new(&format!(
+                    "Dump version {} is not supported, this program reads up to version {JSON_VERSION}",
+                    dump.version
+                )));
+            }
+            self.import_dump(&dump, on_conflict)
+        }
+
+        /// Merges `dump` the same way as `import_json`. Records without timestamps, as in hand
+        /// written files, are stamped with the current time.
+        pub fn import_dump(
+            &self,
+            dump: &Dump,
+            on_conflict: OnConflict,
+        ) -> std::result::Result<JsonSummary, Box<dyn error::Error>> {
+            let mut summary = JsonSummary::default();
+            let existing: HashSet<i64> = self.get_all_locations()?.iter().map(|l| l.id).collect();
+            let mut next_id = existing
+                .iter()
+                .chain(dump.locations.iter().map(|l| &l.id))
+                .max()
+                .map_or(1, |id| id + 1);
+            let location_ids = plan_ids(
+                "Location",
+                dump.locations.iter().map(|l| l.id),
+                &existing,
+                on_conflict,
+                &mut next_id,
+                &mut summary,
+            )?;
+            let existing: HashSet<i64> = self.get_all_items()?.iter().map(|i| i.id).collect();
+            let mut next_id = existing
+                .iter()
+                .chain(dump.items.iter().map(|i| &i.id))
+                .max()
+                .map_or(1, |id| id + 1);
+            let item_ids = plan_ids(
+                "Item",
+                dump.items.iter().map(|i| i.id),
+                &existing,
+                on_conflict,
+                &mut next_id,
+                &mut summary,
+            )?;
+            //references to renumbered locations follow them, others are left as they are
+            let location_id = |id: Option<i64>| {
+                id.map(|id| location_ids.get(&id).copied().flatten().unwrap_or(id))
+            };
+
+            let tx = self.savepoint()?;
+            tx.execute("PRAGMA defer_foreign_keys = ON", ())?;
+            let mut imported = Vec::new();
+            for location in &dump.locations {
+                let Some(id) = location_ids[&location.id] else {
+                    continue;
+                };
+                tx.execute(
+                    &format!(
+                        "INSERT INTO locations (id, name, comment, parent_id, created_at, updated_at)
+                        VALUES (?1, ?2, ?3, ?4, COALESCE(?5, {NOW}), COALESCE(?6, {NOW}))
+                        ON CONFLICT (id) DO UPDATE SET name = excluded.name,
+                            comment = excluded.comment, parent_id = excluded.parent_id,
+                            created_at = excluded.created_at, updated_at = excluded.updated_at"
+                    ),
+                    (
+                        id,
+                        &location.name,
+                        &location.comment,
+                        location_id(location.parent_id),
+                        &location.created_at,
+                        &location.updated_at,
+                    ),
+                )?;
+                imported.push(id);
+            }
+            for id in imported {
+                let parent_id = self.search_location_id(id).and_then(|l| l.parent_id);
+                self.check_parent(id, parent_id)?;
+            }
+            for item in &dump.items {
+                let Some(id) = item_ids[&item.id] else {
+                    continue;
+                };
+                tx.execute(
+                    &format!(
+                        "INSERT INTO items (id, name, comment, location_id, created_at, updated_at)
+                        VALUES (?1, ?2, ?3, ?4, COALESCE(?5, {NOW}), COALESCE(?6, {NOW}))
+                        ON CONFLICT (id) DO UPDATE SET name = excluded.name,
+                            comment = excluded.comment, location_id = excluded.location_id,
+                            created_at = excluded.created_at, updated_at = excluded.updated_at"
+                    ),
+                    (
+                        id,
+                        &item.name,
+                        &item.comment,
+                        location_id(item.location_id),
+                        &item.created_at,
+                        &item.updated_at,
+                    ),
+                )?;
+                tx.execute("DELETE FROM item_tags WHERE item_id = ?1", [id])?;
+                for tag in &item.tags {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO item_tags (item_id, tag) VALUES (?1, ?2)",
+                        (id, tag),
+                    )?;
+                }
+            }
+            tx.commit()?;
+            Ok(summary)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::inventory::*;
+
+    /// Loads one of the TOML files in `fixtures/` the way a seed file is loaded.
+    fn load_fixture(my_inv: &Inventory, fixture: &str) {
+        #[derive(serde::Deserialize)]
+        struct Fixture {
+            locations: Vec<LocationRecord>,
+            items: Vec<ItemRecord>,
+        }
+        let fixture: Fixture = toml::from_str(fixture).unwrap();
+        let dump = Dump {
+            version: JSON_VERSION,
+            locations: fixture.locations,
+            items: fixture.items,
+        };
+        assert!(my_inv.import_dump(&dump, OnConflict::Fail).is_ok());
+    }
+
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_db_creation() {
         let _my_inv: Inventory = Inventory::open_in_memory().unwrap();
         assert!(true);
@@ -248,11 +962,13 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let l2 = Location {
             id: 102,
             name: "location2".to_string(),
             comment: Some("with comment".to_string()),
+            parent_id: None,
         };
         assert!(my_inv.add_location(&l1).is_ok());
         assert!(my_inv.add_location(&l2).is_ok());
@@ -272,6 +988,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: Some("comment".to_string()),
+            parent_id: None,
         };
         let i1 = Item {
             id: 201,
@@ -304,11 +1021,13 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let l2 = Location {
             id: 101,
             name: "location2".to_string(),
             comment: Some("with comment".to_string()),
+            parent_id: None,
         };
         assert!(my_inv.add_location(&l1).is_ok());
         assert!(my_inv.add_location(&l2).is_err())
@@ -321,6 +1040,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let i1 = Item {
             id: 201,
@@ -353,6 +1073,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let i1 = Item {
             id: 201,
@@ -392,11 +1113,13 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let l2 = Location {
             id: 102,
             name: "Location2".to_string(),
             comment: Some("with comment".to_string()),
+            parent_id: None,
         };
         assert!(my_inv.add_location(&l1).is_ok());
         assert!(my_inv.add_location(&l2).is_ok());
@@ -427,6 +1150,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let i1 = Item {
             id: 201,
@@ -478,6 +1202,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         let i1 = Item {
             id: 201,
@@ -499,6 +1224,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: None,
+            parent_id: None,
         };
         assert!(my_inv.add_location(&l1).is_ok());
 
@@ -513,6 +1239,7 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: Some("comment".to_string()),
+            parent_id: None,
         };
         let mut i1 = Item {
             id: 201,
@@ -558,11 +1285,13 @@ mod tests {
             id: 101,
             name: "location1".to_string(),
             comment: Some("comment".to_string()),
+            parent_id: None,
         };
         let l2 = Location {
             id: 102,
             name: "Location2".to_string(),
             comment: Some("with comment".to_string()),
+            parent_id: None,
         };
 
         assert!(my_inv.add_location(&l1).is_ok());
@@ -577,4 +1306,389 @@ mod tests {
 
         assert!(my_inv.edit_location(&l2).is_err());
     }
+
+    #[test]
+    fn test_update_items() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        load_fixture(&my_inv, include_str!("../fixtures/numbered.toml"));
+
+        let changed = my_inv.update_items(&[100, 101], &ItemChange::Move(Some(2)));
+        assert_eq!(changed, Ok(2));
+        assert_eq!(my_inv.items_by_location_id(2).unwrap().len(), 3);
+
+        let comment = ItemChange::SetComment(Some("bulk".to_string()));
+        assert!(my_inv.update_items(&[101, 102], &comment).is_ok());
+        assert_eq!(
+            my_inv.search_item_id(102).unwrap().comment,
+            Some("bulk".to_string())
+        );
+
+        let tag = ItemChange::AddTag("fragile".to_string());
+        assert!(my_inv.update_items(&[100, 102], &tag).is_ok());
+        assert!(my_inv.update_items(&[100], &tag).is_ok());
+        assert_eq!(my_inv.tags_for_item(100), Ok(vec!["fragile".to_string()]));
+        let all_tags = my_inv.all_item_tags().unwrap();
+        assert_eq!(all_tags.len(), 2);
+        assert_eq!(all_tags[&102], vec!["fragile".to_string()]);
+        let untag = ItemChange::RemoveTag("fragile".to_string());
+        assert_eq!(my_inv.update_items(&[100, 101], &untag), Ok(1));
+        assert!(my_inv.tags_for_item(100).unwrap().is_empty());
+
+        assert_eq!(my_inv.update_items(&[100, 102], &ItemChange::Delete), Ok(2));
+        assert!(!my_inv.item_exists(102));
+        assert!(my_inv.tags_for_item(102).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_items_rolls_back() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let l1 = Location {
+            id: 101,
+            name: "location1".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        assert!(my_inv.add_location(&l1).is_ok());
+        for id in [201, 202] {
+            let item = Item {
+                id,
+                name: format!("item{id}"),
+                comment: None,
+                location_id: Some(101),
+            };
+            assert!(my_inv.add_item(&item).is_ok());
+        }
+
+        //the second item does not exist, so the first one is not deleted either
+        assert!(
+            my_inv
+                .update_items(&[201, 203], &ItemChange::Delete)
+                .is_err()
+        );
+        assert!(my_inv.item_exists(201));
+
+        //moving to a missing location fails on the foreign key
+        assert!(
+            my_inv
+                .update_items(&[201, 202], &ItemChange::Move(Some(999)))
+                .is_err()
+        );
+        assert_eq!(my_inv.items_by_location_id(101).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_add_records() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        //the shelf comes before the garage it is inside of
+        let locations = [
+            Location {
+                id: 2,
+                name: "shelf".to_string(),
+                comment: None,
+                parent_id: Some(1),
+            },
+            Location {
+                id: 1,
+                name: "garage".to_string(),
+                comment: None,
+                parent_id: None,
+            },
+        ];
+        let mut items = vec![Item {
+            id: 100,
+            name: "tape".to_string(),
+            comment: None,
+            location_id: Some(2),
+        }];
+        assert!(my_inv.add_records(&locations, &items).is_ok());
+        assert_eq!(my_inv.stats().unwrap().items, 1);
+
+        //an item in a missing location rolls back everything else
+        items[0].id = 101;
+        items.push(Item {
+            id: 102,
+            name: "glue".to_string(),
+            comment: None,
+            location_id: Some(9),
+        });
+        assert!(my_inv.add_records(&[], &items).is_err());
+        assert!(!my_inv.item_exists(101));
+    }
+
+    #[test]
+    fn test_savepoint() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let item = Item {
+            id: 100,
+            name: "tape".to_string(),
+            comment: None,
+            location_id: None,
+        };
+        assert!(my_inv.add_item(&item).is_ok());
+
+        //changes that open their own savepoint are undone with the outer one
+        let outer = my_inv.savepoint().unwrap();
+        let change = ItemChange::SetComment(Some("blue".to_string()));
+        assert_eq!(my_inv.update_items(&[100], &change).unwrap(), 1);
+        drop(outer);
+        assert_eq!(my_inv.search_item_id(100).unwrap().comment, None);
+
+        let outer = my_inv.savepoint().unwrap();
+        assert!(my_inv.update_items(&[100], &change).is_ok());
+        //a nested savepoint can not leave a dangling reference for the outer one to find
+        let inner = my_inv.savepoint().unwrap();
+        inner
+            .execute_batch("PRAGMA defer_foreign_keys = ON; UPDATE items SET location_id = 9")
+            .unwrap();
+        assert!(inner.commit().is_err());
+        assert!(outer.commit().is_ok());
+        let item = my_inv.search_item_id(100).unwrap();
+        assert_eq!(item.comment, Some("blue".to_string()));
+        assert_eq!(item.location_id, None);
+    }
+
+    #[test]
+    fn test_delete_location() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        for (id, parent_id) in [(1, None), (2, Some(1))] {
+            let loc = Location {
+                id,
+                name: format!("location{id}"),
+                comment: None,
+                parent_id,
+            };
+            assert!(my_inv.add_location(&loc).is_ok());
+        }
+        let item = Item {
+            id: 100,
+            name: "item100".to_string(),
+            comment: None,
+            location_id: Some(2),
+        };
+        assert!(my_inv.add_item(&item).is_ok());
+
+        //locations holding anything are kept
+        assert!(my_inv.delete_location(1).is_err());
+        assert!(my_inv.delete_location(2).is_err());
+        assert!(my_inv.update_items(&[100], &ItemChange::Delete).is_ok());
+        assert!(my_inv.delete_location(2).is_ok());
+        assert!(my_inv.delete_location(1).is_ok());
+        assert!(!my_inv.location_exists(1));
+        assert!(my_inv.delete_location(1).is_err());
+    }
+
+    #[test]
+    fn test_location_tree() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        load_fixture(&my_inv, include_str!("../fixtures/nested.toml"));
+        assert_eq!(my_inv.search_location_id(3).unwrap().parent_id, Some(2));
+        let paths = my_inv.location_paths().unwrap();
+        assert_eq!(paths[&3], "Garage › Shelf › Bin");
+        assert_eq!(paths[&4], "Attic");
+
+        //a location can not end up inside itself
+        assert!(my_inv.set_location_parent(1, Some(3)).is_err());
+        assert!(my_inv.set_location_parent(1, Some(1)).is_err());
+        let mut loc1 = my_inv.search_location_id(1).unwrap();
+        loc1.parent_id = Some(2);
+        assert!(my_inv.edit_location(&loc1).is_err());
+
+        assert!(my_inv.set_location_parent(2, Some(4)).is_ok());
+        assert_eq!(my_inv.location_paths().unwrap()[&3], "Attic › Shelf › Bin");
+        assert!(my_inv.set_location_parent(2, None).is_ok());
+        assert_eq!(my_inv.location_paths().unwrap()[&2], "Shelf");
+        assert!(my_inv.set_location_parent(2, Some(99)).is_err());
+    }
+
+    #[test]
+    fn test_item_counts() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let l1 = Location {
+            id: 101,
+            name: "location1".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        assert!(my_inv.add_location(&l1).is_ok());
+        for (id, location_id) in [(201, Some(101)), (202, Some(101)), (203, None)] {
+            let item = Item {
+                id,
+                name: format!("item{id}"),
+                comment: None,
+                location_id,
+            };
+            assert!(my_inv.add_item(&item).is_ok());
+        }
+        let counts = my_inv.item_counts().unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[&101], 2);
+    }
+
+    #[test]
+    fn test_stats() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        assert_eq!(my_inv.stats().unwrap(), Stats::default());
+        load_fixture(&my_inv, include_str!("../fixtures/nested.toml"));
+        //the garage and shelf hold locations and the garage and bin hold items, so only the
+        //attic is empty
+        assert_eq!(
+            my_inv.stats().unwrap(),
+            Stats {
+                items: 4,
+                locations: 4,
+                unassigned_items: 1,
+                empty_locations: 1,
+            }
+        );
+
+        let largest = my_inv.largest_locations(5).unwrap();
+        let largest: Vec<(i64, usize)> = largest.iter().map(|(l, n)| (l.id, *n)).collect();
+        assert_eq!(largest, vec![(3, 2), (1, 1)]);
+        assert_eq!(my_inv.largest_locations(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recent_changes() {
+        let my_inv: Inventory = Inventory::open_in_memory().unwrap();
+        let loc = Location {
+            id: 101,
+            name: "location1".to_string(),
+            comment: None,
+            parent_id: None,
+        };
+        assert!(my_inv.add_location(&loc).is_ok());
+        for id in [201, 202] {
+            let item = Item {
+                id,
+                name: format!("item{id}"),
+                comment: None,
+                location_id: None,
+            };
+            assert!(my_inv.add_item(&item).is_ok());
+        }
+        let recent = my_inv.recent_changes(10).unwrap();
+        assert_eq!(recent.len(), 3);
+        assert!(recent.iter().all(|c| c.added));
+        assert_eq!(recent[0].when.len(), "2025-01-31 18:04".len());
+
+        //timestamps have millisecond precision, so wait for the edit to sort after the adds
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(
+            my_inv
+                .update_items(&[201], &ItemChange::SetComment(Some("moved".to_string())))
+                .is_ok()
+        );
+        let recent = my_inv.recent_changes(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!((recent[0].kind, recent[0].id), (RecordKind::Item, 201));
+        assert!(!recent[0].added);
+    }
+
+    fn fill_json_db(my_inv: &Inventory) {
+        load_fixture(my_inv, include_str!("../fixtures/quoting.toml"));
+        let tag = ItemChange::AddTag("metric".to_string());
+        assert!(my_inv.update_items(&[100], &tag).is_ok());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fill_json_db(&my_inv);
+        let json = my_inv.export_json().unwrap();
+        let dump: Dump = serde_json::from_str(&json).unwrap();
+        assert_eq!(dump.version, JSON_VERSION);
+        assert_eq!(dump.items[0].tags, vec!["metric"]);
+        assert!(dump.items[0].created_at.is_some());
+
+        let other_inv = Inventory::open_in_memory().unwrap();
+        assert!(other_inv.restore_json(&json).is_ok());
+        assert_eq!(other_inv.export_json().unwrap(), json);
+
+        //restoring needs an empty inventory
+        assert!(other_inv.restore_json(&json).is_err());
+        assert!(my_inv.import_json(&json, OnConflict::Fail).is_err());
+    }
+
+    #[test]
+    fn test_json_version() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        assert!(my_inv.restore_json("not json").is_err());
+        let newer = format!(
+            r#"{{"version": {}, "locations": [], "items": []}}"#,
+            JSON_VERSION + 1
+        );
+        assert!(my_inv.restore_json(&newer).is_err());
+
+        //fields left out of the dump are empty, as in hand written files
+        let json = r#"{"version": 1,
+            "locations": [{"id": 1, "name": "garage"}],
+            "items": [{"id": 5, "name": "tape", "location_id": 1}]}"#;
+        assert!(my_inv.restore_json(json).is_ok());
+        assert_eq!(my_inv.search_item_id(5).unwrap().location_id, Some(1));
+    }
+
+    #[test]
+    fn test_json_merge() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fill_json_db(&my_inv);
+        let json = my_inv.export_json().unwrap();
+        let rename = Item {
+            id: 100,
+            name: "renamed".to_string(),
+            comment: None,
+            location_id: None,
+        };
+        assert!(my_inv.edit_item(&rename).is_ok());
+
+        let summary = my_inv.import_json(&json, OnConflict::Skip).unwrap();
+        assert_eq!(summary.skipped, 5);
+        assert_eq!(my_inv.search_item_id(100).unwrap().name, "renamed");
+
+        let summary = my_inv.import_json(&json, OnConflict::Replace).unwrap();
+        assert_eq!(summary.replaced, 5);
+        assert_eq!(my_inv.search_item_id(100).unwrap().name, "tape");
+        assert_eq!(my_inv.export_json().unwrap(), json);
+
+        //renumbered records keep pointing at each other
+        let summary = my_inv.import_json(&json, OnConflict::Renumber).unwrap();
+        assert_eq!(summary.renumbered, 5);
+        assert_eq!(my_inv.stats().unwrap().items, 6);
+        let copy = my_inv.search_item_id(104).unwrap();
+        assert_eq!(copy.name, "screws");
+        assert_eq!(copy.location_id, Some(4));
+        assert_eq!(my_inv.search_location_id(4).unwrap().parent_id, Some(3));
+        assert_eq!(my_inv.all_item_tags().unwrap()[&103], vec!["metric"]);
+
+        //a location in the dump that does not exist anywhere rolls back the whole merge
+        let json = r#"{"version": 1, "locations": [],
+            "items": [{"id": 7, "name": "a"}, {"id": 8, "name": "b", "location_id": 99}]}"#;
+        assert!(my_inv.import_json(json, OnConflict::Skip).is_err());
+        assert!(!my_inv.item_exists(7));
+    }
+
+    #[test]
+    fn test_parent_column_migration() {
+        let path =
+            std::env::temp_dir().join(format!("inventory_migration_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let db = rusqlite::Connection::open(&path).unwrap();
+            db.execute(
+                "CREATE TABLE locations (id INTEGER PRIMARY KEY, name TEXT, comment TEXT)",
+                (),
+            )
+            .unwrap();
+            db.execute("INSERT INTO locations (id, name) VALUES (1, 'old')", ())
+                .unwrap();
+        }
+        let my_inv = Inventory::open_in_file(path.to_str().unwrap()).unwrap();
+        let old = my_inv.search_location_id(1).unwrap();
+        assert_eq!(old.parent_id, None);
+        //records from before changes were tracked only show up once edited
+        assert!(my_inv.recent_changes(10).unwrap().is_empty());
+        assert!(my_inv.set_location_parent(1, None).is_ok());
+        assert_eq!(my_inv.recent_changes(10).unwrap()[0].id, 1);
+        drop(my_inv);
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -0,0 +1,95 @@
+use crate::AppState;
+use crossterm::event::{self, Event};
+use std::any::Any;
+use std::sync::mpsc::{self, Receiver, RecvError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How often `AppEvent::Tick` is sent.
+pub const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// Everything the `App` reacts to, gathered into one stream.
+#[derive(Debug)]
+pub enum AppEvent {
+    /// A key press, mouse action or paste from the terminal.
+    Input(Event),
+    /// The terminal was resized. The next draw fills the new size.
+    Resize,
+    /// Sent every `TICK_RATE`, so things like toasts can update without any input.
+    Tick,
+    /// The result of a background job started with `EventContext::spawn`, along with the state
+    /// of the applet that started it.
+    Worker(AppState, Box<dyn Any + Send>),
+}
+
+/// Merges terminal input, ticks and background work into a single channel.
+///
+/// Each source runs on its own thread and sends into the channel, so the `App` only ever waits
+/// in one place.
+pub struct EventLoop {
+    sender: Sender<AppEvent>,
+    receiver: Receiver<AppEvent>,
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+}
+
+impl EventLoop {
+    /// Starts reading the terminal and sending ticks every `tick_rate`. The threads stop once the
+    /// loop is dropped.
+    pub fn start(&self, tick_rate: Duration) {
+        let input = self.sender.clone();
+        thread::spawn(move || {
+            while let Ok(event) = event::read() {
+                let event = match event {
+                    Event::Resize(..) => AppEvent::Resize,
+                    event => AppEvent::Input(event),
+                };
+                if input.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        let ticks = self.sender.clone();
+        thread::spawn(move || {
+            loop {
+                thread::sleep(tick_rate);
+                if ticks.send(AppEvent::Tick).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// A sender for background workers to post results with.
+    pub fn sender(&self) -> Sender<AppEvent> {
+        self.sender.clone()
+    }
+
+    /// Waits for the next event from any source.
+    pub fn next(&self) -> Result<AppEvent, RecvError> {
+        self.receiver.recv()
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_messages() {
+        let my_loop = EventLoop::default();
+        let sender = my_loop.sender();
+        thread::spawn(move || sender.send(AppEvent::Worker(AppState::Export, Box::new(42_u32))));
+        match my_loop.next() {
+            Ok(AppEvent::Worker(AppState::Export, result)) => {
+                assert_eq!(result.downcast_ref::<u32>(), Some(&42))
+            }
+            other => panic!("expected a worker message, got {other:?}"),
+        }
+    }
+}
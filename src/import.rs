@@ -0,0 +1,569 @@
+use crate::csv::{self, CsvError, Records};
+use crate::db::inventory::{Inventory, Item, Location};
+use crate::validation;
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+
+/// The fields an item import fills in, named as in the export's header row.
+const ITEM_FIELDS: &[&str] = &["id", "name", "comment", "location_id", "location"];
+const LOCATION_FIELDS: &[&str] = &["id", "name", "comment", "parent_id", "parent"];
+
+/// Which CSV column fills each field. Every field starts out read from the column of the same
+/// name, so a file written by the export imports without any mapping.
+pub struct ColumnMap {
+    records: Records,
+    columns: HashMap<&'static str, String>,
+}
+
+impl ColumnMap {
+    pub fn new(records: Records) -> Self {
+        let columns = Self::fields(records)
+            .iter()
+            .map(|field| (*field, field.to_string()))
+            .collect();
+        Self { records, columns }
+    }
+
+    fn fields(records: Records) -> &'static [&'static str] {
+        match records {
+            Records::Items => ITEM_FIELDS,
+            Records::Locations => LOCATION_FIELDS,
+        }
+    }
+
+    /// Reads a mapping written as `field=Column`, e.g. `name=Description`.
+    pub fn set(&mut self, mapping: &str) -> Result<(), Box<CsvError>> {
+        let Some((field, column)) = mapping.split_once('=') else {
+            return Err(CsvError::new(&format!(
+                "Column mappings are written as field=Column, not '{mapping}'"
+            )));
+        };
+        let fields = Self::fields(self.records);
+        let Some(field) = fields.iter().find(|f| **f == field.trim()) else {
+            return Err(CsvError::new(&format!(
+                "Unknown field '{field}', {} have {}",
+                self.records.name(),
+                fields.join(", ")
+            )));
+        };
+        self.columns.insert(field, column.trim().to_string());
+        Ok(())
+    }
+
+    /// Finds each field's column in `header`, ignoring case. ID and name must be there, the
+    /// other fields are left empty without their column.
+    fn find(&self, header: &[String]) -> Result<HashMap<&'static str, usize>, Box<CsvError>> {
+        let mut found = HashMap::new();
+        for (field, column) in &self.columns {
+            match header
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(column))
+            {
+                Some(index) => _ = found.insert(*field, index),
+                None if ["id", "name"].contains(field) => {
+                    return Err(CsvError::new(&format!(
+                        "The header has no '{column}' column for the {field}"
+                    )));
+                }
+                None => {}
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// A row that can not be imported, numbered like spreadsheet rows with the header as row 1.
+#[derive(Debug, PartialEq)]
+pub struct RowProblem {
+    pub row: usize,
+    pub problems: Vec<String>,
+}
+
+/// What an import did, or would do on a dry run.
+#[derive(Debug)]
+pub struct ImportReport {
+    pub records: Records,
+    /// The number of rows below the header.
+    pub rows: usize,
+    pub problems: Vec<RowProblem>,
+    /// The full paths of locations that were not found by name and are created.
+    pub new_locations: Vec<String>,
+    /// True once the rows are in the database. Stays false on a dry run or with any problems.
+    pub imported: bool,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "row {}: {}", problem.row, problem.problems.join("; "))?;
+        }
+        if !self.problems.is_empty() {
+            return write!(
+                f,
+                "{} of {} rows have problems, nothing was imported",
+                self.problems.len(),
+                self.rows
+            );
+        }
+        for path in &self.new_locations {
+            writeln!(f, "new location: {path}")?;
+        }
+        let verb = if self.imported {
+            "Imported"
+        } else {
+            "Ready to import"
+        };
+        write!(f, "{verb} {}", self.records.count(self.rows))?;
+        if !self.new_locations.is_empty() {
+            let locations = Records::Locations.count(self.new_locations.len());
+            write!(f, ", creating {locations}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every location known to the import: those in the database, the ones being imported and the
+/// ones created for names that were not found.
+struct Locations {
+    all: Vec<Location>,
+    created: Vec<Location>,
+    next_id: i64,
+}
+
+impl Locations {
+    fn new(db: &Inventory, file_ids: &HashSet<i64>) -> rusqlite::Result<Self> {
+        let all = db.get_all_locations()?;
+        let next_id = all
+            .iter()
+            .map(|l| l.id)
+            .chain(file_ids.iter().copied())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        Ok(Self {
+            all,
+            created: Vec::new(),
+            next_id,
+        })
+    }
+
+    /// Finds a location by name or by full path, e.g. "Garage › Shelf 2", creating any part of
+    /// the path that does not exist yet. A bare name matching more than one location is an error.
+    fn resolve(&mut self, path: &str) -> Result<i64, String> {
+        let names: Vec<&str> = path.split('›').map(str::trim).collect();
+        if names.iter().any(|name| name.is_empty()) {
+            return Err(format!("Location '{path}' has an empty name in it"));
+        }
+        if let [name] = names[..] {
+            let matches: Vec<i64> = self
+                .all
+                .iter()
+                .filter(|l| l.name == name)
+                .map(|l| l.id)
+                .collect();
+            match matches[..] {
+                [id] => return Ok(id),
+                [_, _, ..] => {
+                    return Err(format!(
+                        "There are {} locations named '{name}', give the full path",
+                        matches.len()
+                    ));
+                }
+                [] => {}
+            }
+        }
+        let mut parent_id = None;
+        for name in names {
+            let found = self
+                .all
+                .iter()
+                .find(|l| l.parent_id == parent_id && l.name == name);
+            parent_id = Some(match found {
+                Some(location) => location.id,
+                None => self.create(name, parent_id),
+            });
+        }
+        Ok(parent_id.unwrap_or_default())
+    }
+
+    fn create(&mut self, name: &str, parent_id: Option<i64>) -> i64 {
+        let location = Location {
+            id: self.next_id,
+            name: name.to_string(),
+            comment: None,
+            parent_id,
+        };
+        self.next_id += 1;
+        self.all.push(location.clone());
+        self.created.push(location);
+        self.next_id - 1
+    }
+
+    fn path(&self, id: i64) -> String {
+        let mut names = Vec::new();
+        let mut next = Some(id);
+        while let Some(location) = next.and_then(|id| self.all.iter().find(|l| l.id == id)) {
+            if names.len() > self.all.len() {
+                break;
+            }
+            names.push(location.name.as_str());
+            next = location.parent_id;
+        }
+        names.reverse();
+        names.join(" › ")
+    }
+
+    /// True if following parents from `id` leads back to it.
+    fn in_cycle(&self, id: i64) -> bool {
+        let mut seen = HashSet::new();
+        let mut next = self
+            .all
+            .iter()
+            .find(|l| l.id == id)
+            .and_then(|l| l.parent_id);
+        while let Some(parent_id) = next {
+            if parent_id == id {
+                return true;
+            }
+            if !seen.insert(parent_id) {
+                return false;
+            }
+            next = self
+                .all
+                .iter()
+                .find(|l| l.id == parent_id)
+                .and_then(|l| l.parent_id);
+        }
+        false
+    }
+}
+
+/// The checks the create forms make, with the same messages.
+fn id_value(value: &str, label: &str) -> Result<i64, String> {
+    if value.is_empty() {
+        return Err(format!("{label} cannot be empty"));
+    }
+    value
+        .parse()
+        .map_err(|_| format!("Failed to parse {label}"))
+}
+
+/// One data row, with the value for each mapped field.
+struct Row<'a> {
+    values: &'a [String],
+    columns: &'a HashMap<&'static str, usize>,
+}
+
+impl Row<'_> {
+    fn get(&self, field: &str) -> &str {
+        self.columns
+            .get(field)
+            .and_then(|i| self.values.get(*i))
+            .map_or("", |v| v.trim())
+    }
+
+    fn optional(&self, field: &str) -> Option<String> {
+        Some(self.get(field).to_string()).filter(|v| !v.is_empty())
+    }
+}
+
+/// Checks every row of `text` and, unless `dry_run` is set or a row has problems, adds them all
+/// in one transaction.
+pub fn import(
+    db: &Inventory,
+    text: &str,
+    columns: &ColumnMap,
+    dry_run: bool,
+) -> Result<ImportReport, Box<dyn error::Error>> {
+    let rows = csv::parse(text)?;
+    let Some((header, rows)) = rows.split_first() else {
+        return Err(CsvError::new("The file is empty"));
+    };
+    let found = columns.find(header)?;
+    let rows: Vec<Row> = rows
+        .iter()
+        .map(|values| Row {
+            values,
+            columns: &found,
+        })
+        .collect();
+    let file_ids: HashSet<i64> = match columns.records {
+        Records::Items => HashSet::new(),
+        Records::Locations => rows
+            .iter()
+            .filter_map(|r| r.get("id").parse().ok())
+            .collect(),
+    };
+    let mut locations = Locations::new(db, &file_ids)?;
+    let mut report = ImportReport {
+        records: columns.records,
+        rows: rows.len(),
+        problems: Vec::new(),
+        new_locations: Vec::new(),
+        imported: false,
+    };
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+    let mut imported_locations = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let mut problems = Vec::new();
+        if row.values.len() != header.len() {
+            problems.push(format!(
+                "Expected {} fields like the header, found {}",
+                header.len(),
+                row.values.len()
+            ));
+        }
+        let result = match columns.records {
+            Records::Items => check_item(db, row, &mut locations).map(|item| items.push(item)),
+            Records::Locations => {
+                check_location(db, row, &file_ids, &mut locations).map(|location| {
+                    locations.all.push(location.clone());
+                    imported_locations.push(location);
+                })
+            }
+        };
+        if let Err(mut row_problems) = result {
+            problems.append(&mut row_problems);
+        }
+        let id = row.get("id");
+        if !id.is_empty() && !seen.insert(id) {
+            problems.push(format!("ID {id} appears more than once in the file"));
+        }
+        if !problems.is_empty() {
+            report.problems.push(RowProblem {
+                row: index + 2,
+                problems,
+            });
+        }
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let cycle = imported_locations
+            .iter()
+            .any(|l| l.id.to_string() == row.get("id") && locations.in_cycle(l.id));
+        if cycle {
+            let problem = "A location can not be inside itself".to_string();
+            match report.problems.iter_mut().find(|p| p.row == index + 2) {
+                Some(row_problem) => row_problem.problems.push(problem),
+                None => report.problems.push(RowProblem {
+                    row: index + 2,
+                    problems: vec![problem],
+                }),
+            }
+        }
+    }
+    report.problems.sort_by_key(|p| p.row);
+    report.new_locations = locations
+        .created
+        .iter()
+        .map(|l| locations.path(l.id))
+        .collect();
+    if report.problems.is_empty() && !dry_run {
+        let mut new_locations = std::mem::take(&mut locations.created);
+        new_locations.append(&mut imported_locations);
+        db.add_records(&new_locations, &items)?;
+        report.imported = true;
+    }
+    Ok(report)
+}
+
+fn check_item(db: &Inventory, row: &Row, locations: &mut Locations) -> Result<Item, Vec<String>> {
+    let mut problems = Vec::new();
+    let id = id_value(row.get("id"), "Item ID")
+        .and_then(|id| validation::item_id_unused(&id.to_string(), db).map(|_| id))
+        .map_err(|e| problems.push(e));
+    let name = row.get("name");
+    if let Err(e) = validation::not_blank("Name", name) {
+        problems.push(e);
+    }
+    let location_id = match (row.get("location_id"), row.get("location")) {
+        ("", "") => Ok(None),
+        ("", path) => locations.resolve(path).map(Some),
+        (value, _) => id_value(value, "Location ID")
+            .and_then(|id| validation::location_id_exists(value, db).map(|_| Some(id))),
+    }
+    .map_err(|e| problems.push(e));
+    match (id, location_id) {
+        (Ok(id), Ok(location_id)) if problems.is_empty() => Ok(Item {
+            id,
+            name: name.to_string(),
+            comment: row.optional("comment"),
+            location_id,
+        }),
+        _ => Err(problems),
+    }
+}
+
+fn check_location(
+    db: &Inventory,
+    row: &Row,
+    file_ids: &HashSet<i64>,
+    locations: &mut Locations,
+) -> Result<Location, Vec<String>> {
+    let mut problems = Vec::new();
+    let id = id_value(row.get("id"), "Location ID")
+        .and_then(|id| validation::location_id_unused(&id.to_string(), db).map(|_| id))
+        .map_err(|e| problems.push(e));
+    let name = row.get("name");
+    if let Err(e) = validation::not_blank("Name", name) {
+        problems.push(e);
+    }
+    let parent_id = match (row.get("parent_id"), row.get("parent")) {
+        ("", "") => Ok(None),
+        ("", path) => locations.resolve(path).map(Some),
+        (value, _) => id_value(value, "Parent ID").and_then(|id| {
+            if file_ids.contains(&id) {
+                Ok(Some(id))
+            } else {
+                validation::location_id_exists(value, db)
+                    .map(|_| Some(id))
+                    .map_err(|_| "Parent ID does not exist".to_string())
+            }
+        }),
+    }
+    .map_err(|e| problems.push(e));
+    match (id, parent_id) {
+        (Ok(id), Ok(parent_id)) if problems.is_empty() => Ok(Location {
+            id,
+            name: name.to_string(),
+            comment: row.optional("comment"),
+            parent_id,
+        }),
+        _ => Err(problems),
+    }
+}
+
+#[cfg(test)]
+mod import_tests {
+    use super::*;
+    use crate::seed::fixtures;
+
+    #[test]
+    fn test_column_map() {
+        let mut my_map = ColumnMap::new(Records::Items);
+        assert!(my_map.set("name = Description").is_ok());
+        assert!(my_map.set("parent=Where").is_err());
+        assert!(my_map.set("location").is_err());
+        let header: Vec<String> = ["ID", "description"].map(String::from).to_vec();
+        let found = my_map.find(&header).unwrap();
+        assert_eq!(found.get("id"), Some(&0));
+        assert_eq!(found.get("name"), Some(&1));
+        assert_eq!(found.get("comment"), None);
+
+        let header: Vec<String> = ["id", "name"].map(String::from).to_vec();
+        assert!(my_map.find(&header).is_err());
+    }
+
+    #[test]
+    fn test_dry_run_problems() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::SAME_NAMES);
+        let text = "id,name,location_id,location\n\
+                    100,taken,,\n\
+                    101,,,\n\
+                    102,glue,9,\n\
+                    abc,pins,,Shelf\n\
+                    103,bolts,,Garage › Shelf\n\
+                    103,nuts,,\n\
+                    104,nails\n";
+        let report = import(&my_inv, text, &ColumnMap::new(Records::Items), true).unwrap();
+        assert!(!report.imported);
+        assert_eq!(report.rows, 7);
+        let problems: Vec<(usize, String)> = report
+            .problems
+            .iter()
+            .map(|p| (p.row, p.problems.join("; ")))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (2, "Item ID already exists".to_string()),
+                (3, "Name cannot be empty".to_string()),
+                (4, "Location ID does not exist".to_string()),
+                (
+                    5,
+                    "Failed to parse Item ID; There are 2 locations named 'Shelf', give the full path"
+                        .to_string()
+                ),
+                (7, "ID 103 appears more than once in the file".to_string()),
+                (8, "Expected 4 fields like the header, found 2".to_string()),
+            ]
+        );
+        assert!(
+            report
+                .to_string()
+                .ends_with("6 of 7 rows have problems, nothing was imported")
+        );
+
+        //nothing is written even without the dry run
+        let report = import(&my_inv, text, &ColumnMap::new(Records::Items), false).unwrap();
+        assert!(!report.imported);
+        assert_eq!(my_inv.stats().unwrap().items, 1);
+    }
+
+    #[test]
+    fn test_import_items() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::SAME_NAMES);
+        let mut my_map = ColumnMap::new(Records::Items);
+        assert!(my_map.set("name=Description").is_ok());
+        assert!(my_map.set("location=Where").is_ok());
+        let text = "Description,id,Where,comment\n\
+                    bolts,101,Garage › Shelf,\"M6, zinc\"\n\
+                    paint,102,Attic › Box 1,\n\
+                    brush,103,Attic › Box 1,\n\
+                    glue,104,,\n";
+
+        let report = import(&my_inv, text, &my_map, true).unwrap();
+        assert!(report.problems.is_empty());
+        assert_eq!(report.new_locations, vec!["Attic", "Attic › Box 1"]);
+        assert_eq!(
+            report.to_string(),
+            "new location: Attic\nnew location: Attic › Box 1\n\
+             Ready to import 4 items, creating 2 locations"
+        );
+        assert_eq!(my_inv.stats().unwrap().items, 1);
+
+        let report = import(&my_inv, text, &my_map, false).unwrap();
+        assert!(report.imported);
+        let bolts = my_inv.search_item_id(101).unwrap();
+        assert_eq!(bolts.comment.as_deref(), Some("M6, zinc"));
+        assert_eq!(bolts.location_id, Some(2));
+        let paint = my_inv.search_item_id(102).unwrap();
+        let brush = my_inv.search_item_id(103).unwrap();
+        assert_eq!(paint.location_id, brush.location_id);
+        assert_eq!(
+            my_inv.location_paths().unwrap()[&paint.location_id.unwrap()],
+            "Attic › Box 1"
+        );
+        assert_eq!(my_inv.search_item_id(104).unwrap().location_id, None);
+    }
+
+    #[test]
+    fn test_import_locations() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::SAME_NAMES);
+        //an exported file, with the child before its parent
+        let text = "id,name,comment,parent_id,parent\r\n\
+                    10,Drawer,,11,\r\n\
+                    11,Desk,,,Garage\r\n\
+                    12,Tray,,,Garage › Desk\r\n";
+        let report = import(&my_inv, text, &ColumnMap::new(Records::Locations), false).unwrap();
+        assert!(report.problems.is_empty(), "{report}");
+        assert!(report.new_locations.is_empty());
+        let paths = my_inv.location_paths().unwrap();
+        assert_eq!(paths[&10], "Garage › Desk › Drawer");
+        assert_eq!(paths[&12], "Garage › Desk › Tray");
+
+        let text = "id,name,parent_id\n20,Loop,21\n21,Back,20\n22,Lost,99\n";
+        let report = import(&my_inv, text, &ColumnMap::new(Records::Locations), false).unwrap();
+        let rows: Vec<usize> = report.problems.iter().map(|p| p.row).collect();
+        assert_eq!(rows, vec![2, 3, 4]);
+        assert_eq!(
+            report.problems[2].problems,
+            vec!["Parent ID does not exist"]
+        );
+        assert!(!my_inv.location_exists(20));
+    }
+}
@@ -0,0 +1,735 @@
+use crate::config::{ConfigError, KeysConfig};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Something a key can be bound to.
+///
+/// Actions belong to a `Context`: list actions are used by menus and tables, form actions by
+/// screens with text entry, where unbound keys are typed into the focused field. `Help` and the
+/// global actions handled by the `App` are available in both, although keys that type a
+/// character only work outside of forms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    /// Clears the filter if there is one, otherwise leaves the screen.
+    Back,
+    Up,
+    Down,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Select,
+    Edit,
+    Filter,
+    NextMatch,
+    PreviousMatch,
+    Sort,
+    Mark,
+    MarkRange,
+    MarkAll,
+    Bulk,
+    Delete,
+    Preview,
+    GrowPreview,
+    ShrinkPreview,
+    Cut,
+    Paste,
+    Cancel,
+    Save,
+    NextField,
+    PreviousField,
+    Help,
+    Messages,
+    Home,
+    Breadcrumbs,
+    Palette,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Context {
+    List,
+    Form,
+}
+
+impl Action {
+    pub const ALL: [Action; 33] = [
+        Action::Quit,
+        Action::Back,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Top,
+        Action::Bottom,
+        Action::Select,
+        Action::Edit,
+        Action::Filter,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::Sort,
+        Action::Mark,
+        Action::MarkRange,
+        Action::MarkAll,
+        Action::Bulk,
+        Action::Delete,
+        Action::Preview,
+        Action::GrowPreview,
+        Action::ShrinkPreview,
+        Action::Cut,
+        Action::Paste,
+        Action::Cancel,
+        Action::Save,
+        Action::NextField,
+        Action::PreviousField,
+        Action::Help,
+        Action::Messages,
+        Action::Home,
+        Action::Breadcrumbs,
+        Action::Palette,
+    ];
+
+    pub fn contexts(self) -> &'static [Context] {
+        match self {
+            Action::Cancel | Action::Save | Action::NextField | Action::PreviousField => {
+                &[Context::Form]
+            }
+            Action::Help
+            | Action::Messages
+            | Action::Home
+            | Action::Breadcrumbs
+            | Action::Palette => &[Context::List, Context::Form],
+            _ => &[Context::List],
+        }
+    }
+
+    fn shares_context(self, other: Action) -> bool {
+        self.contexts().iter().any(|c| other.contexts().contains(c))
+    }
+
+    /// The name used for the action in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Back => "back",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Select => "select",
+            Action::Edit => "edit",
+            Action::Filter => "filter",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::Sort => "sort",
+            Action::Mark => "mark",
+            Action::MarkRange => "mark_range",
+            Action::MarkAll => "mark_all",
+            Action::Bulk => "bulk",
+            Action::Delete => "delete",
+            Action::Preview => "preview",
+            Action::GrowPreview => "grow_preview",
+            Action::ShrinkPreview => "shrink_preview",
+            Action::Cut => "cut",
+            Action::Paste => "paste",
+            Action::Cancel => "cancel",
+            Action::Save => "save",
+            Action::NextField => "next_field",
+            Action::PreviousField => "previous_field",
+            Action::Help => "help",
+            Action::Messages => "messages",
+            Action::Home => "home",
+            Action::Breadcrumbs => "breadcrumbs",
+            Action::Palette => "palette",
+        }
+    }
+
+    /// What the action does, as listed in the help overlay.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Leave this screen",
+            Action::Back => "Clear the filter or marks, or leave this screen",
+            Action::Up => "Move up",
+            Action::Down => "Move down",
+            Action::Left => "Select the previous column",
+            Action::Right => "Select the next column",
+            Action::Top => "Go to the first row",
+            Action::Bottom => "Go to the last row",
+            Action::Select => "Open the selected entry",
+            Action::Edit => "Edit the selected row",
+            Action::Filter => "Filter the rows",
+            Action::NextMatch => "Go to the next filter match",
+            Action::PreviousMatch => "Go to the previous filter match",
+            Action::Sort => "Sort by the selected column",
+            Action::Mark => "Mark or unmark the selected row",
+            Action::MarkRange => "Mark every row from the last marked one",
+            Action::MarkAll => "Mark every row matching the filter, or unmark them",
+            Action::Bulk => "Change the marked rows",
+            Action::Delete => "Delete the marked rows",
+            Action::Preview => "Show the preview pane on the side, at the bottom, or hide it",
+            Action::GrowPreview => "Make the preview pane bigger",
+            Action::ShrinkPreview => "Make the preview pane smaller",
+            Action::Cut => "Cut the selected location to move it",
+            Action::Paste => "Move the cut location inside the selected one",
+            Action::Cancel => "Leave without saving",
+            Action::Save => "Save",
+            Action::NextField => "Go to the next field",
+            Action::PreviousField => "Go to the previous field",
+            Action::Help => "Show this help",
+            Action::Messages => "Show the message log",
+            Action::Home => "Go back to the dashboard",
+            Action::Breadcrumbs => "Pick a screen to go back to",
+            Action::Palette => "Search for a command or record",
+        }
+    }
+}
+
+/// A single key press. Shift is folded into the character for letters, so `G` is Shift+g.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self {
+            code,
+            modifiers: modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    /// Parses names like `q`, `G`, `Esc`, `Space`, `F1` or `Ctrl+p`.
+    pub fn parse(text: &str) -> Result<Self, Box<ConfigError>> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((modifier, key)) = rest.split_once('+')
+            && !key.is_empty()
+        {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => return Err(ConfigError::new(&format!("Unknown modifier in '{text}'"))),
+            };
+            rest = key;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(ConfigError::new(&format!("Unknown key '{text}'"))),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    /// Whether pressing the key types a character into a text field.
+    fn is_printable(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        KeyEvent::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "BackTab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Delete"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code}"),
+        }
+    }
+}
+
+/// One or more keys pressed in order, written space separated in the config (`"g g"`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeySequence(Vec<Key>);
+
+impl KeySequence {
+    pub fn parse(text: &str) -> Result<Self, Box<ConfigError>> {
+        let keys = text
+            .split_whitespace()
+            .map(Key::parse)
+            .collect::<Result<Vec<Key>, _>>()?;
+        if keys.is_empty() {
+            return Err(ConfigError::new("Empty key binding"));
+        }
+        Ok(Self(keys))
+    }
+
+    fn starts_with(&self, keys: &[Key]) -> bool {
+        self.0.starts_with(keys)
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Action, KeySequence)>,
+}
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/// Installs the keymap used by `current`. Only the first call has any effect.
+pub fn init(keymap: Keymap) {
+    let _ = KEYMAP.set(keymap);
+}
+
+pub fn current() -> &'static Keymap {
+    KEYMAP.get_or_init(Keymap::default_preset)
+}
+
+enum Lookup {
+    Found(Action),
+    Prefix,
+    Unbound,
+}
+
+impl Keymap {
+    fn from_table(table: &[(Action, &[&str])]) -> Self {
+        let bindings = table
+            .iter()
+            .flat_map(|(action, keys)| {
+                keys.iter().map(|k| {
+                    (
+                        *action,
+                        KeySequence::parse(k).expect("valid preset binding"),
+                    )
+                })
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    pub fn default_preset() -> Self {
+        Self::from_table(&[
+            (Action::Quit, &["q"]),
+            (Action::Back, &["Esc"]),
+            (Action::Up, &["Up"]),
+            (Action::Down, &["Down"]),
+            (Action::Left, &["Left"]),
+            (Action::Right, &["Right"]),
+            (Action::Top, &["Home"]),
+            (Action::Bottom, &["End"]),
+            (Action::Select, &["Enter"]),
+            (Action::Edit, &["e"]),
+            (Action::Filter, &["/"]),
+            (Action::NextMatch, &["n"]),
+            (Action::PreviousMatch, &["N"]),
+            (Action::Sort, &["s"]),
+            (Action::Mark, &["Space"]),
+            (Action::MarkRange, &["V"]),
+            (Action::MarkAll, &["Ctrl+a"]),
+            (Action::Bulk, &["b"]),
+            (Action::Delete, &["Delete"]),
+            (Action::Preview, &["p"]),
+            (Action::GrowPreview, &["+"]),
+            (Action::ShrinkPreview, &["-"]),
+            (Action::Cut, &["x"]),
+            (Action::Paste, &["v"]),
+            (Action::Cancel, &["Esc"]),
+            (Action::Save, &["Ctrl+s"]),
+            (Action::NextField, &["Tab", "Down"]),
+            (Action::PreviousField, &["BackTab", "Up"]),
+            (Action::Help, &["?", "F1"]),
+            (Action::Messages, &["F2"]),
+            (Action::Home, &["Alt+h"]),
+            (Action::Breadcrumbs, &["Alt+b"]),
+            (Action::Palette, &["Ctrl+p"]),
+        ])
+    }
+
+    pub fn vim() -> Self {
+        Self::from_table(&[
+            (Action::Quit, &["q"]),
+            (Action::Back, &["Esc"]),
+            (Action::Up, &["k", "Up"]),
+            (Action::Down, &["j", "Down"]),
+            (Action::Left, &["h", "Left"]),
+            (Action::Right, &["l", "Right"]),
+            (Action::Top, &["g g", "Home"]),
+            (Action::Bottom, &["G", "End"]),
+            (Action::Select, &["Enter"]),
+            (Action::Edit, &["e"]),
+            (Action::Filter, &["/"]),
+            (Action::NextMatch, &["n"]),
+            (Action::PreviousMatch, &["N"]),
+            (Action::Sort, &["s"]),
+            (Action::Mark, &["Space"]),
+            (Action::MarkRange, &["V"]),
+            (Action::MarkAll, &["Ctrl+a"]),
+            (Action::Bulk, &["b"]),
+            (Action::Delete, &["Delete"]),
+            (Action::Preview, &["p"]),
+            (Action::GrowPreview, &["+"]),
+            (Action::ShrinkPreview, &["-"]),
+            (Action::Cut, &["x"]),
+            (Action::Paste, &["v"]),
+            (Action::Cancel, &["Esc"]),
+            (Action::Save, &["Ctrl+s"]),
+            (Action::NextField, &["Tab", "Down", "Ctrl+j"]),
+            (Action::PreviousField, &["BackTab", "Up", "Ctrl+k"]),
+            (Action::Help, &["?", "F1"]),
+            (Action::Messages, &["F2"]),
+            (Action::Home, &["Alt+h"]),
+            (Action::Breadcrumbs, &["Alt+b"]),
+            (Action::Palette, &["Ctrl+p"]),
+        ])
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default_preset()),
+            "vim" => Some(Self::vim()),
+            _ => None,
+        }
+    }
+
+    /// Builds the configured keymap. Bindings listed for an action replace the preset's
+    /// bindings for it. Conflicting bindings are reported as an error.
+    pub fn from_config(config: &KeysConfig) -> Result<Self, Box<ConfigError>> {
+        let mut keymap = Self::by_name(&config.preset)
+            .ok_or_else(|| ConfigError::new(&format!("Unknown key preset '{}'", config.preset)))?;
+        for (action, keys) in &config.bindings {
+            keymap.bindings.retain(|(a, _)| a != action);
+            for key in keys {
+                keymap.bindings.push((*action, KeySequence::parse(key)?));
+            }
+        }
+        let conflicts = keymap.conflicts();
+        if !conflicts.is_empty() {
+            return Err(ConfigError::new(&format!(
+                "Conflicting key bindings: {}",
+                conflicts.join("; ")
+            )));
+        }
+        Ok(keymap)
+    }
+
+    /// Describes every pair of bindings in the same context where one shadows the other, every
+    /// form binding that would stop a character from being typed, and every action left without
+    /// a key.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for action in Action::ALL {
+            let unbound = action
+                .contexts()
+                .iter()
+                .any(|&context| self.keys(action, context).next().is_none());
+            if unbound {
+                conflicts.push(format!("{} is not bound to any key", action.name()));
+            }
+        }
+        for (i, (action, keys)) in self.bindings.iter().enumerate() {
+            if action.contexts() == [Context::Form] && keys.0[0].is_printable() {
+                conflicts.push(format!(
+                    "'{keys}' ({}) would block typing in text fields",
+                    action.name()
+                ));
+            }
+            for (other_action, other_keys) in &self.bindings[i + 1..] {
+                if !action.shares_context(*other_action)
+                    || (action == other_action && keys == other_keys)
+                {
+                    continue;
+                }
+                if keys == other_keys {
+                    conflicts.push(format!(
+                        "'{keys}' is bound to both {} and {}",
+                        action.name(),
+                        other_action.name()
+                    ));
+                } else if keys.starts_with(&other_keys.0) || other_keys.starts_with(&keys.0) {
+                    conflicts.push(format!(
+                        "'{keys}' ({}) overlaps '{other_keys}' ({})",
+                        action.name(),
+                        other_action.name()
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The key sequences that trigger `action` in `context`, in the order they were configured.
+    pub fn keys(&self, action: Action, context: Context) -> impl Iterator<Item = &KeySequence> {
+        self.bindings
+            .iter()
+            .filter(move |(a, keys)| *a == action && usable(*a, keys, context))
+            .map(|(_, keys)| keys)
+    }
+
+    /// Whether `key` on its own triggers `action` in `context`. Used for keys that are handled
+    /// outside of the applets, which can't take part in multi key bindings.
+    pub fn matches(&self, action: Action, context: Context, key: KeyEvent) -> bool {
+        let key = Key::from(key);
+        self.keys(action, context).any(|keys| keys.0 == [key])
+    }
+
+    fn lookup(&self, context: Context, pressed: &[Key]) -> Lookup {
+        let mut prefix = false;
+        for (action, keys) in &self.bindings {
+            if !usable(*action, keys, context) {
+                continue;
+            }
+            if keys.0 == pressed {
+                return Lookup::Found(*action);
+            }
+            prefix |= keys.starts_with(pressed);
+        }
+        if prefix {
+            Lookup::Prefix
+        } else {
+            Lookup::Unbound
+        }
+    }
+}
+
+/// Keys that type a character are left to the text fields in forms.
+fn usable(action: Action, keys: &KeySequence, context: Context) -> bool {
+    action.contexts().contains(&context) && (context == Context::List || !keys.0[0].is_printable())
+}
+
+/// Collects key presses into actions, remembering the start of a multi key binding like `g g`.
+#[derive(Default)]
+pub struct KeyReader {
+    pending: Vec<Key>,
+}
+
+impl KeyReader {
+    pub fn action(&mut self, context: Context, key: KeyEvent) -> Option<Action> {
+        self.action_in(current(), context, key)
+    }
+
+    /// True while the keys read so far are the start of a longer binding.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn action_in(&mut self, keymap: &Keymap, context: Context, key: KeyEvent) -> Option<Action> {
+        self.pending.push(key.into());
+        match keymap.lookup(context, &self.pending) {
+            Lookup::Found(action) => {
+                self.pending.clear();
+                Some(action)
+            }
+            Lookup::Prefix => None,
+            Lookup::Unbound if self.pending.len() > 1 => {
+                //the sequence was abandoned, so start over from the latest key
+                self.pending.clear();
+                self.action_in(keymap, context, key)
+            }
+            Lookup::Unbound => {
+                self.pending.clear();
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Key::parse("q").unwrap(),
+            Key::new(KeyCode::Char('q'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("Ctrl+p").unwrap(),
+            Key::new(KeyCode::Char('p'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            Key::parse("+").unwrap(),
+            Key::new(KeyCode::Char('+'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("esc").unwrap(),
+            Key::new(KeyCode::Esc, KeyModifiers::NONE)
+        );
+        assert_eq!(
+            Key::parse("F5").unwrap(),
+            Key::new(KeyCode::F(5), KeyModifiers::NONE)
+        );
+        assert!(Key::parse("Hyper+x").is_err());
+        assert!(Key::parse("F13").is_err());
+        assert!(Key::parse("Escape").is_err());
+        assert!(KeySequence::parse("").is_err());
+
+        for text in ["g g", "Ctrl+s", "Space", "BackTab", "Alt+Enter", "F1"] {
+            assert_eq!(KeySequence::parse(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_shift_is_folded() {
+        let key = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(Key::from(key), Key::parse("G").unwrap());
+    }
+
+    #[test]
+    fn test_presets_have_no_conflicts() {
+        assert!(Keymap::default_preset().conflicts().is_empty());
+        assert!(Keymap::vim().conflicts().is_empty());
+        for action in Action::ALL {
+            for keymap in [Keymap::default_preset(), Keymap::vim()] {
+                for context in action.contexts() {
+                    assert!(keymap.keys(action, *context).next().is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sequences() {
+        let keymap = Keymap::vim();
+        let mut my_reader = KeyReader::default();
+        let g = press(KeyCode::Char('g'));
+        assert_eq!(my_reader.action_in(&keymap, Context::List, g), None);
+        assert!(my_reader.is_pending());
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::List, g),
+            Some(Action::Top)
+        );
+        assert!(!my_reader.is_pending());
+
+        //an abandoned sequence falls through to the next key
+        assert_eq!(my_reader.action_in(&keymap, Context::List, g), None);
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::List, press(KeyCode::Char('j'))),
+            Some(Action::Down)
+        );
+
+        //the same key can mean different things in different contexts
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::List, press(KeyCode::Esc)),
+            Some(Action::Back)
+        );
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::Form, press(KeyCode::Esc)),
+            Some(Action::Cancel)
+        );
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::Form, press(KeyCode::Char('j'))),
+            None
+        );
+
+        //help is everywhere, but '?' is typed into form fields
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::List, press(KeyCode::Char('?'))),
+            Some(Action::Help)
+        );
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::Form, press(KeyCode::Char('?'))),
+            None
+        );
+        assert_eq!(
+            my_reader.action_in(&keymap, Context::Form, press(KeyCode::F(1))),
+            Some(Action::Help)
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = KeysConfig {
+            preset: "vim".to_string(),
+            bindings: HashMap::from([(Action::Quit, vec!["Q".to_string(), "Ctrl+c".to_string()])]),
+        };
+        let keymap = Keymap::from_config(&config).unwrap();
+        let quit: Vec<String> = keymap
+            .keys(Action::Quit, Context::List)
+            .map(|k| k.to_string())
+            .collect();
+        assert_eq!(quit, vec!["Q", "Ctrl+c"]);
+
+        config.bindings.insert(Action::Sort, vec!["g".to_string()]);
+        let error = Keymap::from_config(&config).unwrap_err().to_string();
+        assert!(error.contains("'g g' (top) overlaps 'g' (sort)"));
+
+        config.bindings.clear();
+        config.bindings.insert(Action::Edit, vec!["j".to_string()]);
+        let error = Keymap::from_config(&config).unwrap_err().to_string();
+        assert!(error.contains("'j' is bound to both down and edit"));
+
+        config.bindings.clear();
+        config.bindings.insert(Action::Save, vec!["s".to_string()]);
+        assert!(Keymap::from_config(&config).is_err());
+
+        //an action can not be left without a key
+        config.bindings.clear();
+        config.bindings.insert(Action::Quit, Vec::new());
+        let error = Keymap::from_config(&config).unwrap_err().to_string();
+        assert!(error.contains("quit is not bound to any key"));
+
+        config.bindings.clear();
+        config.preset = "emacs".to_string();
+        assert!(Keymap::from_config(&config).is_err());
+    }
+}
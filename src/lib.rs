@@ -0,0 +1,3 @@
+//! The inventory database. It is kept apart from the terminal interface in `main.rs`, so its
+//! API is complete on its own rather than limited to what the applets call.
+pub mod db;
@@ -1,18 +1,50 @@
-use ratatui::DefaultTerminal;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use ratatui::{DefaultTerminal, Frame};
+use std::io::stdout;
+use std::process::ExitCode;
+use std::time::Instant;
 mod applets;
+use crate::applets::breadcrumbs::Breadcrumbs;
+use crate::applets::context::EventContext;
+use crate::applets::notifications::{Level, Notifications};
+use crate::applets::palette::Palette;
 use crate::db::inventory::Inventory;
-mod db;
+use crate::events::{AppEvent, EventLoop};
+mod cli;
+mod config;
+mod csv;
+use rust_inventory::db;
+mod events;
+mod import;
+mod keymap;
+mod seed;
+mod theme;
+mod validation;
+
+/// The deepest the applet stack may grow. Opening another applet drops the oldest one above the
+/// dashboard that agrees to close.
+const MAX_DEPTH: usize = 16;
 
 struct App {
-    applets: Vec<Box<dyn applets::Applet>>,
+    /// The applet stack, each with the state that opened it so it can be found again.
+    applets: Vec<(AppState, Box<dyn applets::Applet>)>,
     db: Inventory,
+    notifications: Notifications,
+    breadcrumbs: Breadcrumbs,
+    palette: Palette,
+    events: EventLoop,
+    /// The depth a breadcrumb or palette jump is headed for while an applet on the way asks
+    /// whether it may close.
+    pending_jump: Option<usize>,
 }
 
 #[derive(Clone, PartialEq, Debug)]
 enum AppState {
-    TopMenu,
+    Dashboard,
     ListItems,
     ListLocations,
+    LocationTree,
     Exit,
     EditLocation(i64),
     EditItem(i64),
@@ -20,87 +52,447 @@ enum AppState {
     CreateLocation,
     CreateItem,
     ItemLookup,
+    Export,
     Error(String),
 }
 
 impl Default for App {
     fn default() -> Self {
-        let mut app = Self::new();
-        app.applets
-            .push(Box::new(applets::TopMenuApplet::default()));
+        let mut app = Self::new(Inventory::open_in_memory().unwrap());
+        app.open(AppState::Dashboard);
         app
     }
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(db: Inventory) -> Self {
         Self {
             applets: Vec::new(),
-            db: Inventory::open_in_memory().unwrap(),
+            db,
+            notifications: Notifications::default(),
+            breadcrumbs: Breadcrumbs::default(),
+            palette: Palette::default(),
+            events: EventLoop::default(),
+            pending_jump: None,
+        }
+    }
+
+    /// Makes the applet for `state` the top of the stack. If one was already opened with the same
+    /// state, the stack goes back to it instead of opening a second copy.
+    fn open(&mut self, state: AppState) {
+        if let Some(depth) = self.applets.iter().position(|(s, _)| *s == state) {
+            self.leave_to(depth);
+            return;
+        }
+        let applet: Box<dyn applets::Applet> = match &state {
+            AppState::Dashboard => Box::new(applets::DashboardApplet::default()),
+            AppState::ListItems => Box::new(applets::ListItemsApplet::default()),
+            AppState::ListLocations => Box::new(applets::ListLocationsApplet::default()),
+            AppState::LocationTree => Box::new(applets::LocationTreeApplet::default()),
+            AppState::EditLocation(id) => Box::new(applets::EditLocationApplet::new(*id)),
+            AppState::EditItem(id) => Box::new(applets::EditItemApplet::new(*id)),
+            AppState::CreateLocation => Box::new(applets::CreateLocationApplet::new()),
+            AppState::CreateItem => Box::new(applets::CreateItemApplet::new()),
+            AppState::ItemLookup => Box::new(applets::ItemLookupApplet::new()),
+            AppState::Export => Box::new(applets::ExportApplet::new()),
+            AppState::Error(msg) => Box::new(applets::ErrorApplet::new(msg.clone())),
+            AppState::Exit | AppState::NoChange => return,
+        };
+        if self.applets.len() >= MAX_DEPTH {
+            let Some(oldest) = (1..self.applets.len()).find(|&i| self.applets[i].1.request_leave())
+            else {
+                self.notifications.push(
+                    Level::Warning,
+                    "Too many screens are open, close one before opening another",
+                );
+                return;
+            };
+            self.applets.remove(oldest);
         }
+        self.applets.push((state, applet));
     }
+
+    /// Closes the applets above `depth`, top first. Each is asked with `request_leave`, and one
+    /// that wants to stay, e.g. a form with unsaved changes, is left on top with everything below.
+    /// If it closes after all, the jump carries on from there.
+    fn leave_to(&mut self, depth: usize) {
+        while self.applets.len() > depth + 1 {
+            let Some((_, top_applet)) = self.applets.last_mut() else {
+                return;
+            };
+            if !top_applet.request_leave() {
+                self.pending_jump = Some(depth);
+                return;
+            }
+            self.applets.pop();
+        }
+    }
+
     fn run(mut self, terminal: &mut DefaultTerminal) -> Result<(), Box<dyn std::error::Error>> {
-        while let Some(top_applet) = self.applets.last_mut() {
-            if let Err(e) = top_applet.run(terminal, &self.db) {
-                self.applets
-                    .push(Box::new(applets::ErrorApplet::new(e.to_string())))
-            } else {
-                match top_applet.get_next_state() {
-                    // It is possible to create new applets until we run out of memory.  Probably should add limits at some point
-                    AppState::ListItems => self
-                        .applets
-                        .push(Box::new(applets::ListItemsApplet::default())),
-                    AppState::ListLocations => self
-                        .applets
-                        .push(Box::new(applets::ListLocationsApplet::default())),
-                    AppState::EditLocation(id) => self
-                        .applets
-                        .push(Box::new(applets::EditLocationApplet::new(id))),
-                    AppState::EditItem(id) => self
-                        .applets
-                        .push(Box::new(applets::EditItemApplet::new(id))),
-                    AppState::CreateLocation => self
+        self.events.start(events::TICK_RATE);
+        while !self.applets.is_empty() {
+            terminal.draw(|frame| self.draw(frame))?;
+            let event = self.events.next()?;
+            self.handle_event(event);
+        }
+        Ok(())
+    }
+
+    /// Draws the top applet with the breadcrumbs, command palette and notifications over it.
+    fn draw(&mut self, frame: &mut Frame) {
+        self.breadcrumbs
+            .set_titles(self.applets.iter().map(|(_, a)| a.title()).collect());
+        if let Some((_, top_applet)) = self.applets.last_mut() {
+            top_applet.render(frame);
+        }
+        self.breadcrumbs.render(frame);
+        self.palette.render(frame);
+        self.notifications.render(frame);
+    }
+
+    /// Passes `event` through the overlays to the top applet, then opens or closes applets as
+    /// asked.
+    fn handle_event(&mut self, event: AppEvent) {
+        if let AppEvent::Tick = event {
+            self.notifications.expire(Instant::now());
+        }
+        let used = match &event {
+            AppEvent::Input(input) => {
+                self.notifications.handle_event(input)
+                    || self.palette.handle_event(input, &self.db)
+                    || self.breadcrumbs.handle_event(input)
+            }
+            _ => false,
+        };
+        let target = match &event {
+            //job results go back to the applet that started the job, wherever it is now
+            AppEvent::Worker(owner, _) => {
+                let found = self.applets.iter().position(|(state, _)| state == owner);
+                if found.is_none() {
+                    self.notifications.push(
+                        Level::Warning,
+                        "A background job finished after its applet was closed",
+                    );
+                }
+                found
+            }
+            _ => self.applets.len().checked_sub(1),
+        };
+        let Some(target) = target else {
+            return;
+        };
+        let mut next_state = AppState::NoChange;
+        if !used {
+            let (state, applet) = &mut self.applets[target];
+            let mut ctx = EventContext::new(
+                &self.db,
+                &mut self.notifications,
+                self.events.sender(),
+                state.clone(),
+            );
+            match applet.handle_event(&event, &mut ctx) {
+                Ok(()) => next_state = applet.get_next_state(),
+                Err(e) if is_fatal(e.as_ref()) => next_state = AppState::Error(e.to_string()),
+                //anything else can be recovered from, so stay on the same applet
+                Err(e) => self.notifications.push(Level::Error, e.to_string()),
+            }
+        }
+        //an applet below the top can not move the stack, short of a fatal error
+        if target + 1 < self.applets.len() && !matches!(next_state, AppState::Error(_)) {
+            next_state = AppState::NoChange;
+        }
+        let pending_jump = self.pending_jump.take();
+        if let Some(depth) = self.breadcrumbs.take_jump() {
+            self.leave_to(depth);
+        } else if let Some(state) = self.palette.take_choice() {
+            self.open(state);
+        } else {
+            match next_state {
+                AppState::NoChange => {
+                    //the jump waits for the answer, and is dropped if the applet stays
+                    if self
                         .applets
-                        .push(Box::new(applets::CreateLocationApplet::new())),
-                    AppState::Error(msg) => {
-                        self.applets.push(Box::new(applets::ErrorApplet::new(msg)))
+                        .last()
+                        .is_some_and(|(_, a)| a.is_asking_to_leave())
+                    {
+                        self.pending_jump = pending_jump;
                     }
-                    AppState::CreateItem => self
-                        .applets
-                        .push(Box::new(applets::CreateItemApplet::new())),
-                    AppState::ItemLookup => self
-                        .applets
-                        .push(Box::new(applets::ItemLookupApplet::new())),
-                    AppState::Exit => _ = self.applets.pop(),
-                    _ => continue,
+                    return;
                 }
+                AppState::Exit => {
+                    self.applets.pop();
+                    if let Some(depth) = pending_jump {
+                        self.leave_to(depth);
+                    }
+                }
+                state => self.open(state),
             }
-            if let Some(new_top) = self.applets.last_mut() {
-                new_top.refresh(&self.db);
-            }
         }
+        if let Some((_, new_top)) = self.applets.last_mut() {
+            new_top.refresh(&self.db);
+        }
+    }
+}
 
-        Ok(())
+/// True if the database itself failed, e.g. it could not be read or written. The user can not
+/// fix that from the applet, so it gets the full screen `ErrorApplet` rather than a toast.
+fn is_fatal(error: &(dyn std::error::Error + 'static)) -> bool {
+    matches!(
+        error.downcast_ref::<rusqlite::Error>(),
+        Some(rusqlite::Error::SqliteFailure(e, _)) if e.code != rusqlite::ErrorCode::ConstraintViolation
+    )
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (options, command) = match cli::parse_options(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => return fail(e.as_ref(), cli::Format::Text),
+    };
+    match try_main(&options, command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => fail(e.as_ref(), options.format),
+    }
+}
+
+/// Prints `error` in `format` and returns its exit code.
+fn fail(error: &(dyn std::error::Error + 'static), format: cli::Format) -> ExitCode {
+    cli::print_error(error, format);
+    ExitCode::from(cli::exit_code(error))
+}
+
+/// Runs `command` from the command line, or the interface if there is none.
+fn try_main(options: &cli::Options, command: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let data = config::Config::load_data()?;
+    let db = match options.database.as_ref().or(data.database.as_ref()) {
+        Some(path) => Inventory::open_in_file(path)?,
+        None => {
+            cli::require_database(command)?;
+            Inventory::open_in_memory()?
+        }
+    };
+    for path in data.seeds.iter().chain(&options.seeds) {
+        seed::Seed::from_file(path)?.load(&db)?;
+    }
+    if !command.is_empty() {
+        return cli::run(command, &db, options.format, &mut stdout());
     }
+
+    let config = config::Config::load()?;
+    theme::init(theme::Theme::from_config(
+        &config.theme,
+        theme::no_color_requested(),
+    )?);
+    keymap::init(keymap::Keymap::from_config(&config.keys)?);
+    let mut myapp = App::new(db);
+    myapp.open(AppState::Dashboard);
+    execute!(stdout(), EnableMouseCapture)?;
+    let result = ratatui::run(|terminal| myapp.run(terminal));
+    execute!(stdout(), DisableMouseCapture)?;
+    result
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let myapp = App::default();
-    for i in 0..100 {
-        let loc = db::inventory::Location {
-            id: i,
-            name: format!("location{i}").to_string(),
-            comment: Some(format!("comment{i}").to_string()),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn titles(my_app: &App) -> Vec<String> {
+        my_app.applets.iter().map(|(_, a)| a.title()).collect()
+    }
+
+    #[test]
+    fn test_open() {
+        let mut my_app = App::default();
+        my_app.open(AppState::ListItems);
+        my_app.open(AppState::EditItem(104));
+        assert_eq!(
+            titles(&my_app),
+            vec!["Dashboard", "List Items", "Edit Item 104"]
+        );
+
+        //opening a state already on the stack goes back to it
+        my_app.open(AppState::ListItems);
+        assert_eq!(titles(&my_app), vec!["Dashboard", "List Items"]);
+        my_app.open(AppState::Dashboard);
+        assert_eq!(titles(&my_app), vec!["Dashboard"]);
+
+        my_app.open(AppState::NoChange);
+        assert_eq!(my_app.applets.len(), 1);
+    }
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> AppEvent {
+        AppEvent::Input(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    #[test]
+    fn test_handle_event() {
+        let mut my_app = App::default();
+        my_app.open(AppState::ListItems);
+
+        //ticks and resizes leave the stack alone
+        my_app.handle_event(AppEvent::Tick);
+        my_app.handle_event(AppEvent::Resize);
+        assert_eq!(titles(&my_app), vec!["Dashboard", "List Items"]);
+
+        //the palette takes keys before the applet does
+        my_app.handle_event(press(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        my_app.handle_event(press(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(my_app.applets.len(), 2);
+
+        my_app.handle_event(press(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(titles(&my_app), vec!["Dashboard"]);
+        my_app.handle_event(press(KeyCode::Char('q'), KeyModifiers::NONE));
+        assert!(my_app.applets.is_empty());
+    }
+
+    #[test]
+    fn test_worker_routing() {
+        let mut my_app = App::default();
+        my_app.open(AppState::Export);
+        my_app.open(AppState::ListItems);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let mut screen = |my_app: &mut App| {
+            terminal.draw(|frame| my_app.draw(frame)).unwrap();
+            format!("{:?}", terminal.backend().buffer())
         };
+
+        //the result reaches the export applet under the list without closing anything
+        my_app.handle_event(AppEvent::Worker(AppState::Export, Box::new(42_u32)));
+        assert_eq!(
+            titles(&my_app),
+            vec!["Dashboard", "Export to CSV", "List Items"]
+        );
+        assert!(!screen(&mut my_app).contains("background job"));
+
+        //with nobody left to take it, the App says so
+        my_app.handle_event(AppEvent::Worker(AppState::CreateItem, Box::new(42_u32)));
+        assert_eq!(my_app.applets.len(), 3);
+        assert!(screen(&mut my_app).contains("background job"));
+    }
+
+    #[test]
+    fn test_leave_dirty_form() {
+        let mut my_app = App::default();
+        my_app.open(AppState::ListItems);
+        my_app.open(AppState::CreateItem);
+        my_app.handle_event(press(KeyCode::Char('5'), KeyModifiers::NONE));
+        //the breadcrumbs learn the stack when drawn
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| my_app.draw(frame)).unwrap();
+
+        //jumping to the dashboard stops at the form, which asks about its changes first
+        my_app.handle_event(press(KeyCode::Char('h'), KeyModifiers::ALT));
+        assert_eq!(
+            titles(&my_app),
+            vec!["Dashboard", "List Items", "Create Item"]
+        );
+        //discarding the changes finishes the jump
+        my_app.handle_event(press(KeyCode::Left, KeyModifiers::NONE));
+        my_app.handle_event(press(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(titles(&my_app), vec!["Dashboard"]);
+
+        //keeping the changes drops it, so a later discard only closes the form
+        my_app.open(AppState::ListItems);
+        my_app.open(AppState::CreateItem);
+        my_app.handle_event(press(KeyCode::Char('5'), KeyModifiers::NONE));
+        terminal.draw(|frame| my_app.draw(frame)).unwrap();
+        my_app.handle_event(press(KeyCode::Char('h'), KeyModifiers::ALT));
+        my_app.handle_event(press(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            titles(&my_app),
+            vec!["Dashboard", "List Items", "Create Item"]
+        );
+        my_app.handle_event(press(KeyCode::Esc, KeyModifiers::NONE));
+        my_app.handle_event(press(KeyCode::Left, KeyModifiers::NONE));
+        my_app.handle_event(press(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(titles(&my_app), vec!["Dashboard", "List Items"]);
+        my_app.open(AppState::Dashboard);
+
+        //with nothing to lose the jump goes straight through
+        my_app.open(AppState::CreateItem);
+        terminal.draw(|frame| my_app.draw(frame)).unwrap();
+        my_app.handle_event(press(KeyCode::Char('h'), KeyModifiers::ALT));
+        assert_eq!(titles(&my_app), vec!["Dashboard"]);
+
+        //so does picking the dashboard from the palette
+        my_app.open(AppState::CreateItem);
+        my_app.handle_event(press(KeyCode::Char('5'), KeyModifiers::NONE));
+        my_app.handle_event(press(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        for c in "dashboard".chars() {
+            my_app.handle_event(press(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        my_app.handle_event(press(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(titles(&my_app), vec!["Dashboard", "Create Item"]);
+        //keep editing
+        my_app.handle_event(press(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(titles(&my_app), vec!["Dashboard", "Create Item"]);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut my_app = App::default();
+        for id in 0..MAX_DEPTH as i64 + 4 {
+            my_app.open(AppState::EditItem(id));
+        }
+        assert_eq!(my_app.applets.len(), MAX_DEPTH);
+        assert_eq!(my_app.applets[0].0, AppState::Dashboard);
+        assert_eq!(my_app.applets[1].0, AppState::EditItem(5));
+
+        //a form with unsaved changes is passed over for the next oldest applet
+        let mut my_app = App::default();
+        my_app.open(AppState::CreateItem);
+        my_app.handle_event(press(KeyCode::Char('5'), KeyModifiers::NONE));
+        for id in 0..MAX_DEPTH as i64 {
+            my_app.open(AppState::EditItem(id));
+        }
+        assert_eq!(my_app.applets.len(), MAX_DEPTH);
+        assert_eq!(my_app.applets[1].0, AppState::CreateItem);
+        assert_eq!(my_app.applets[2].0, AppState::EditItem(2));
+    }
+
+    #[test]
+    fn test_max_depth_all_dirty() {
+        let mut my_app = App::default();
+        for id in 1..MAX_DEPTH as i64 {
+            my_app.open(AppState::EditItem(id));
+            my_app.handle_event(press(KeyCode::Char('5'), KeyModifiers::NONE));
+        }
+        assert_eq!(my_app.applets.len(), MAX_DEPTH);
+
+        //with nothing that can close, the new applet is refused rather than losing changes
+        my_app.open(AppState::CreateItem);
+        assert_eq!(my_app.applets.len(), MAX_DEPTH);
+        assert_eq!(
+            my_app.applets[MAX_DEPTH - 1].0,
+            AppState::EditItem(MAX_DEPTH as i64 - 1)
+        );
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| my_app.draw(frame)).unwrap();
+        assert!(format!("{:?}", terminal.backend().buffer()).contains("Too many screens"));
+    }
+
+    #[test]
+    fn test_is_fatal() {
+        let my_inv = Inventory::open_in_memory().unwrap();
         let item = db::inventory::Item {
-            id: i + 100,
-            name: format!("item{i}").to_string(),
-            comment: Some(format!("comment{i}").to_string()),
-            location_id: Some(i),
+            id: 1,
+            name: "n".to_string(),
+            comment: None,
+            location_id: None,
         };
-        myapp.db.add_location(&loc)?;
-        myapp.db.add_item(&item)?;
-    }
+        my_inv.add_item(&item).unwrap();
+        let duplicate: Box<dyn std::error::Error> = my_inv.add_item(&item).unwrap_err().into();
+        assert!(!is_fatal(duplicate.as_ref()));
 
-    ratatui::run(|terminal| myapp.run(terminal))
+        let failure = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
+            None,
+        );
+        assert!(is_fatal(&failure));
+        assert!(!is_fatal(&std::fmt::Error));
+    }
 }
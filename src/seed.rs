@@ -0,0 +1,167 @@
+use crate::db::inventory::{Dump, Inventory, ItemRecord, JSON_VERSION, LocationRecord, OnConflict};
+use serde::Deserialize;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Locations and items described in a TOML file, for demo data, scripted setups and tests.
+///
+/// Records take the same fields as in a JSON dump. Only `id` and `name` are required, and
+/// timestamps left out are set to the time the seed is loaded.
+///
+/// ```toml
+/// locations = [
+///     { id = 1, name = "Garage" },
+///     { id = 2, name = "Shelf", parent_id = 1 },
+/// ]
+///
+/// [[items]]
+/// id = 100
+/// name = "M3 screws"
+/// location_id = 2
+/// tags = ["metric"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Seed {
+    pub locations: Vec<LocationRecord>,
+    pub items: Vec<ItemRecord>,
+}
+
+#[derive(Debug)]
+pub struct SeedError {
+    error_text: String,
+}
+
+impl fmt::Display for SeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Seed Error: {}", self.error_text)
+    }
+}
+
+impl error::Error for SeedError {}
+
+impl SeedError {
+    pub fn new(msg: &str) -> Box<Self> {
+        Box::new(SeedError {
+            error_text: msg.to_string(),
+        })
+    }
+}
+
+impl Seed {
+    pub fn parse(text: &str) -> Result<Self, Box<SeedError>> {
+        toml::from_str(text).map_err(|e| SeedError::new(&e.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, Box<SeedError>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| SeedError::new(&format!("Failed to read {}: {e}", path.display())))?;
+        Self::parse(&text)
+            .map_err(|e| SeedError::new(&format!("{}: {}", path.display(), e.error_text)))
+    }
+
+    /// Adds every record in one transaction, skipping any whose ID is already taken so a seed can
+    /// be loaded into the same database every time the program starts.
+    pub fn load(self, db: &Inventory) -> Result<(), Box<dyn error::Error>> {
+        let dump = Dump {
+            version: JSON_VERSION,
+            locations: self.locations,
+            items: self.items,
+        };
+        db.import_dump(&dump, OnConflict::Skip)?;
+        Ok(())
+    }
+}
+
+/// Fixtures shared by the test modules, kept in `fixtures/` next to the demo data.
+#[cfg(test)]
+pub mod fixtures {
+    use super::Seed;
+    use crate::db::inventory::Inventory;
+
+    /// Locations 0 to 4 named `location{i}`, each holding item `100 + i` named `item{i}`, all
+    /// with a `comment{i}`.
+    pub const NUMBERED: &str = include_str!("../fixtures/numbered.toml");
+    /// Garage › Shelf › Bin and an Attic, with two items in the bin, one in the garage and one
+    /// without a location.
+    pub const NESTED: &str = include_str!("../fixtures/nested.toml");
+    /// Locations 0 to 2, with two items in location 1, one in location 2 and one without a
+    /// location.
+    pub const STATS: &str = include_str!("../fixtures/stats.toml");
+    /// Locations and items named banana, Apple and cherry, whose IDs, names and locations each
+    /// sort in a different order.
+    pub const SORTING: &str = include_str!("../fixtures/sorting.toml");
+    /// A few records with commas, quotes and line breaks in their text.
+    pub const QUOTING: &str = include_str!("../fixtures/quoting.toml");
+    /// A Garage holding a Shelf, another Shelf at the top level and one item.
+    pub const SAME_NAMES: &str = include_str!("../fixtures/same_names.toml");
+
+    /// Loads `fixture` into `db`, panicking on any problem.
+    pub fn load(db: &Inventory, fixture: &str) {
+        Seed::parse(fixture).unwrap().load(db).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod seed_tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        let my_seed = Seed::parse(
+            r#"
+            locations = [
+                { id = 1, name = "Garage" },
+                { id = 2, name = "Shelf", parent_id = 1 },
+            ]
+
+            [[items]]
+            id = 100
+            name = "M3 screws"
+            comment = "pan head"
+            location_id = 2
+            tags = ["metric"]
+            "#,
+        )
+        .unwrap();
+        assert!(my_seed.load(&my_inv).is_ok());
+        assert_eq!(my_inv.location_paths().unwrap()[&2], "Garage › Shelf");
+        assert_eq!(my_inv.search_item_id(100).unwrap().location_id, Some(2));
+        assert_eq!(my_inv.all_item_tags().unwrap()[&100], vec!["metric"]);
+        //timestamps left out are set, so seeded records show up as recent changes
+        assert_eq!(my_inv.recent_changes(10).unwrap().len(), 3);
+
+        //loading again leaves the records already there alone
+        let again = Seed::parse("locations = [{ id = 1, name = \"Attic\" }]").unwrap();
+        assert!(again.load(&my_inv).is_ok());
+        assert_eq!(my_inv.search_location_id(1).unwrap().name, "Garage");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Seed::parse("").unwrap().items.is_empty());
+        assert!(Seed::parse("[[items]]\nname = \"no id\"").is_err());
+        assert!(Seed::parse("[[item]]\nid = 1\nname = \"typo\"").is_err());
+        assert!(Seed::from_file(Path::new("no/such/seed.toml")).is_err());
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NUMBERED);
+        assert_eq!(my_inv.stats().unwrap().items, 5);
+
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        assert_eq!(my_inv.location_paths().unwrap()[&3], "Garage › Shelf › Bin");
+
+        let demo = Seed::from_file(Path::new("fixtures/demo.toml")).unwrap();
+        let my_inv = Inventory::open_in_memory().unwrap();
+        assert!(demo.load(&my_inv).is_ok());
+        assert_eq!(my_inv.stats().unwrap().locations, 100);
+        assert_eq!(my_inv.search_item_id(142).unwrap().location_id, Some(42));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::config::{ConfigError, ThemeConfig};
+use ratatui::style::{Color, Style};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// The styles every applet draws with, so colors are picked in one place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    /// Plain text in tables, lists and fields.
+    pub text: Style,
+    /// The focused field or button.
+    pub focus: Style,
+    /// Error messages and fields that failed validation.
+    pub error: Style,
+    /// Messages about an action that did nothing, short of an error.
+    pub warning: Style,
+    pub success: Style,
+    /// Hints and other messages that need nothing done about them.
+    pub info: Style,
+    pub read_only: Style,
+    pub disabled: Style,
+    /// The highlighted entry of a menu.
+    pub selection: Style,
+    pub cell_highlight: Style,
+    pub row_highlight: Style,
+    /// Rows marked for a bulk action.
+    pub marked: Style,
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Installs the theme used by `current`. Only the first call has any effect.
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            text: Style::new().white(),
+            focus: Style::new().yellow(),
+            error: Style::new().red(),
+            warning: Style::new().yellow(),
+            success: Style::new().green(),
+            info: Style::new().blue(),
+            read_only: Style::new().bold(),
+            disabled: Style::new().dim(),
+            selection: Style::new().bold(),
+            cell_highlight: Style::new().red(),
+            row_highlight: Style::new().reversed(),
+            marked: Style::new().cyan().bold(),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            text: Style::new().black(),
+            focus: Style::new().blue(),
+            error: Style::new().red(),
+            warning: Style::new().magenta(),
+            success: Style::new().green(),
+            info: Style::new().cyan(),
+            read_only: Style::new().bold(),
+            disabled: Style::new().dim(),
+            selection: Style::new().bold(),
+            cell_highlight: Style::new().magenta(),
+            row_highlight: Style::new().reversed(),
+            marked: Style::new().blue().bold(),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Style::new().white(),
+            focus: Style::new().black().on_yellow().bold(),
+            error: Style::new().white().on_red().bold(),
+            warning: Style::new().black().on_yellow().bold(),
+            success: Style::new().black().on_green().bold(),
+            info: Style::new().white().on_blue().bold(),
+            read_only: Style::new().white().bold(),
+            disabled: Style::new().dark_gray(),
+            selection: Style::new().black().on_white().bold(),
+            cell_highlight: Style::new().black().on_cyan(),
+            row_highlight: Style::new().reversed().bold(),
+            marked: Style::new().black().on_green(),
+        }
+    }
+
+    /// Uses only text attributes, for `NO_COLOR` and monochrome terminals.
+    pub fn no_color() -> Self {
+        Self {
+            text: Style::new(),
+            focus: Style::new().bold().underlined(),
+            error: Style::new().bold(),
+            warning: Style::new().bold(),
+            success: Style::new(),
+            info: Style::new().italic(),
+            read_only: Style::new().bold(),
+            disabled: Style::new().dim(),
+            selection: Style::new().bold(),
+            cell_highlight: Style::new().underlined(),
+            row_highlight: Style::new().reversed(),
+            marked: Style::new().bold().italic(),
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "no-color" => Some(Self::no_color()),
+            _ => None,
+        }
+    }
+
+    /// Builds the configured theme. When `no_color` is set (see `no_color_requested`) the
+    /// no-color palette is used and color overrides are ignored.
+    pub fn from_config(config: &ThemeConfig, no_color: bool) -> Result<Self, Box<ConfigError>> {
+        let mut theme = Self::by_name(&config.name)
+            .ok_or_else(|| ConfigError::new(&format!("Unknown theme '{}'", config.name)))?;
+        if no_color {
+            return Ok(Self::no_color());
+        }
+        for (name, color) in &config.colors {
+            let color = Color::from_str(color)
+                .map_err(|_| ConfigError::new(&format!("Unknown color '{color}'")))?;
+            let style = match name.as_str() {
+                "text" => &mut theme.text,
+                "focus" => &mut theme.focus,
+                "error" => &mut theme.error,
+                "warning" => &mut theme.warning,
+                "success" => &mut theme.success,
+                "info" => &mut theme.info,
+                "read_only" => &mut theme.read_only,
+                "disabled" => &mut theme.disabled,
+                "selection" => &mut theme.selection,
+                "cell_highlight" => &mut theme.cell_highlight,
+                "row_highlight" => &mut theme.row_highlight,
+                "marked" => &mut theme.marked,
+                _ => return Err(ConfigError::new(&format!("Unknown style '{name}'"))),
+            };
+            *style = style.fg(color);
+        }
+        Ok(theme)
+    }
+}
+
+/// Follows https://no-color.org: any non-empty `NO_COLOR` disables color.
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    #[test]
+    fn test_by_name() {
+        assert_eq!(Theme::by_name("dark"), Some(Theme::dark()));
+        assert_eq!(Theme::by_name("light"), Some(Theme::light()));
+        assert_eq!(
+            Theme::by_name("high-contrast"),
+            Some(Theme::high_contrast())
+        );
+        assert_eq!(Theme::by_name("no-color"), Some(Theme::no_color()));
+        assert_eq!(Theme::by_name("neon"), None);
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = ThemeConfig {
+            name: "light".to_string(),
+            colors: HashMap::from([("focus".to_string(), "green".to_string())]),
+        };
+        let theme = Theme::from_config(&config, false).unwrap();
+        assert_eq!(theme.focus, Style::new().green());
+        assert_eq!(theme.text, Theme::light().text);
+
+        assert_eq!(
+            Theme::from_config(&config, true).unwrap(),
+            Theme::no_color()
+        );
+
+        config
+            .colors
+            .insert("focus".to_string(), "notacolor".to_string());
+        assert!(Theme::from_config(&config, false).is_err());
+        config.colors.clear();
+        config.colors.insert("title".to_string(), "red".to_string());
+        assert!(Theme::from_config(&config, false).is_err());
+        config.colors.clear();
+        config.name = "neon".to_string();
+        assert!(Theme::from_config(&config, false).is_err());
+    }
+
+    #[test]
+    fn test_no_color_has_no_colors() {
+        let theme = Theme::no_color();
+        for style in [
+            theme.text,
+            theme.focus,
+            theme.error,
+            theme.warning,
+            theme.success,
+            theme.info,
+            theme.read_only,
+            theme.disabled,
+            theme.selection,
+            theme.cell_highlight,
+            theme.row_highlight,
+            theme.marked,
+        ] {
+            assert_eq!(style.fg, None);
+            assert_eq!(style.bg, None);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Checks on record fields shared by the forms, the command line and the CSV import, so a value
+//! is accepted or refused the same way wherever it is entered. Each returns the message to show
+//! when the value is refused.
+use crate::db::inventory::Inventory;
+
+/// Refuses a value with nothing but whitespace in it, as in a required field or a name.
+pub fn not_blank(label: &str, value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        return Err(format!("{label} cannot be empty"));
+    }
+    Ok(())
+}
+
+pub fn item_id_unused(value: &str, db: &Inventory) -> Result<(), String> {
+    match value.parse::<i64>() {
+        Ok(id) if db.item_exists(id) => Err("Item ID already exists".to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub fn location_id_unused(value: &str, db: &Inventory) -> Result<(), String> {
+    match value.parse::<i64>() {
+        Ok(id) if db.location_exists(id) => Err("Location ID already exists".to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub fn location_id_exists(value: &str, db: &Inventory) -> Result<(), String> {
+    match value.parse::<i64>() {
+        Ok(id) if !db.location_exists(id) => Err("Location ID does not exist".to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::*;
+    use crate::seed::fixtures;
+
+    #[test]
+    fn test_not_blank() {
+        assert!(not_blank("Name", "tape").is_ok());
+        assert!(not_blank("Name", " tape ").is_ok());
+        assert_eq!(
+            not_blank("Name", " \t "),
+            Err("Name cannot be empty".to_string())
+        );
+        assert!(not_blank("Name", "").is_err());
+    }
+
+    #[test]
+    fn test_ids() {
+        let my_inv = Inventory::open_in_memory().unwrap();
+        fixtures::load(&my_inv, fixtures::NESTED);
+        assert!(item_id_unused("100", &my_inv).is_err());
+        assert!(item_id_unused("200", &my_inv).is_ok());
+        assert!(location_id_unused("1", &my_inv).is_err());
+        assert!(location_id_unused("9", &my_inv).is_ok());
+        assert!(location_id_exists("1", &my_inv).is_ok());
+        assert_eq!(
+            location_id_exists("9", &my_inv),
+            Err("Location ID does not exist".to_string())
+        );
+        //values that are not numbers are left to the parsing
+        assert!(location_id_exists("nine", &my_inv).is_ok());
+    }
+}